@@ -15,8 +15,10 @@ use service::event::EventListener;
 use service::network::endpoints::event::{NetworkServiceEvent, NetworkServiceEventType, NetworkServiceRequest, WiFiConnServiceMessage, WiFiConnServiceRequest, WiFiConnServiceResponse};
 use service::network::wireless::ap::AccessPointSecurity;
 use service::network::{NetworkService};
-use service::niri::NiriService;
+use service::compositor::{self, CompositorService};
+use service::control::{self, ControlRequest, ControlServer};
 use smol::Timer;
+use tracing::{error, warn};
 use tracing_subscriber::EnvFilter;
 
 const APP_ID: &str = "io.github.bigsaltyfishes.molyuubar";
@@ -35,7 +37,39 @@ fn init_logging() {
         .init();
 }
 
+/// Runs `molyuu-bar msg <command>`: sends the command to the running bar and
+/// prints its reply, if any, as JSON.
+fn msg(args: &[String]) -> glib::ExitCode {
+    let request = match ControlRequest::from_args(args) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!(
+                "Usage: molyuu-bar msg toggle-visibility|open-popover <name>|show-message <text> [timeout-ms]|reload-config|dump-state"
+            );
+            return glib::ExitCode::FAILURE;
+        }
+    };
+    let path = control::socket_path(|name| std::env::var(name).ok());
+    match smol::block_on(control::send(&path, &request)) {
+        Ok(serde_json::Value::Null) => glib::ExitCode::SUCCESS,
+        Ok(reply) => {
+            println!("{}", serde_json::to_string_pretty(&reply).unwrap_or_default());
+            glib::ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            glib::ExitCode::FAILURE
+        }
+    }
+}
+
 fn main() -> glib::ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("msg") {
+        return msg(&args[2..]);
+    }
+
     gio::resources_register_include!("icons.gresource").expect("Failed to register resources.");
 
     init_logging();
@@ -60,13 +94,38 @@ fn main() -> glib::ExitCode {
         );
     });
     app.connect_activate(|app| {
-        let mut service = NiriService::new();
-        let taskbar = windows::bar::Taskbar::new(app, &mut service);
+        let Some(compositor) = compositor::current() else {
+            error!("No supported compositor found. Is niri, Sway or Hyprland running?");
+            app.quit();
+            return;
+        };
+        let mut service = CompositorService::new(compositor);
+        let config = windows::config::read();
+        let taskbar = windows::bar::Taskbar::new(app, &mut service, &config);
+
+        // Exported on the session bus with the application, e.g. for
+        // `gapplication action io.github.bigsaltyfishes.molyuubar toggle-visibility`.
+        let visibility = taskbar.visibility().clone();
+        let toggle_visibility = gio::SimpleAction::new("toggle-visibility", None);
+        toggle_visibility.connect_activate(move |_, _| visibility.toggle());
+        app.add_action(&toggle_visibility);
+
+        let control = taskbar.control();
+        match ControlServer::bind(&control::socket_path(|name| std::env::var(name).ok())) {
+            Ok(server) => {
+                glib::spawn_future_local(async move {
+                    server.serve(&control).await;
+                });
+            }
+            Err(e) => warn!("Failed to open the control socket: {}", e),
+        }
+
+        windows::control::serve_session_bus(&taskbar, &mut service);
 
         smol::spawn(async move {
             service.listen().await;
         }).detach();
-        taskbar.export_widget().present();
+        taskbar.present();
     });
     app.run()
 }
\ No newline at end of file
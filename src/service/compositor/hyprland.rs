@@ -0,0 +1,226 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::de::DeserializeOwned;
+use smol::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::unix::UnixStream,
+};
+use tracing::{info, instrument};
+
+use super::{Compositor, CompositorCommand, CompositorError, StatePublisher};
+use crate::service::event::{KeyboardLayouts, WindowInfo, WorkspaceInfo};
+
+#[derive(serde::Deserialize)]
+struct HyprWorkspace {
+    /// Negative for special workspaces.
+    id: i64,
+    name: String,
+    monitor: String,
+    windows: usize,
+}
+
+#[derive(serde::Deserialize)]
+struct WorkspaceRef {
+    id: i64,
+}
+
+#[derive(serde::Deserialize)]
+struct Monitor {
+    focused: bool,
+    #[serde(rename = "activeWorkspace")]
+    active_workspace: WorkspaceRef,
+}
+
+#[derive(serde::Deserialize)]
+struct Client {
+    /// Hex, e.g. `0x55d0c8b2a5e0`.
+    address: String,
+    title: String,
+    class: String,
+    workspace: WorkspaceRef,
+    /// A bool in older versions, the fullscreen mode from 0 to 3 in newer ones.
+    #[serde(default)]
+    fullscreen: serde_json::Value,
+}
+
+#[derive(serde::Deserialize)]
+struct ActiveWindow {
+    address: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Keyboard {
+    /// Comma separated xkb layout codes, e.g. `us,de`.
+    layout: String,
+    #[serde(default)]
+    main: bool,
+    active_layout_index: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+struct Devices {
+    keyboards: Vec<Keyboard>,
+}
+
+/// Parses a window address like `0x55d0c8b2a5e0` into a window id.
+pub fn parse_address(address: &str) -> Option<u64> {
+    u64::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+}
+
+/// Hyprland, through the sockets in its instance directory.
+pub struct HyprlandCompositor {
+    dir: PathBuf,
+}
+
+impl HyprlandCompositor {
+    /// `dir` is the instance directory, `$XDG_RUNTIME_DIR/hypr/$HYPRLAND_INSTANCE_SIGNATURE`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Sends a request to the command socket, which answers one request per connection.
+    async fn request(&self, request: &str) -> Result<String, CompositorError> {
+        let mut stream = UnixStream::connect(self.dir.join(".socket.sock")).await?;
+        stream.write_all(request.as_bytes()).await?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await?;
+        Ok(reply)
+    }
+
+    async fn query<T: DeserializeOwned>(&self, request: &str) -> Result<T, CompositorError> {
+        Ok(serde_json::from_str(&self.request(&format!("j/{}", request)).await?)?)
+    }
+
+    async fn dispatch(&self, command: &str) -> Result<(), CompositorError> {
+        let reply = self.request(command).await?;
+        match reply.trim() {
+            "ok" => Ok(()),
+            error => Err(CompositorError::Rejected(error.to_string())),
+        }
+    }
+}
+
+fn command_string(command: CompositorCommand) -> String {
+    match command {
+        CompositorCommand::FocusWorkspace { id } => format!("dispatch workspace {}", id),
+        CompositorCommand::FocusWorkspaceDown => "dispatch workspace m+1".to_string(),
+        CompositorCommand::FocusWorkspaceUp => "dispatch workspace m-1".to_string(),
+        CompositorCommand::FocusColumnLeft => "dispatch movefocus l".to_string(),
+        CompositorCommand::FocusColumnRight => "dispatch movefocus r".to_string(),
+        CompositorCommand::FocusWindow { id } => format!("dispatch focuswindow address:0x{:x}", id),
+        CompositorCommand::CloseWindow { id } => format!("dispatch closewindow address:0x{:x}", id),
+        CompositorCommand::MoveWindowToWorkspace { window, workspace } => {
+            format!("dispatch movetoworkspacesilent {},address:0x{:x}", workspace, window)
+        }
+        CompositorCommand::SwitchLayoutNext => "switchxkblayout all next".to_string(),
+        CompositorCommand::SwitchLayoutPrevious => "switchxkblayout all prev".to_string(),
+        CompositorCommand::SwitchLayout { index } => format!("switchxkblayout all {}", index),
+    }
+}
+
+#[async_trait::async_trait]
+impl Compositor for HyprlandCompositor {
+    fn name(&self) -> &'static str {
+        "Hyprland"
+    }
+
+    #[instrument(skip_all)]
+    async fn listen(&self, publisher: &mut StatePublisher) -> Result<(), CompositorError> {
+        let stream = UnixStream::connect(self.dir.join(".socket2.sock")).await?;
+        let mut reader = BufReader::new(stream);
+        info!("Hyprland is ready to handle events");
+
+        self.refresh(publisher).await?;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(CompositorError::Protocol("Hyprland closed the event socket".to_string()));
+            }
+            // Events come in bursts, e.g. `openwindow` followed by `activewindow`.
+            // Query the state once the burst has been read.
+            if reader.buffer().contains(&b'\n') {
+                continue;
+            }
+            self.refresh(publisher).await?;
+        }
+    }
+
+    async fn execute(&self, command: CompositorCommand) -> Result<(), CompositorError> {
+        self.dispatch(&command_string(command)).await
+    }
+
+    async fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, CompositorError> {
+        let workspaces: Vec<HyprWorkspace> = self.query("workspaces").await?;
+        let monitors: Vec<Monitor> = self.query("monitors").await?;
+        let active: Vec<i64> = monitors.iter().map(|monitor| monitor.active_workspace.id).collect();
+        let focused = monitors
+            .iter()
+            .find(|monitor| monitor.focused)
+            .map(|monitor| monitor.active_workspace.id);
+
+        // Special workspaces are toggled over the others, they get no button.
+        let mut workspaces: Vec<HyprWorkspace> = workspaces.into_iter().filter(|w| w.id > 0).collect();
+        workspaces.sort_by(|a, b| (&a.monitor, a.id).cmp(&(&b.monitor, b.id)));
+        let mut positions: HashMap<String, u8> = HashMap::new();
+        Ok(workspaces
+            .into_iter()
+            .map(|workspace| {
+                let idx = positions.entry(workspace.monitor.clone()).or_insert(0);
+                *idx = idx.saturating_add(1);
+                WorkspaceInfo {
+                    id: workspace.id as u64,
+                    idx: *idx,
+                    name: Some(workspace.name),
+                    output: Some(workspace.monitor),
+                    is_active: active.contains(&workspace.id),
+                    is_focused: Some(workspace.id) == focused,
+                    windows: workspace.windows,
+                }
+            })
+            .collect())
+    }
+
+    async fn windows(&self) -> Result<Vec<WindowInfo>, CompositorError> {
+        let clients: Vec<Client> = self.query("clients").await?;
+        // Without a focused window Hyprland answers `{}`.
+        let active: ActiveWindow = self.query("activewindow").await?;
+        let focused = active.address.as_deref().and_then(parse_address);
+        Ok(clients
+            .into_iter()
+            .filter_map(|client| {
+                let id = parse_address(&client.address)?;
+                Some(WindowInfo {
+                    id,
+                    title: Some(client.title),
+                    app_id: Some(client.class).filter(|class| !class.is_empty()),
+                    workspace_id: u64::try_from(client.workspace.id).ok().filter(|id| *id > 0),
+                    is_focused: Some(id) == focused,
+                    is_fullscreen: client.fullscreen.as_bool().unwrap_or(false)
+                        || client.fullscreen.as_u64().is_some_and(|mode| mode != 0),
+                })
+            })
+            .collect())
+    }
+
+    /// Hyprland only reports layout codes, so the names are codes like `us`.
+    async fn keyboard_layouts(&self) -> Result<KeyboardLayouts, CompositorError> {
+        let devices: Devices = self.query("devices").await?;
+        let keyboard = devices
+            .keyboards
+            .iter()
+            .find(|keyboard| keyboard.main)
+            .or_else(|| devices.keyboards.first());
+        Ok(keyboard
+            .map(|keyboard| KeyboardLayouts {
+                names: keyboard
+                    .layout
+                    .split(',')
+                    .map(|code| code.trim().to_string())
+                    .filter(|code| !code.is_empty())
+                    .collect(),
+                current: keyboard.active_layout_index.unwrap_or(0),
+            })
+            .unwrap_or_default())
+    }
+}
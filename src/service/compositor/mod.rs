@@ -0,0 +1,309 @@
+pub mod hyprland;
+pub mod niri;
+pub mod sway;
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    io,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use tracing::{error, info, warn};
+
+use super::event::{EventBus, EventListener, KeyboardLayouts, UIUpdateEvent, UIUpdateEventType, WindowInfo, WorkspaceInfo};
+
+/// Something a widget asks the compositor to do.
+///
+/// Ids are the ones reported in `WorkspaceInfo` and `WindowInfo`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompositorCommand {
+    FocusWorkspace { id: u64 },
+    /// The workspace below, or the next one on compositors without vertical workspaces.
+    FocusWorkspaceDown,
+    /// The workspace above, or the previous one on compositors without vertical workspaces.
+    FocusWorkspaceUp,
+    FocusColumnLeft,
+    FocusColumnRight,
+    FocusWindow { id: u64 },
+    CloseWindow { id: u64 },
+    MoveWindowToWorkspace { window: u64, workspace: u64 },
+    SwitchLayoutNext,
+    SwitchLayoutPrevious,
+    /// Index into `KeyboardLayouts::names`.
+    SwitchLayout { index: usize },
+}
+
+/// Why talking to the compositor failed.
+#[derive(Debug)]
+pub enum CompositorError {
+    Niri(niri::client::NiriError),
+    /// Connecting to or talking over the IPC socket failed.
+    Io(io::Error),
+    /// The compositor sent something that could not be parsed.
+    Protocol(String),
+    /// The compositor rejected a command, with its error message.
+    Rejected(String),
+    /// The compositor has no equivalent of the command.
+    Unsupported(CompositorCommand),
+}
+
+impl Display for CompositorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompositorError::Niri(e) => e.fmt(f),
+            CompositorError::Io(e) => write!(f, "compositor socket error: {}", e),
+            CompositorError::Protocol(message) => write!(f, "invalid reply from the compositor: {}", message),
+            CompositorError::Rejected(message) => write!(f, "compositor: {}", message),
+            CompositorError::Unsupported(command) => write!(f, "not supported by the compositor: {:?}", command),
+        }
+    }
+}
+
+impl std::error::Error for CompositorError {}
+
+impl From<niri::client::NiriError> for CompositorError {
+    fn from(e: niri::client::NiriError) -> Self {
+        CompositorError::Niri(e)
+    }
+}
+
+impl From<io::Error> for CompositorError {
+    fn from(e: io::Error) -> Self {
+        CompositorError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for CompositorError {
+    fn from(e: serde_json::Error) -> Self {
+        CompositorError::Protocol(e.to_string())
+    }
+}
+
+/// A window manager the bar can show and control.
+#[async_trait::async_trait]
+pub trait Compositor: Send + Sync {
+    /// Shown in place of the app name while no window is focused.
+    fn name(&self) -> &'static str;
+
+    /// Follows the compositor's state, publishing changes to `publisher`, until
+    /// the connection to the compositor is lost.
+    async fn listen(&self, publisher: &mut StatePublisher) -> Result<(), CompositorError>;
+
+    async fn execute(&self, command: CompositorCommand) -> Result<(), CompositorError>;
+
+    /// All workspaces, in the order they are shown, with their window counts.
+    async fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, CompositorError>;
+
+    async fn windows(&self) -> Result<Vec<WindowInfo>, CompositorError>;
+
+    async fn keyboard_layouts(&self) -> Result<KeyboardLayouts, CompositorError>;
+
+    /// Queries the complete state and publishes what changed.
+    async fn refresh(&self, publisher: &mut StatePublisher) -> Result<(), CompositorError> {
+        publisher.update_workspaces(self.workspaces().await?);
+        publisher.update_windows(self.windows().await?);
+        match self.keyboard_layouts().await {
+            Ok(layouts) => publisher.update_keyboard_layouts(layouts),
+            Err(e) => warn!("Failed to read keyboard layouts: {}", e),
+        }
+        Ok(())
+    }
+}
+
+/// Turns full snapshots of the compositor state into `UIUpdateEvent`s,
+/// publishing only what changed since the last snapshot.
+pub struct StatePublisher {
+    name: &'static str,
+    events: EventBus<UIUpdateEventType, UIUpdateEvent>,
+    workspaces: Option<Vec<WorkspaceInfo>>,
+    windows: HashMap<u64, WindowInfo>,
+    /// App id and title last sent in `WindowFocusChanged`.
+    focused: Option<(Option<String>, Option<String>)>,
+    keyboard_layouts: Option<KeyboardLayouts>,
+}
+
+impl StatePublisher {
+    /// `name` is shown as the app id while no window is focused.
+    pub fn new(name: &'static str, events: EventBus<UIUpdateEventType, UIUpdateEvent>) -> Self {
+        Self {
+            name,
+            events,
+            workspaces: None,
+            windows: HashMap::new(),
+            focused: None,
+            keyboard_layouts: None,
+        }
+    }
+
+    pub fn update_workspaces(&mut self, workspaces: Vec<WorkspaceInfo>) {
+        if self.workspaces.as_ref() == Some(&workspaces) {
+            return;
+        }
+        self.workspaces = Some(workspaces.clone());
+        self.events.publish(
+            UIUpdateEventType::WorkspaceChanged,
+            UIUpdateEvent::WorkspaceChanged { workspaces },
+        );
+    }
+
+    pub fn update_windows(&mut self, windows: Vec<WindowInfo>) {
+        let windows: HashMap<u64, WindowInfo> = windows.into_iter().map(|w| (w.id, w)).collect();
+        for (event_type, event) in diff_windows(&self.windows, &windows) {
+            self.events.publish(event_type, event);
+        }
+
+        let focused = match windows.values().find(|w| w.is_focused) {
+            Some(window) => (window.app_id.clone(), window.title.clone()),
+            None => (Some(self.name.to_string()), Some("Desktop".to_string())),
+        };
+        if self.focused.as_ref() != Some(&focused) {
+            self.focused = Some(focused.clone());
+            let (app_id, title) = focused;
+            self.events.publish(
+                UIUpdateEventType::WindowFocusChanged,
+                UIUpdateEvent::WindowFocusChanged { app_id, title },
+            );
+        }
+        self.windows = windows;
+    }
+
+    pub fn update_keyboard_layouts(&mut self, layouts: KeyboardLayouts) {
+        if self.keyboard_layouts.as_ref() == Some(&layouts) {
+            return;
+        }
+        self.keyboard_layouts = Some(layouts.clone());
+        self.events.publish(
+            UIUpdateEventType::KeyboardLayoutChanged,
+            UIUpdateEvent::KeyboardLayoutChanged { layouts },
+        );
+    }
+}
+
+/// Returns the events turning the window list `before` into `after`:
+/// closed windows first, then opened and changed ones, each ordered by id.
+pub fn diff_windows(
+    before: &HashMap<u64, WindowInfo>,
+    after: &HashMap<u64, WindowInfo>,
+) -> Vec<(UIUpdateEventType, UIUpdateEvent)> {
+    let mut closed: Vec<u64> = before.keys().filter(|id| !after.contains_key(id)).copied().collect();
+    closed.sort_unstable();
+    let mut current: Vec<&WindowInfo> = after.values().collect();
+    current.sort_unstable_by_key(|window| window.id);
+
+    let closed = closed
+        .into_iter()
+        .map(|id| (UIUpdateEventType::WindowClosed, UIUpdateEvent::WindowClosed { id }));
+    let opened_or_changed = current.into_iter().filter_map(|window| match before.get(&window.id) {
+        None => Some((
+            UIUpdateEventType::WindowOpened,
+            UIUpdateEvent::WindowOpened { window: window.clone() },
+        )),
+        Some(previous) if previous != window => Some((
+            UIUpdateEventType::WindowChanged,
+            UIUpdateEvent::WindowChanged { window: window.clone() },
+        )),
+        Some(_) => None,
+    });
+    closed.chain(opened_or_changed).collect()
+}
+
+/// A compositor found in the environment, with the socket to reach it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompositorKind {
+    Niri(PathBuf),
+    /// The instance directory holding `.socket.sock` and `.socket2.sock`.
+    Hyprland(PathBuf),
+    Sway(PathBuf),
+}
+
+impl CompositorKind {
+    /// Picks the compositor from the variables it sets for its clients, checking
+    /// `NIRI_SOCKET`, `HYPRLAND_INSTANCE_SIGNATURE`, `SWAYSOCK` and `I3SOCK` in that order.
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        if let Some(socket) = var("NIRI_SOCKET") {
+            return Some(CompositorKind::Niri(socket.into()));
+        }
+        if let Some(signature) = var("HYPRLAND_INSTANCE_SIGNATURE") {
+            // Hyprland moved its sockets from /tmp into the runtime directory.
+            let runtime = var("XDG_RUNTIME_DIR")
+                .map(|dir| PathBuf::from(dir).join("hypr").join(&signature))
+                .filter(|dir| dir.exists());
+            let dir = runtime.unwrap_or_else(|| PathBuf::from("/tmp/hypr").join(&signature));
+            return Some(CompositorKind::Hyprland(dir));
+        }
+        var("SWAYSOCK")
+            .or_else(|| var("I3SOCK"))
+            .map(|socket| CompositorKind::Sway(socket.into()))
+    }
+
+    pub fn connect(self) -> Arc<dyn Compositor> {
+        match self {
+            CompositorKind::Niri(socket) => Arc::new(niri::NiriCompositor::new(socket)),
+            CompositorKind::Hyprland(dir) => Arc::new(hyprland::HyprlandCompositor::new(dir)),
+            CompositorKind::Sway(socket) => Arc::new(sway::SwayCompositor::new(socket)),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref COMPOSITOR: Option<Arc<dyn Compositor>> = {
+        let kind = CompositorKind::detect(|name| std::env::var(name).ok());
+        info!("Detected compositor: {:?}", kind);
+        kind.map(CompositorKind::connect)
+    };
+}
+
+/// Returns the compositor the bar runs on, `None` if none was detected.
+pub fn current() -> Option<Arc<dyn Compositor>> {
+    COMPOSITOR.clone()
+}
+
+/// Executes `command` in the background, logging if it fails.
+pub fn dispatch(command: CompositorCommand) {
+    let Some(compositor) = current() else {
+        warn!("No compositor to execute {:?} on", command);
+        return;
+    };
+    smol::spawn(async move {
+        if let Err(e) = compositor.execute(command).await {
+            warn!("Failed to execute compositor command: {}", e);
+        }
+    })
+    .detach();
+}
+
+/// Publishes the state of a compositor for the bar's widgets.
+pub struct CompositorService {
+    compositor: Arc<dyn Compositor>,
+    events: EventBus<UIUpdateEventType, UIUpdateEvent>,
+}
+
+impl CompositorService {
+    pub fn new(compositor: Arc<dyn Compositor>) -> Self {
+        Self {
+            compositor,
+            // Each of these describes a single window, so the last one says little.
+            events: EventBus::new().without_replay([
+                UIUpdateEventType::WindowOpened,
+                UIUpdateEventType::WindowChanged,
+                UIUpdateEventType::WindowClosed,
+            ]),
+        }
+    }
+
+    pub async fn listen(&self) {
+        let mut publisher = StatePublisher::new(self.compositor.name(), self.events.clone());
+        if let Err(e) = self.compositor.listen(&mut publisher).await {
+            error!("Lost connection to {}: {}", self.compositor.name(), e);
+        }
+    }
+}
+
+impl EventListener<UIUpdateEventType, UIUpdateEvent> for CompositorService {
+    fn event_bus(&self) -> &EventBus<UIUpdateEventType, UIUpdateEvent> {
+        &self.events
+    }
+}
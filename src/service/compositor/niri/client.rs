@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    io,
+    net::Shutdown,
+    path::PathBuf,
+};
+
+use niri_ipc::{Action, KeyboardLayouts, Output, Reply, Request, Response, Window, Workspace};
+use smol::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::unix::UnixStream,
+};
+
+/// Why a niri request failed.
+#[derive(Debug)]
+pub enum NiriError {
+    /// `NIRI_SOCKET` is not set.
+    NoSocket,
+    /// Connecting to or talking over the socket failed.
+    Io(io::Error),
+    /// niri sent a reply that could not be parsed.
+    Protocol(serde_json::Error),
+    /// niri rejected the request, with its error message.
+    Niri(String),
+    /// niri answered with a response of the wrong kind.
+    UnexpectedResponse(Box<Response>),
+    /// niri closed the connection without replying.
+    Disconnected,
+}
+
+impl Display for NiriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NiriError::NoSocket => write!(f, "NIRI_SOCKET not set. Is niri running?"),
+            NiriError::Io(e) => write!(f, "niri socket error: {}", e),
+            NiriError::Protocol(e) => write!(f, "invalid reply from niri: {}", e),
+            NiriError::Niri(message) => write!(f, "niri: {}", message),
+            NiriError::UnexpectedResponse(response) => write!(f, "unexpected response from niri: {:?}", response),
+            NiriError::Disconnected => write!(f, "niri closed the connection"),
+        }
+    }
+}
+
+impl std::error::Error for NiriError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NiriError::Io(e) => Some(e),
+            NiriError::Protocol(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NiriError {
+    fn from(e: io::Error) -> Self {
+        NiriError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for NiriError {
+    fn from(e: serde_json::Error) -> Self {
+        NiriError::Protocol(e)
+    }
+}
+
+/// A client for niri's command socket.
+///
+/// niri answers a single request per connection and then closes it, so every
+/// request opens a connection of its own. Requests from several tasks therefore
+/// never wait for each other's replies.
+pub struct NiriClient {
+    path: Option<PathBuf>,
+}
+
+impl NiriClient {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+        }
+    }
+
+    /// Uses the socket in `NIRI_SOCKET`.
+    pub fn from_env() -> Self {
+        Self {
+            path: std::env::var_os("NIRI_SOCKET").map(PathBuf::from),
+        }
+    }
+
+    /// Sends a request and waits for its reply.
+    pub async fn request(&self, request: Request) -> Result<Response, NiriError> {
+        let path = self.path.as_ref().ok_or(NiriError::NoSocket)?;
+        let mut line = serde_json::to_string(&request)?;
+        line.push('\n');
+
+        let mut stream = UnixStream::connect(path).await?;
+        stream.write_all(line.as_bytes()).await?;
+        stream.shutdown(Shutdown::Write)?;
+
+        let mut reply = String::new();
+        if BufReader::new(stream).read_line(&mut reply).await? == 0 {
+            return Err(NiriError::Disconnected);
+        }
+        match serde_json::from_str::<Reply>(&reply)? {
+            Ok(response) => Ok(response),
+            Err(message) => Err(NiriError::Niri(message)),
+        }
+    }
+
+    /// Performs an action.
+    pub async fn action(&self, action: Action) -> Result<(), NiriError> {
+        match self.request(Request::Action(action)).await? {
+            Response::Handled => Ok(()),
+            other => Err(NiriError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+
+    pub async fn workspaces(&self) -> Result<Vec<Workspace>, NiriError> {
+        match self.request(Request::Workspaces).await? {
+            Response::Workspaces(workspaces) => Ok(workspaces),
+            other => Err(NiriError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+
+    pub async fn windows(&self) -> Result<Vec<Window>, NiriError> {
+        match self.request(Request::Windows).await? {
+            Response::Windows(windows) => Ok(windows),
+            other => Err(NiriError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+
+    /// Returns the connected outputs by connector name.
+    pub async fn outputs(&self) -> Result<HashMap<String, Output>, NiriError> {
+        match self.request(Request::Outputs).await? {
+            Response::Outputs(outputs) => Ok(outputs),
+            other => Err(NiriError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+
+    pub async fn focused_window(&self) -> Result<Option<Window>, NiriError> {
+        match self.request(Request::FocusedWindow).await? {
+            Response::FocusedWindow(window) => Ok(window),
+            other => Err(NiriError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+
+    pub async fn keyboard_layouts(&self) -> Result<KeyboardLayouts, NiriError> {
+        match self.request(Request::KeyboardLayouts).await? {
+            Response::KeyboardLayouts(layouts) => Ok(layouts),
+            other => Err(NiriError::UnexpectedResponse(Box::new(other))),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+pub mod client;
+
+use std::{collections::HashMap, net::Shutdown, path::PathBuf};
+
+use client::{NiriClient, NiriError};
+use niri_ipc::{Action, Event, LayoutSwitchTarget, Reply, Request, Window, Workspace, WorkspaceReferenceArg};
+use smol::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::unix::UnixStream,
+};
+use tracing::{info, instrument, warn};
+
+use super::{Compositor, CompositorCommand, CompositorError, StatePublisher};
+use crate::service::event::{KeyboardLayouts, WindowInfo, WorkspaceInfo};
+
+pub struct NiriWorkspaces {
+    workspaces: HashMap<u64, Workspace>,
+    focused: Option<u64>,
+}
+
+impl NiriWorkspaces {
+    pub fn new() -> Self {
+        NiriWorkspaces {
+            workspaces: HashMap::new(),
+            focused: None,
+        }
+    }
+
+    pub fn update_all(&mut self, workspaces: Vec<Workspace>) {
+        self.focused = workspaces.iter().find(|w| w.is_focused).map(|w| w.id);
+        self.workspaces = workspaces.into_iter().map(|w| (w.id, w)).collect();
+    }
+
+    pub fn get_workspace_by_id(&self, id: u64) -> Option<Workspace> {
+        self.workspaces.get(&id).cloned()
+    }
+
+    pub fn set_focused(&mut self, id: u64) {
+        if let Some(workspace) = self.focused.and_then(|focused| self.workspaces.get_mut(&focused)) {
+            workspace.is_focused = false;
+        }
+        if let Some(workspace) = self.workspaces.get_mut(&id) {
+            workspace.is_focused = true;
+        }
+        self.focused = Some(id);
+    }
+
+    /// Makes `id` the workspace shown on its output.
+    pub fn set_active(&mut self, id: u64) {
+        let Some(output) = self.workspaces.get(&id).map(|workspace| workspace.output.clone()) else {
+            return;
+        };
+        for workspace in self.workspaces.values_mut().filter(|workspace| workspace.output == output) {
+            workspace.is_active = workspace.id == id;
+        }
+    }
+
+    pub fn set_active_window(&mut self, id: u64, active_window_id: Option<u64>) {
+        if let Some(workspace) = self.workspaces.get_mut(&id) {
+            workspace.active_window_id = active_window_id;
+        }
+    }
+
+    pub fn get_focused(&self) -> Option<Workspace> {
+        self.workspaces.get(&self.focused?).cloned()
+    }
+
+    pub fn num_workspaces(&self) -> usize {
+        self.workspaces.len()
+    }
+
+    /// Returns all workspaces in the order they are shown, by output and index,
+    /// with the number of `windows` on each.
+    pub fn list(&self, windows: &NiriWindows) -> Vec<WorkspaceInfo> {
+        let counts = windows.count_by_workspace();
+        let mut workspaces: Vec<WorkspaceInfo> = self
+            .workspaces
+            .values()
+            .map(|workspace| WorkspaceInfo {
+                id: workspace.id,
+                idx: workspace.idx,
+                name: workspace.name.clone(),
+                output: workspace.output.clone(),
+                is_active: workspace.is_active,
+                is_focused: workspace.is_focused,
+                windows: counts.get(&workspace.id).copied().unwrap_or(0),
+            })
+            .collect();
+        workspaces.sort_by(|a, b| (&a.output, a.idx).cmp(&(&b.output, b.idx)));
+        workspaces
+    }
+}
+
+pub struct NiriWindows {
+    windows: HashMap<u64, Window>,
+    focused: u64,
+}
+
+impl NiriWindows {
+    pub fn new() -> Self {
+        NiriWindows {
+            windows: HashMap::new(),
+            focused: 0,
+        }
+    }
+
+    pub fn update_all(&mut self, windows: Vec<Window>) {
+        // The list is complete, windows missing from it are gone.
+        self.windows.clear();
+        for window in windows {
+            if window.is_focused {
+                self.focused = window.id;
+            }
+            self.windows.insert(window.id, window);
+        }
+    }
+
+    pub fn add_window(&mut self, window: Window) {
+        self.windows.insert(window.id, window);
+    }
+
+    pub fn remove_window(&mut self, id: u64) {
+        self.windows.remove(&id);
+        if id == self.focused {
+            self.focused = 0;
+        }
+    }
+
+    pub fn set_focused(&mut self, id: Option<u64>) {
+        let id = id.unwrap_or(0);
+        if let Some(window) = self.windows.get_mut(&self.focused) {
+            window.is_focused = false;
+        }
+        if let Some(window) = self.windows.get_mut(&id) {
+            window.is_focused = true;
+        }
+        self.focused = id;
+    }
+
+    pub fn get_focused(&self) -> Option<Window> {
+        self.windows.get(&self.focused).cloned()
+    }
+
+    /// Returns every window by id.
+    pub fn infos(&self) -> HashMap<u64, WindowInfo> {
+        self.windows
+            .values()
+            .map(|window| {
+                let info = WindowInfo {
+                    id: window.id,
+                    title: window.title.clone(),
+                    app_id: window.app_id.clone(),
+                    workspace_id: window.workspace_id,
+                    is_focused: window.is_focused,
+                    // Not reported by niri, see `WindowInfo::is_fullscreen`.
+                    is_fullscreen: false,
+                };
+                (window.id, info)
+            })
+            .collect()
+    }
+
+    /// Returns the number of windows on each workspace by workspace id.
+    pub fn count_by_workspace(&self) -> HashMap<u64, usize> {
+        let mut counts = HashMap::new();
+        for workspace_id in self.windows.values().filter_map(|window| window.workspace_id) {
+            *counts.entry(workspace_id).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+/// The state niri reported on its event stream so far.
+struct NiriState {
+    workspaces: NiriWorkspaces,
+    windows: NiriWindows,
+    keyboard_layouts: Option<KeyboardLayouts>,
+}
+
+impl NiriState {
+    fn apply(&mut self, event: Event) {
+        match event {
+            Event::WorkspacesChanged { workspaces } => self.workspaces.update_all(workspaces),
+            Event::WorkspaceActivated { id, focused } => {
+                self.workspaces.set_active(id);
+                if focused {
+                    self.workspaces.set_focused(id);
+                }
+            }
+            Event::WorkspaceActiveWindowChanged {
+                workspace_id,
+                active_window_id,
+            } => self.workspaces.set_active_window(workspace_id, active_window_id),
+            Event::WindowsChanged { windows } => self.windows.update_all(windows),
+            Event::WindowClosed { id } => self.windows.remove_window(id),
+            Event::WindowOpenedOrChanged { window } => {
+                // niri only reports the newly focused window, unfocus the old one.
+                if window.is_focused {
+                    self.windows.set_focused(Some(window.id));
+                }
+                self.windows.add_window(window);
+            }
+            Event::WindowFocusChanged { id } => self.windows.set_focused(id),
+            Event::KeyboardLayoutsChanged { keyboard_layouts } => {
+                self.keyboard_layouts = Some(convert_layouts(keyboard_layouts));
+            }
+            Event::KeyboardLayoutSwitched { idx } => {
+                if let Some(layouts) = &mut self.keyboard_layouts {
+                    layouts.current = idx as usize;
+                }
+            }
+            _ => {
+                warn!("Unhandled event: {:?}", event);
+            }
+        }
+    }
+}
+
+fn convert_layouts(layouts: niri_ipc::KeyboardLayouts) -> KeyboardLayouts {
+    KeyboardLayouts {
+        names: layouts.names,
+        current: layouts.current_idx as usize,
+    }
+}
+
+fn niri_action(command: CompositorCommand) -> Result<Action, CompositorError> {
+    let action = match command {
+        CompositorCommand::FocusWorkspace { id } => Action::FocusWorkspace {
+            reference: WorkspaceReferenceArg::Id(id),
+        },
+        CompositorCommand::FocusWorkspaceDown => Action::FocusWorkspaceDown {},
+        CompositorCommand::FocusWorkspaceUp => Action::FocusWorkspaceUp {},
+        CompositorCommand::FocusColumnLeft => Action::FocusColumnLeft {},
+        CompositorCommand::FocusColumnRight => Action::FocusColumnRight {},
+        CompositorCommand::FocusWindow { id } => Action::FocusWindow { id },
+        CompositorCommand::CloseWindow { id } => Action::CloseWindow { id: Some(id) },
+        CompositorCommand::MoveWindowToWorkspace { window, workspace } => Action::MoveWindowToWorkspace {
+            window_id: Some(window),
+            reference: WorkspaceReferenceArg::Id(workspace),
+            focus: false,
+        },
+        CompositorCommand::SwitchLayoutNext => Action::SwitchLayout {
+            layout: LayoutSwitchTarget::Next,
+        },
+        CompositorCommand::SwitchLayoutPrevious => Action::SwitchLayout {
+            layout: LayoutSwitchTarget::Prev,
+        },
+        CompositorCommand::SwitchLayout { index } => match u8::try_from(index) {
+            Ok(index) => Action::SwitchLayout {
+                layout: LayoutSwitchTarget::Index(index),
+            },
+            Err(_) => return Err(CompositorError::Unsupported(command)),
+        },
+    };
+    Ok(action)
+}
+
+/// niri, through its JSON socket.
+pub struct NiriCompositor {
+    socket: PathBuf,
+    client: NiriClient,
+}
+
+impl NiriCompositor {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        let socket = socket.into();
+        Self {
+            client: NiriClient::new(socket.clone()),
+            socket,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Compositor for NiriCompositor {
+    fn name(&self) -> &'static str {
+        "Niri"
+    }
+
+    #[instrument(skip_all)]
+    async fn listen(&self, publisher: &mut StatePublisher) -> Result<(), CompositorError> {
+        // The event stream needs a connection of its own, niri sends nothing but
+        // events on it once the request is answered.
+        let mut stream = UnixStream::connect(&self.socket).await?;
+        let mut request = serde_json::to_string(&Request::EventStream)?;
+        request.push('\n');
+        stream.write_all(request.as_bytes()).await?;
+        stream.shutdown(Shutdown::Write)?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+        if let Err(message) = serde_json::from_str::<Reply>(&line)? {
+            return Err(NiriError::Niri(message).into());
+        }
+        info!("Niri is ready to handle events");
+
+        let mut state = NiriState {
+            workspaces: NiriWorkspaces::new(),
+            windows: NiriWindows::new(),
+            keyboard_layouts: None,
+        };
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(NiriError::Disconnected.into());
+            }
+            match serde_json::from_str::<Event>(&line) {
+                Ok(event) => state.apply(event),
+                // Newer niri versions send events this version does not know.
+                Err(e) => {
+                    warn!("Failed to parse niri event: {}", e);
+                    continue;
+                }
+            }
+            publisher.update_workspaces(state.workspaces.list(&state.windows));
+            publisher.update_windows(state.windows.infos().into_values().collect());
+            if let Some(layouts) = &state.keyboard_layouts {
+                publisher.update_keyboard_layouts(layouts.clone());
+            }
+        }
+    }
+
+    async fn execute(&self, command: CompositorCommand) -> Result<(), CompositorError> {
+        Ok(self.client.action(niri_action(command)?).await?)
+    }
+
+    async fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, CompositorError> {
+        let mut workspaces = NiriWorkspaces::new();
+        workspaces.update_all(self.client.workspaces().await?);
+        let mut windows = NiriWindows::new();
+        windows.update_all(self.client.windows().await?);
+        Ok(workspaces.list(&windows))
+    }
+
+    async fn windows(&self) -> Result<Vec<WindowInfo>, CompositorError> {
+        let mut windows = NiriWindows::new();
+        windows.update_all(self.client.windows().await?);
+        let mut infos: Vec<WindowInfo> = windows.infos().into_values().collect();
+        infos.sort_unstable_by_key(|window| window.id);
+        Ok(infos)
+    }
+
+    async fn keyboard_layouts(&self) -> Result<KeyboardLayouts, CompositorError> {
+        Ok(convert_layouts(self.client.keyboard_layouts().await?))
+    }
+}
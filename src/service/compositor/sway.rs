@@ -0,0 +1,295 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::de::DeserializeOwned;
+use smol::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::unix::UnixStream,
+};
+use tracing::{info, instrument, warn};
+
+use super::{Compositor, CompositorCommand, CompositorError, StatePublisher};
+use crate::service::event::{KeyboardLayouts, WindowInfo, WorkspaceInfo};
+
+/// Every i3 IPC message starts with this, followed by the payload length and
+/// message type as native endian `u32`s.
+pub const MAGIC: &[u8; 6] = b"i3-ipc";
+pub const RUN_COMMAND: u32 = 0;
+pub const GET_WORKSPACES: u32 = 1;
+pub const SUBSCRIBE: u32 = 2;
+pub const GET_TREE: u32 = 4;
+pub const GET_INPUTS: u32 = 100;
+/// Set in the type of event messages.
+pub const EVENT: u32 = 1 << 31;
+
+/// Writes one i3 IPC message.
+pub async fn write_message(stream: &mut UnixStream, kind: u32, payload: &[u8]) -> std::io::Result<()> {
+    let mut message = Vec::with_capacity(MAGIC.len() + 8 + payload.len());
+    message.extend_from_slice(MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload);
+    stream.write_all(&message).await
+}
+
+/// Reads one i3 IPC message, returning its type and payload.
+pub async fn read_message(stream: &mut UnixStream) -> Result<(u32, Vec<u8>), CompositorError> {
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header).await?;
+    if &header[..6] != MAGIC {
+        return Err(CompositorError::Protocol("message without i3-ipc magic".to_string()));
+    }
+    let length = u32::from_ne_bytes(header[6..10].try_into().expect("Slice has 4 bytes"));
+    let kind = u32::from_ne_bytes(header[10..14].try_into().expect("Slice has 4 bytes"));
+    let mut payload = vec![0u8; length as usize];
+    stream.read_exact(&mut payload).await?;
+    Ok((kind, payload))
+}
+
+#[derive(serde::Deserialize)]
+struct SwayWorkspace {
+    id: u64,
+    name: String,
+    output: String,
+    visible: bool,
+    focused: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct WindowProperties {
+    class: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Node {
+    id: u64,
+    #[serde(rename = "type")]
+    kind: String,
+    name: Option<String>,
+    #[serde(default)]
+    focused: bool,
+    /// 0 when not fullscreen, 1 for fullscreen on the output, 2 for global fullscreen.
+    #[serde(default)]
+    fullscreen_mode: u8,
+    /// Set for Wayland clients.
+    app_id: Option<String>,
+    /// Set for XWayland clients.
+    window_properties: Option<WindowProperties>,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    floating_nodes: Vec<Node>,
+}
+
+impl Node {
+    fn is_window(&self) -> bool {
+        matches!(self.kind.as_str(), "con" | "floating_con")
+            && self.nodes.is_empty()
+            && (self.app_id.is_some() || self.window_properties.is_some())
+    }
+
+    /// Appends the windows below this node, `workspace` being the workspace it is on.
+    fn collect_windows(&self, workspace: Option<u64>, windows: &mut Vec<WindowInfo>) {
+        // The scratchpad is a hidden workspace, its windows are on no workspace.
+        let workspace = match self.kind.as_str() {
+            "workspace" if self.name.as_deref() == Some("__i3_scratch") => None,
+            "workspace" => Some(self.id),
+            _ => workspace,
+        };
+        if self.is_window() {
+            windows.push(WindowInfo {
+                id: self.id,
+                title: self.name.clone(),
+                app_id: self
+                    .app_id
+                    .clone()
+                    .or_else(|| self.window_properties.as_ref().and_then(|p| p.class.clone())),
+                workspace_id: workspace,
+                is_focused: self.focused,
+                is_fullscreen: self.fullscreen_mode != 0,
+            });
+        }
+        for node in self.nodes.iter().chain(&self.floating_nodes) {
+            node.collect_windows(workspace, windows);
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Input {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    xkb_layout_names: Vec<String>,
+    xkb_active_layout_index: Option<usize>,
+}
+
+#[derive(serde::Deserialize)]
+struct CommandResult {
+    success: bool,
+    error: Option<String>,
+}
+
+/// Quotes a workspace name for a sway command.
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Numbers the workspaces per output, in the order sway lists them, and counts the
+/// windows on each.
+fn workspace_infos(workspaces: Vec<SwayWorkspace>, windows: &[WindowInfo]) -> Vec<WorkspaceInfo> {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for workspace_id in windows.iter().filter_map(|window| window.workspace_id) {
+        *counts.entry(workspace_id).or_insert(0) += 1;
+    }
+
+    let mut positions: HashMap<String, u8> = HashMap::new();
+    let mut infos: Vec<WorkspaceInfo> = workspaces
+        .into_iter()
+        .map(|workspace| {
+            let idx = positions.entry(workspace.output.clone()).or_insert(0);
+            *idx = idx.saturating_add(1);
+            WorkspaceInfo {
+                id: workspace.id,
+                idx: *idx,
+                name: Some(workspace.name),
+                output: Some(workspace.output),
+                is_active: workspace.visible,
+                is_focused: workspace.focused,
+                windows: counts.get(&workspace.id).copied().unwrap_or(0),
+            }
+        })
+        .collect();
+    infos.sort_by(|a, b| (&a.output, a.idx).cmp(&(&b.output, b.idx)));
+    infos
+}
+
+/// Sway, or i3, through the i3 IPC socket.
+pub struct SwayCompositor {
+    socket: PathBuf,
+}
+
+impl SwayCompositor {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        Self { socket: socket.into() }
+    }
+
+    /// Sends one message on a fresh connection and parses the reply.
+    async fn request<T: DeserializeOwned>(&self, kind: u32, payload: &str) -> Result<T, CompositorError> {
+        let mut stream = UnixStream::connect(&self.socket).await?;
+        write_message(&mut stream, kind, payload.as_bytes()).await?;
+        let (_, reply) = read_message(&mut stream).await?;
+        Ok(serde_json::from_slice(&reply)?)
+    }
+
+    async fn run_command(&self, command: &str) -> Result<(), CompositorError> {
+        let results: Vec<CommandResult> = self.request(RUN_COMMAND, command).await?;
+        match results.into_iter().find(|result| !result.success) {
+            Some(failed) => Err(CompositorError::Rejected(
+                failed.error.unwrap_or_else(|| format!("{} failed", command)),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the name of the workspace with the given container id.
+    async fn workspace_name(&self, id: u64) -> Result<String, CompositorError> {
+        let workspaces: Vec<SwayWorkspace> = self.request(GET_WORKSPACES, "").await?;
+        workspaces
+            .into_iter()
+            .find(|workspace| workspace.id == id)
+            .map(|workspace| workspace.name)
+            .ok_or_else(|| CompositorError::Rejected(format!("No workspace with id {}", id)))
+    }
+
+    async fn command_string(&self, command: CompositorCommand) -> Result<String, CompositorError> {
+        let command = match command {
+            CompositorCommand::FocusWorkspace { id } => format!("workspace {}", quote(&self.workspace_name(id).await?)),
+            CompositorCommand::FocusWorkspaceDown => "workspace next_on_output".to_string(),
+            CompositorCommand::FocusWorkspaceUp => "workspace prev_on_output".to_string(),
+            CompositorCommand::FocusColumnLeft => "focus left".to_string(),
+            CompositorCommand::FocusColumnRight => "focus right".to_string(),
+            CompositorCommand::FocusWindow { id } => format!("[con_id={}] focus", id),
+            CompositorCommand::CloseWindow { id } => format!("[con_id={}] kill", id),
+            CompositorCommand::MoveWindowToWorkspace { window, workspace } => format!(
+                "[con_id={}] move container to workspace {}",
+                window,
+                quote(&self.workspace_name(workspace).await?)
+            ),
+            CompositorCommand::SwitchLayoutNext => "input type:keyboard xkb_switch_layout next".to_string(),
+            CompositorCommand::SwitchLayoutPrevious => "input type:keyboard xkb_switch_layout prev".to_string(),
+            CompositorCommand::SwitchLayout { index } => format!("input type:keyboard xkb_switch_layout {}", index),
+        };
+        Ok(command)
+    }
+}
+
+#[async_trait::async_trait]
+impl Compositor for SwayCompositor {
+    fn name(&self) -> &'static str {
+        "Sway"
+    }
+
+    #[instrument(skip_all)]
+    async fn listen(&self, publisher: &mut StatePublisher) -> Result<(), CompositorError> {
+        let mut stream = UnixStream::connect(&self.socket).await?;
+        write_message(&mut stream, SUBSCRIBE, br#"["workspace","window","input"]"#).await?;
+        let (_, reply) = read_message(&mut stream).await?;
+        let reply: CommandResult = serde_json::from_slice(&reply)?;
+        if !reply.success {
+            return Err(CompositorError::Rejected("Subscribing to events failed".to_string()));
+        }
+        info!("Sway is ready to handle events");
+
+        self.refresh(publisher).await?;
+        loop {
+            // The events only say what kind of thing changed, query everything again.
+            let (kind, _) = read_message(&mut stream).await?;
+            if kind & EVENT != 0 {
+                self.refresh(publisher).await?;
+            }
+        }
+    }
+
+    /// Fetches the tree once for both the windows and the window counts of the
+    /// workspaces.
+    async fn refresh(&self, publisher: &mut StatePublisher) -> Result<(), CompositorError> {
+        let windows = self.windows().await?;
+        let workspaces: Vec<SwayWorkspace> = self.request(GET_WORKSPACES, "").await?;
+        publisher.update_workspaces(workspace_infos(workspaces, &windows));
+        publisher.update_windows(windows);
+        match self.keyboard_layouts().await {
+            Ok(layouts) => publisher.update_keyboard_layouts(layouts),
+            Err(e) => warn!("Failed to read keyboard layouts: {}", e),
+        }
+        Ok(())
+    }
+
+    async fn execute(&self, command: CompositorCommand) -> Result<(), CompositorError> {
+        let command = self.command_string(command).await?;
+        self.run_command(&command).await
+    }
+
+    async fn workspaces(&self) -> Result<Vec<WorkspaceInfo>, CompositorError> {
+        let workspaces: Vec<SwayWorkspace> = self.request(GET_WORKSPACES, "").await?;
+        Ok(workspace_infos(workspaces, &self.windows().await?))
+    }
+
+    async fn windows(&self) -> Result<Vec<WindowInfo>, CompositorError> {
+        let tree: Node = self.request(GET_TREE, "").await?;
+        let mut windows = Vec::new();
+        tree.collect_windows(None, &mut windows);
+        Ok(windows)
+    }
+
+    async fn keyboard_layouts(&self) -> Result<KeyboardLayouts, CompositorError> {
+        let inputs: Vec<Input> = self.request(GET_INPUTS, "").await?;
+        Ok(inputs
+            .into_iter()
+            .find(|input| input.kind == "keyboard" && !input.xkb_layout_names.is_empty())
+            .map(|input| KeyboardLayouts {
+                names: input.xkb_layout_names,
+                current: input.xkb_active_layout_index.unwrap_or(0),
+            })
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,271 @@
+//! The bar's object on the session bus.
+//!
+//! GApplication already owns the application id as a bus name, so the object is
+//! exported under its own name. Methods are carried out by the same
+//! `ControlHandler` as `molyuu-bar msg`, property changes emit `PropertiesChanged`.
+
+use futures_lite::future;
+use smol::channel::{Receiver, Sender};
+use tracing::warn;
+use zbus::{Connection, connection::Builder, fdo, interface};
+
+use crate::service::{
+    control::{ControlHandler, ControlReply, ControlRequest, MESSAGE_TIMEOUT_MS},
+    event::{EventHandler, EventListener, SubscribeOptions, Subscription, UIUpdateEvent, UIUpdateEventType},
+    network::endpoints::event::{NetworkServiceEvent, NetworkServiceEventType},
+};
+
+pub const BUS_NAME: &str = "io.github.bigsaltyfishes.molyuubar.Bar";
+pub const OBJECT_PATH: &str = "/io/github/bigsaltyfishes/molyuubar/Bar";
+
+/// A request from the bus together with the channel its reply goes to.
+pub type ControlCall = (ControlRequest, Sender<ControlReply>);
+
+/// What the bus object exposes as properties.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BarState {
+    pub visible: bool,
+    /// As shown in the bar: the compositor's name and `Desktop` while no window is
+    /// focused.
+    pub focused_app_id: String,
+    pub focused_title: String,
+    /// Name of the primary connection's profile, empty while offline.
+    pub network_connection: String,
+    /// Device type of the primary connection, e.g. `WiFi`, empty while offline.
+    pub network_type: String,
+    pub wireless_enabled: bool,
+    pub metered: bool,
+}
+
+impl BarState {
+    /// Follows the focused window.
+    pub fn observe_compositor(&mut self, event: &UIUpdateEvent) {
+        if let UIUpdateEvent::WindowFocusChanged { app_id, title } = event {
+            self.focused_app_id = app_id.clone().unwrap_or_default();
+            self.focused_title = title.clone().unwrap_or_default();
+        }
+    }
+
+    /// Follows the network summary.
+    pub fn observe_network(&mut self, event: &NetworkServiceEvent) {
+        match event {
+            NetworkServiceEvent::PrimaryConnectionChanged {
+                device_type,
+                connection_id,
+                ..
+            } => {
+                self.network_connection = connection_id.clone().unwrap_or_default();
+                self.network_type = device_type
+                    .map(|device_type| format!("{:?}", device_type))
+                    .unwrap_or_default();
+            }
+            NetworkServiceEvent::GlobalWirelessEnabledStateChanged { enabled } => {
+                self.wireless_enabled = *enabled;
+            }
+            NetworkServiceEvent::MeteredStateChanged { state } => self.metered = state.is_metered(),
+            _ => {}
+        }
+    }
+}
+
+struct BarInterface {
+    state: BarState,
+    calls: Sender<ControlCall>,
+}
+
+impl BarInterface {
+    async fn call(&self, request: ControlRequest) -> fdo::Result<()> {
+        let (sender, receiver) = smol::channel::bounded(1);
+        self.calls
+            .send((request, sender))
+            .await
+            .map_err(|_| fdo::Error::Failed("The bar is shutting down".to_string()))?;
+        match receiver.recv().await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(message)) => Err(fdo::Error::Failed(message)),
+            Err(_) => Err(fdo::Error::Failed("The bar did not answer".to_string())),
+        }
+    }
+}
+
+#[interface(name = "io.github.bigsaltyfishes.molyuubar.Bar")]
+impl BarInterface {
+    /// Opens the popover of a bar module, e.g. `network`.
+    async fn open_popover(&self, name: String) -> fdo::Result<()> {
+        self.call(ControlRequest::OpenPopover { name }).await
+    }
+
+    /// Shows `text` in the middle of the bar, for a default time if `timeout_ms` is 0.
+    async fn show_message(&self, text: String, timeout_ms: u32) -> fdo::Result<()> {
+        let timeout_ms = if timeout_ms == 0 { MESSAGE_TIMEOUT_MS } else { timeout_ms };
+        self.call(ControlRequest::ShowMessage { text, timeout_ms }).await
+    }
+
+    async fn reload(&self) -> fdo::Result<()> {
+        self.call(ControlRequest::ReloadConfig).await
+    }
+
+    #[zbus(property)]
+    fn visible(&self) -> bool {
+        self.state.visible
+    }
+
+    #[zbus(property)]
+    fn focused_app_id(&self) -> String {
+        self.state.focused_app_id.clone()
+    }
+
+    #[zbus(property)]
+    fn focused_title(&self) -> String {
+        self.state.focused_title.clone()
+    }
+
+    #[zbus(property)]
+    fn network_connection(&self) -> String {
+        self.state.network_connection.clone()
+    }
+
+    #[zbus(property)]
+    fn network_type(&self) -> String {
+        self.state.network_type.clone()
+    }
+
+    #[zbus(property)]
+    fn wireless_enabled(&self) -> bool {
+        self.state.wireless_enabled
+    }
+
+    #[zbus(property)]
+    fn metered(&self) -> bool {
+        self.state.metered
+    }
+}
+
+/// The bar's connection to the bus. Cheap to clone.
+#[derive(Clone)]
+pub struct BarBus {
+    connection: Connection,
+}
+
+impl BarBus {
+    /// Exports the object on the bus `builder` connects to, e.g.
+    /// `Builder::session()`. Method calls are sent to `calls`, see `answer_calls`.
+    pub async fn serve(builder: Builder<'_>, state: BarState, calls: Sender<ControlCall>) -> zbus::Result<Self> {
+        let connection = builder
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, BarInterface { state, calls })?
+            .build()
+            .await?;
+        Ok(Self { connection })
+    }
+
+    /// Changes the exported state, emitting `PropertiesChanged` for what changed.
+    pub async fn update(&self, update: impl FnOnce(&mut BarState)) -> zbus::Result<()> {
+        let object = self
+            .connection
+            .object_server()
+            .interface::<_, BarInterface>(OBJECT_PATH)
+            .await?;
+        let old = {
+            let mut object = object.get_mut().await;
+            let old = object.state.clone();
+            update(&mut object.state);
+            old
+        };
+
+        let object_ref = object.get().await;
+        let new = &object_ref.state;
+        let context = object.signal_context();
+        if new.visible != old.visible {
+            object_ref.visible_changed(context).await?;
+        }
+        if new.focused_app_id != old.focused_app_id {
+            object_ref.focused_app_id_changed(context).await?;
+        }
+        if new.focused_title != old.focused_title {
+            object_ref.focused_title_changed(context).await?;
+        }
+        if new.network_connection != old.network_connection {
+            object_ref.network_connection_changed(context).await?;
+        }
+        if new.network_type != old.network_type {
+            object_ref.network_type_changed(context).await?;
+        }
+        if new.wireless_enabled != old.wireless_enabled {
+            object_ref.wireless_enabled_changed(context).await?;
+        }
+        if new.metered != old.metered {
+            object_ref.metered_changed(context).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Carries out the calls `BarBus` receives with `handler`, until the bus is gone.
+pub async fn answer_calls(calls: Receiver<ControlCall>, handler: &impl ControlHandler) {
+    while let Ok((request, reply)) = calls.recv().await {
+        let _ = reply.send(handler.handle(request).await).await;
+    }
+}
+
+/// Keeps the exported focused window and network summary up to date.
+#[derive(Default)]
+pub struct BarStateFeed {
+    compositor: Option<Subscription<UIUpdateEventType, UIUpdateEvent>>,
+    network: Option<Subscription<NetworkServiceEventType, NetworkServiceEvent>>,
+}
+
+impl BarStateFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forwards the events of the services the feed is registered to to `bus`.
+    pub async fn listen(self, bus: BarBus) {
+        let compositor = async {
+            let Some(subscription) = self.compositor else {
+                return;
+            };
+            while let Some(event) = subscription.recv().await {
+                Self::forward(&bus, |state| state.observe_compositor(&event)).await;
+            }
+        };
+        let network = async {
+            let Some(subscription) = self.network else {
+                return;
+            };
+            while let Some(event) = subscription.recv().await {
+                Self::forward(&bus, |state| state.observe_network(&event)).await;
+            }
+        };
+        future::zip(compositor, network).await;
+    }
+
+    async fn forward(bus: &BarBus, observe: impl FnOnce(&mut BarState)) {
+        if let Err(e) = bus.update(observe).await {
+            warn!("Failed to update the bar state on the bus: {}", e);
+        }
+    }
+}
+
+impl EventHandler<UIUpdateEventType, UIUpdateEvent> for BarStateFeed {
+    fn register_to_listener(&mut self, listener: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) {
+        self.compositor = Some(listener.subscribe(
+            vec![UIUpdateEventType::WindowFocusChanged],
+            SubscribeOptions::new().replay(),
+        ));
+    }
+}
+
+impl EventHandler<NetworkServiceEventType, NetworkServiceEvent> for BarStateFeed {
+    fn register_to_listener(&mut self, listener: &mut impl EventListener<NetworkServiceEventType, NetworkServiceEvent>) {
+        self.network = Some(listener.subscribe(
+            vec![
+                NetworkServiceEventType::PrimaryConnectionChanged,
+                NetworkServiceEventType::GlobalWirelessEnabledStateChanged,
+                NetworkServiceEventType::MeteredStateChanged,
+            ],
+            SubscribeOptions::new().replay(),
+        ));
+    }
+}
@@ -0,0 +1,225 @@
+pub mod dbus;
+pub mod state;
+
+use std::{
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde_json::Value;
+use smol::{
+    LocalExecutor,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::unix::{UnixListener, UnixStream},
+};
+use smol_timeout::TimeoutExt;
+use tracing::{debug, warn};
+
+/// Overrides the path of the control socket.
+pub const SOCKET_VAR: &str = "MOLYUU_BAR_SOCKET";
+
+/// How long a client may take to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long `show-message` shows a message unless told otherwise.
+pub const MESSAGE_TIMEOUT_MS: u32 = 3000;
+
+/// A request to the running bar, sent as one line of JSON.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControlRequest {
+    /// Shows a hidden bar or hides a shown one.
+    ToggleVisibility,
+    /// Opens the popover of a bar module, e.g. `network`.
+    OpenPopover { name: String },
+    /// Shows `text` in the middle of the bar for `timeout_ms` milliseconds.
+    ShowMessage { text: String, timeout_ms: u32 },
+    /// Reads the configuration again.
+    ReloadConfig,
+    /// Describes the compositor and network state as JSON.
+    DumpState,
+}
+
+impl ControlRequest {
+    /// Parses the arguments following `molyuu-bar msg`.
+    pub fn from_args<S: AsRef<str>>(args: &[S]) -> Result<Self, String> {
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        match args.as_slice() {
+            ["toggle-visibility"] => Ok(ControlRequest::ToggleVisibility),
+            ["open-popover", name] => Ok(ControlRequest::OpenPopover { name: name.to_string() }),
+            ["open-popover"] => Err("open-popover needs the name of a popover, e.g. network".to_string()),
+            ["show-message", text] => Ok(ControlRequest::ShowMessage {
+                text: text.to_string(),
+                timeout_ms: MESSAGE_TIMEOUT_MS,
+            }),
+            ["show-message", text, timeout_ms] => match timeout_ms.parse() {
+                Ok(timeout_ms) => Ok(ControlRequest::ShowMessage {
+                    text: text.to_string(),
+                    timeout_ms,
+                }),
+                Err(_) => Err(format!("Invalid timeout in milliseconds: {}", timeout_ms)),
+            },
+            ["show-message"] => Err("show-message needs the text to show".to_string()),
+            ["reload-config"] => Ok(ControlRequest::ReloadConfig),
+            ["dump-state"] => Ok(ControlRequest::DumpState),
+            [] => Err("Missing command".to_string()),
+            [command, ..] => Err(format!("Unknown command or arguments: {}", command)),
+        }
+    }
+}
+
+/// The answer to a `ControlRequest`, `Err` carrying a message for the user.
+/// Sent as `{"Ok": ...}` or `{"Err": "..."}`, like niri's replies.
+pub type ControlReply = Result<Value, String>;
+
+/// Why a request to the running bar failed.
+#[derive(Debug)]
+pub enum ControlError {
+    /// Connecting to or talking over the socket failed, usually because the bar is
+    /// not running.
+    Io(io::Error),
+    /// The bar sent something that could not be parsed.
+    Protocol(String),
+    /// The bar could not carry out the request, with its error message.
+    Rejected(String),
+}
+
+impl Display for ControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlError::Io(e) => write!(f, "control socket error: {}. Is molyuu-bar running?", e),
+            ControlError::Protocol(message) => write!(f, "invalid reply from molyuu-bar: {}", message),
+            ControlError::Rejected(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ControlError {}
+
+impl From<io::Error> for ControlError {
+    fn from(e: io::Error) -> Self {
+        ControlError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ControlError {
+    fn from(e: serde_json::Error) -> Self {
+        ControlError::Protocol(e.to_string())
+    }
+}
+
+/// Where the control socket lives: `MOLYUU_BAR_SOCKET`, or one socket per Wayland
+/// display in `XDG_RUNTIME_DIR`, falling back to the temporary directory.
+pub fn socket_path(var: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    if let Some(path) = var(SOCKET_VAR) {
+        return PathBuf::from(path);
+    }
+    let display = var("WAYLAND_DISPLAY").unwrap_or_else(|| "wayland-0".to_string());
+    let dir = var("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("molyuu-bar.{}.sock", display))
+}
+
+/// Sends `request` to the bar listening on `path` and waits for the reply.
+pub async fn send(path: &Path, request: &ControlRequest) -> Result<Value, ControlError> {
+    let mut stream = UnixStream::connect(path).await?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).await?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).await?;
+    serde_json::from_str::<ControlReply>(&reply)?.map_err(ControlError::Rejected)
+}
+
+/// Carries out requests on behalf of the `ControlServer`.
+#[async_trait::async_trait(?Send)]
+pub trait ControlHandler {
+    async fn handle(&self, request: ControlRequest) -> ControlReply;
+}
+
+/// Accepts requests from `molyuu-bar msg` on a Unix socket.
+pub struct ControlServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl ControlServer {
+    /// Listens on `path`, replacing a socket left behind by an earlier instance.
+    /// Fails with `AddrInUse` while another bar still answers on it.
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("another molyuu-bar is listening on {:?}", path),
+            ));
+        }
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        let listener = UnixListener::bind(path)?;
+        debug!("Control socket listening at {:?}", path);
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Answers one request per connection. Every connection gets a task of its own,
+    /// so a slow client does not hold up the others.
+    pub async fn serve(&self, handler: &impl ControlHandler) {
+        let connections = LocalExecutor::new();
+        connections
+            .run(async {
+                loop {
+                    match self.listener.accept().await {
+                        Ok((stream, _)) => {
+                            connections
+                                .spawn(async move {
+                                    if let Err(e) = Self::answer(stream, handler).await {
+                                        warn!("Failed to answer control request: {}", e);
+                                    }
+                                })
+                                .detach();
+                        }
+                        Err(e) => {
+                            warn!("Failed to accept control connection: {}", e);
+                            return;
+                        }
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn answer(mut stream: UnixStream, handler: &impl ControlHandler) -> io::Result<()> {
+        let mut line = String::new();
+        let mut reader = BufReader::new(stream.clone());
+        match reader.read_line(&mut line).timeout(REQUEST_TIMEOUT).await {
+            Some(result) => result?,
+            None => return Err(io::Error::new(io::ErrorKind::TimedOut, "no request received")),
+        };
+
+        let reply = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => {
+                debug!("Control request: {:?}", request);
+                handler.handle(request).await
+            }
+            Err(e) => Err(format!("Invalid request: {}", e)),
+        };
+        let mut reply = serde_json::to_string(&reply)?;
+        reply.push('\n');
+        stream.write_all(reply.as_bytes()).await
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
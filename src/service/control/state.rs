@@ -0,0 +1,147 @@
+//! The JSON `molyuu-bar msg dump-state` prints.
+
+use serde_json::{Value, json};
+
+use crate::service::{
+    compositor::{Compositor, CompositorError},
+    event::{KeyboardLayouts, WindowInfo, WorkspaceInfo},
+    network::{
+        snapshot::{NetworkDeviceSnapshot, NetworkSnapshot},
+        wireless::ap::{AccessPoint, WirelessBand},
+    },
+};
+
+fn workspace(workspace: &WorkspaceInfo) -> Value {
+    json!({
+        "id": workspace.id,
+        "idx": workspace.idx,
+        "name": workspace.name,
+        "output": workspace.output,
+        "is_active": workspace.is_active,
+        "is_focused": workspace.is_focused,
+        "windows": workspace.windows,
+    })
+}
+
+fn window(window: &WindowInfo) -> Value {
+    json!({
+        "id": window.id,
+        "title": window.title,
+        "app_id": window.app_id,
+        "workspace_id": window.workspace_id,
+        "is_focused": window.is_focused,
+        "is_fullscreen": window.is_fullscreen,
+    })
+}
+
+/// Describes what the bar shows of the compositor. `keyboard_layouts` is `None`
+/// when the compositor could not report them.
+pub fn compositor_state(
+    name: &str,
+    workspaces: &[WorkspaceInfo],
+    windows: &[WindowInfo],
+    keyboard_layouts: Option<&KeyboardLayouts>,
+) -> Value {
+    json!({
+        "name": name,
+        "workspaces": workspaces.iter().map(workspace).collect::<Vec<_>>(),
+        "windows": windows.iter().map(window).collect::<Vec<_>>(),
+        "keyboard_layouts": keyboard_layouts.map(|layouts| json!({
+            "names": layouts.names,
+            "current": layouts.current,
+        })),
+    })
+}
+
+/// Asks `compositor` for its current state, see `compositor_state`.
+pub async fn query_compositor(compositor: &dyn Compositor) -> Result<Value, CompositorError> {
+    let workspaces = compositor.workspaces().await?;
+    let windows = compositor.windows().await?;
+    let keyboard_layouts = compositor.keyboard_layouts().await.ok();
+    Ok(compositor_state(
+        compositor.name(),
+        &workspaces,
+        &windows,
+        keyboard_layouts.as_ref(),
+    ))
+}
+
+fn access_point(ap: &AccessPoint) -> Value {
+    json!({
+        "ssid": ap.ssid,
+        "bssid": ap.bssid,
+        "frequency": ap.frequency,
+        "band": WirelessBand::from_frequency(ap.frequency).map(|band| band.to_string()),
+        "channel": WirelessBand::channel(ap.frequency),
+        "signal_strength": ap.signal_strength,
+    })
+}
+
+fn device(device: &NetworkDeviceSnapshot) -> Value {
+    // Group the scan results by network, strongest networks first.
+    let mut networks: Vec<_> = device.access_points.iter().collect();
+    networks.sort_by(|(a_key, a), (b_key, b)| {
+        let strongest = |aps: &[AccessPoint]| aps.iter().map(|ap| ap.signal_strength).max();
+        strongest(b).cmp(&strongest(a)).then_with(|| a_key.0.cmp(&b_key.0))
+    });
+    let access_points: Vec<Value> = networks
+        .into_iter()
+        .map(|((ssid, security), aps)| {
+            json!({
+                "ssid": ssid,
+                "security": format!("{:?}", security),
+                "access_points": aps.iter().map(access_point).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    json!({
+        "interface": device.interface,
+        "type": format!("{:?}", device.device_type),
+        "state": device.state.map(|state| format!("{:?}", state)),
+        "active_access_point": device.active_access_point.as_ref().map(access_point),
+        "access_points": access_points,
+        "scanning": device.scanning,
+        "wired_link": device.wired_link.as_ref().map(|link| json!({
+            "carrier": link.carrier,
+            "speed": link.speed,
+            "perm_hw_address": link.perm_hw_address,
+        })),
+        "modem_status": device.modem_status.as_ref().map(|status| json!({
+            "state": format!("{:?}", status.state),
+            "signal_quality": status.signal_quality,
+            "access_technology": format!("{:?}", status.access_technology),
+            "operator_name": status.operator_name,
+        })),
+    })
+}
+
+/// Describes a `NetworkSnapshot`. Enums are written as their variant names.
+pub fn network_state(snapshot: &NetworkSnapshot) -> Value {
+    let mut saved_profiles: Vec<_> = snapshot.saved_profiles.iter().collect();
+    saved_profiles.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+    let primary = &snapshot.primary_connection;
+
+    json!({
+        "devices": snapshot.devices.iter().map(device).collect::<Vec<_>>(),
+        "wireless_enabled": snapshot.wireless_enabled,
+        "primary_connection": json!({
+            "interface": primary.interface,
+            "type": primary.device_type.map(|device_type| format!("{:?}", device_type)),
+            "connection_id": primary.connection_id,
+        }),
+        "metered": json!({
+            "device": format!("{:?}", snapshot.metered.device),
+            "profile": format!("{:?}", snapshot.metered.profile),
+            "is_metered": snapshot.metered.is_metered(),
+        }),
+        "saved_profiles": saved_profiles
+            .into_iter()
+            .map(|((ssid, security), works)| json!({
+                "ssid": ssid,
+                "security": format!("{:?}", security),
+                "works": works,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use tracing::{debug, warn};
+
+/// The parts of a `.desktop` file the bar shows, localized for the current locale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DesktopEntry {
+    /// Desktop file id, e.g. `org.gnome.Nautilus` for `org.gnome.Nautilus.desktop`.
+    pub id: String,
+    pub name: String,
+    /// An icon name from the icon theme, or an absolute path.
+    pub icon: Option<String>,
+    pub startup_wm_class: Option<String>,
+    pub path: PathBuf,
+}
+
+impl DesktopEntry {
+    /// Parses the `[Desktop Entry]` group of a desktop file.
+    ///
+    /// `locales` lists the locale keys to try, most specific first, see `locale_keys`.
+    /// Returns `None` for entries without a name and for hidden entries.
+    pub fn parse(id: &str, path: &Path, content: &str, locales: &[String]) -> Option<Self> {
+        let mut in_group = false;
+        let mut values: HashMap<&str, &str> = HashMap::new();
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_group = line == "[Desktop Entry]";
+                continue;
+            }
+            if !in_group || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                values.entry(key.trim()).or_insert(value.trim());
+            }
+        }
+
+        if values.get("Hidden") == Some(&"true") {
+            return None;
+        }
+        let localized = |key: &str| {
+            locales
+                .iter()
+                .find_map(|locale| values.get(format!("{}[{}]", key, locale).as_str()))
+                .or_else(|| values.get(key))
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+
+        Some(Self {
+            id: id.to_string(),
+            name: localized("Name")?,
+            icon: localized("Icon"),
+            startup_wm_class: values.get("StartupWMClass").map(|v| v.to_string()),
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+/// Returns the keys to look up localized values with for a locale like
+/// `de_DE.UTF-8@euro`, most specific first, as the desktop entry specification orders them.
+pub fn locale_keys(locale: &str) -> Vec<String> {
+    let (locale, modifier) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale, Some(modifier)),
+        None => (locale, None),
+    };
+    let locale = locale.split('.').next().unwrap_or(locale);
+    let (lang, country) = match locale.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (locale, None),
+    };
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut keys = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        keys.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        keys.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        keys.push(format!("{}@{}", lang, modifier));
+    }
+    keys.push(lang.to_string());
+    keys
+}
+
+/// All entries found in the application directories.
+struct Index {
+    /// By lowercase desktop file id.
+    by_id: HashMap<String, Arc<DesktopEntry>>,
+    /// By lowercase `StartupWMClass`.
+    by_wm_class: HashMap<String, Arc<DesktopEntry>>,
+    /// Every directory scanned with its modification time, `None` if it does not exist.
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+fn modified(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
+
+impl Index {
+    fn build(dirs: &[PathBuf], locales: &[String]) -> Self {
+        let mut index = Self {
+            by_id: HashMap::new(),
+            by_wm_class: HashMap::new(),
+            stamps: Vec::new(),
+        };
+        // Earlier directories take precedence, so later ones never replace an entry.
+        for dir in dirs {
+            index.scan(dir, dir, locales);
+        }
+        debug!("Indexed {} desktop entries", index.by_id.len());
+        index
+    }
+
+    fn scan(&mut self, root: &Path, dir: &Path, locales: &[String]) {
+        self.stamps.push((dir.to_path_buf(), modified(dir)));
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = read_dir.flatten().map(|entry| entry.path()).collect();
+        paths.sort();
+        for path in paths {
+            if path.is_dir() {
+                self.scan(root, &path, locales);
+                continue;
+            }
+            if path.extension().is_none_or(|ext| ext != "desktop") {
+                continue;
+            }
+            // `kde/okular.desktop` has the id `kde-okular`.
+            let Some(id) = path
+                .strip_prefix(root)
+                .ok()
+                .and_then(|relative| relative.with_extension("").to_str().map(|id| id.replace('/', "-")))
+            else {
+                continue;
+            };
+            if self.by_id.contains_key(&id.to_lowercase()) {
+                continue;
+            }
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("Failed to read {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let Some(entry) = DesktopEntry::parse(&id, &path, &content, locales) else {
+                continue;
+            };
+            let entry = Arc::new(entry);
+            if let Some(wm_class) = &entry.startup_wm_class {
+                self.by_wm_class.entry(wm_class.to_lowercase()).or_insert(entry.clone());
+            }
+            self.by_id.insert(id.to_lowercase(), entry);
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        self.stamps.iter().any(|(dir, stamp)| modified(dir) != *stamp)
+    }
+
+    fn find(&self, app_id: &str) -> Option<Arc<DesktopEntry>> {
+        let app_id = app_id.to_lowercase();
+        if app_id.is_empty() {
+            return None;
+        }
+        // `org.gnome.Nautilus`, or `org.gnome.nautilus` from an app that lowercases it.
+        if let Some(entry) = self.by_id.get(&app_id) {
+            return Some(entry.clone());
+        }
+        // X11 and XWayland apps report their WM class.
+        if let Some(entry) = self.by_wm_class.get(&app_id) {
+            return Some(entry.clone());
+        }
+        // `Google-chrome` or `Foot Client` for `google-chrome` and `foot-client`.
+        let dashed = app_id.replace(' ', "-");
+        if let Some(entry) = self.by_id.get(&dashed) {
+            return Some(entry.clone());
+        }
+        // `nautilus` for `org.gnome.Nautilus`, and the other way around. Pick the
+        // shortest id so the result does not depend on hash map order.
+        let last = |id: &str| id.rsplit('.').next().unwrap_or(id).to_string();
+        let app_last = last(&dashed);
+        self.by_id
+            .iter()
+            .filter(|(id, _)| last(id) == app_last)
+            .min_by_key(|(id, _)| (id.len(), id.to_string()))
+            .map(|(_, entry)| entry.clone())
+    }
+}
+
+/// Maps window app ids to desktop entries.
+///
+/// Lookups are cached. The index is rebuilt when any application directory
+/// changes, e.g. when an application is installed or removed.
+pub struct DesktopEntries {
+    dirs: Vec<PathBuf>,
+    locales: Vec<String>,
+    index: Option<Index>,
+    cache: HashMap<String, Option<Arc<DesktopEntry>>>,
+}
+
+impl DesktopEntries {
+    pub fn new(dirs: Vec<PathBuf>, locales: Vec<String>) -> Self {
+        Self {
+            dirs,
+            locales,
+            index: None,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Searches the `applications` directories of `XDG_DATA_HOME` and
+    /// `XDG_DATA_DIRS`, localized for the locale in `LC_ALL`, `LC_MESSAGES` or `LANG`.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let data_home = var("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".local/share")));
+        let data_dirs = var("XDG_DATA_DIRS").unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+        let dirs = data_home
+            .into_iter()
+            .chain(data_dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from))
+            .map(|dir| dir.join("applications"))
+            .collect();
+
+        let locale = var("LC_ALL").or_else(|| var("LC_MESSAGES")).or_else(|| var("LANG"));
+        Self::new(dirs, locale.map(|l| locale_keys(&l)).unwrap_or_default())
+    }
+
+    /// Drops the index and all cached lookups.
+    pub fn invalidate(&mut self) {
+        self.index = None;
+        self.cache.clear();
+    }
+
+    /// Finds the desktop entry of the application with the given app id or WM class.
+    pub fn resolve(&mut self, app_id: &str) -> Option<Arc<DesktopEntry>> {
+        if self.index.as_ref().is_some_and(Index::is_stale) {
+            debug!("Application directories changed, reindexing desktop entries");
+            self.invalidate();
+        }
+        if let Some(cached) = self.cache.get(app_id) {
+            return cached.clone();
+        }
+        let index = self
+            .index
+            .get_or_insert_with(|| Index::build(&self.dirs, &self.locales));
+        let entry = index.find(app_id);
+        self.cache.insert(app_id.to_string(), entry.clone());
+        entry
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref DESKTOP_ENTRIES: Mutex<DesktopEntries> = Mutex::new(DesktopEntries::from_env());
+}
+
+/// Resolves an app id with the shared `DesktopEntries` of the environment.
+pub fn resolve(app_id: &str) -> Option<Arc<DesktopEntry>> {
+    DESKTOP_ENTRIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .resolve(app_id)
+}
+
+/// Like `resolve`, but reads the application directories on a blocking thread.
+pub async fn lookup(app_id: String) -> Option<Arc<DesktopEntry>> {
+    smol::unblock(move || resolve(&app_id)).await
+}
@@ -0,0 +1,311 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+    sync::{Arc, Mutex, MutexGuard, Weak},
+    time::Instant,
+};
+
+use smol::{
+    Timer,
+    channel::{Receiver, Sender},
+    future,
+};
+
+/// A key events are published under, usually a `...EventType` enum.
+pub trait Topic: Hash + Eq + Clone + Debug + Send + 'static {}
+
+impl<T: Hash + Eq + Clone + Debug + Send + 'static> Topic for T {}
+
+/// What a bounded subscription does with a new event while it is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued event.
+    DropOldest,
+    /// Discard the new event.
+    DropNewest,
+    /// Replace the newest queued event of the same topic, so only the latest state of a
+    /// topic waits in the queue. Falls back to `DropOldest` if the topic is not queued.
+    Coalesce,
+}
+
+/// How many events a subscription may queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Capacity {
+    #[default]
+    Unbounded,
+    Bounded(usize, OverflowPolicy),
+}
+
+/// Options for `EventBus::subscribe`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SubscribeOptions {
+    capacity: Capacity,
+    replay: bool,
+}
+
+impl SubscribeOptions {
+    /// Unbounded, without replay.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the queue to `capacity` events, applying `overflow` once it is full.
+    pub fn bounded(mut self, capacity: usize, overflow: OverflowPolicy) -> Self {
+        self.capacity = Capacity::Bounded(capacity.max(1), overflow);
+        self
+    }
+
+    /// Delivers the last event published on each subscribed topic right away, except
+    /// on topics the bus does not replay, see `EventBus::without_replay`.
+    pub fn replay(mut self) -> Self {
+        self.replay = true;
+        self
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panicking subscriber must not take the whole bus down with it.
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Queued events of one subscription.
+/// Events without a topic were passed to `subscribe_with_initial` and are never dropped.
+struct Mailbox<T, E> {
+    queue: Mutex<VecDeque<(Option<T>, E)>>,
+    capacity: Capacity,
+}
+
+impl<T: Topic, E> Mailbox<T, E> {
+    fn push(&self, topic: T, event: E) {
+        let mut queue = lock(&self.queue);
+        let Capacity::Bounded(capacity, overflow) = self.capacity else {
+            queue.push_back((Some(topic), event));
+            return;
+        };
+        if queue.len() < capacity {
+            queue.push_back((Some(topic), event));
+            return;
+        }
+
+        let oldest = queue.iter().position(|(queued, _)| queued.is_some());
+        match overflow {
+            OverflowPolicy::DropNewest => {}
+            OverflowPolicy::Coalesce => {
+                match queue.iter_mut().rev().find(|(queued, _)| queued.as_ref() == Some(&topic)) {
+                    Some(queued) => queued.1 = event,
+                    None => {
+                        if let Some(oldest) = oldest {
+                            queue.remove(oldest);
+                        }
+                        queue.push_back((Some(topic), event));
+                    }
+                }
+            }
+            OverflowPolicy::DropOldest => {
+                if let Some(oldest) = oldest {
+                    queue.remove(oldest);
+                }
+                queue.push_back((Some(topic), event));
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<E> {
+        lock(&self.queue).pop_front().map(|(_, event)| event)
+    }
+}
+
+struct Subscriber<T, E> {
+    id: u64,
+    topics: Vec<T>,
+    mailbox: Arc<Mailbox<T, E>>,
+    // Dropping the entry closes the channel, which ends the subscription.
+    notify: Sender<()>,
+}
+
+struct BusState<T, E> {
+    next_id: u64,
+    sequence: u64,
+    subscribers: Vec<Subscriber<T, E>>,
+    // Last event of every topic with the sequence number it was published with.
+    retained: HashMap<T, (u64, E)>,
+    // Topics that are never retained.
+    unretained: HashSet<T>,
+}
+
+/// A publish / subscribe bus for the events of one service.
+///
+/// Publishing never blocks: every subscriber has its own queue, which is either
+/// unbounded or bounded with an `OverflowPolicy`. Dropping a `Subscription`
+/// unsubscribes it, dropping every clone of the bus ends all subscriptions.
+pub struct EventBus<T, E> {
+    state: Arc<Mutex<BusState<T, E>>>,
+}
+
+impl<T, E> Clone for EventBus<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Topic, E: Clone + Send + 'static> Default for EventBus<T, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Topic, E: Clone + Send + 'static> EventBus<T, E> {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BusState {
+                next_id: 0,
+                sequence: 0,
+                subscribers: Vec::new(),
+                retained: HashMap::new(),
+                unretained: HashSet::new(),
+            })),
+        }
+    }
+
+    /// Stops keeping the last event of `topics` for replay. Meant for topics whose
+    /// events each describe one of several things, e.g. one device, where the last
+    /// event alone would misrepresent the others.
+    pub fn without_replay(self, topics: impl IntoIterator<Item = T>) -> Self {
+        lock(&self.state).unretained.extend(topics);
+        self
+    }
+
+    /// Subscribes to events published on any of `topics`.
+    pub fn subscribe(&self, topics: Vec<T>, options: SubscribeOptions) -> Subscription<T, E> {
+        self.subscribe_with_initial(topics, options, Vec::new())
+    }
+
+    /// Like `subscribe`, but queues `initial` ahead of everything else, e.g. a
+    /// handshake carrying the service's command sender.
+    pub fn subscribe_with_initial(
+        &self,
+        topics: Vec<T>,
+        options: SubscribeOptions,
+        initial: Vec<E>,
+    ) -> Subscription<T, E> {
+        let mut state = lock(&self.state);
+        let id = state.next_id;
+        state.next_id += 1;
+
+        let mailbox = Arc::new(Mailbox {
+            queue: Mutex::new(initial.into_iter().map(|event| (None, event)).collect()),
+            capacity: options.capacity,
+        });
+        if options.replay {
+            let mut retained: Vec<(u64, T, E)> = topics
+                .iter()
+                .filter_map(|topic| {
+                    let (sequence, event) = state.retained.get(topic)?;
+                    Some((*sequence, topic.clone(), event.clone()))
+                })
+                .collect();
+            retained.sort_by_key(|(sequence, _, _)| *sequence);
+            for (_, topic, event) in retained {
+                mailbox.push(topic, event);
+            }
+        }
+
+        let (notify, notified) = smol::channel::bounded(1);
+        let _ = notify.try_send(());
+        state.subscribers.push(Subscriber {
+            id,
+            topics,
+            mailbox: mailbox.clone(),
+            notify,
+        });
+
+        Subscription {
+            id,
+            mailbox,
+            notified,
+            bus: Arc::downgrade(&self.state),
+        }
+    }
+
+    /// Publishes `event` on `topic` to every subscriber of that topic and keeps it
+    /// for replay, unless the topic is excluded from it.
+    pub fn publish(&self, topic: T, event: E) {
+        let mut state = lock(&self.state);
+        state.sequence += 1;
+        let sequence = state.sequence;
+        for subscriber in state.subscribers.iter().filter(|s| s.topics.contains(&topic)) {
+            subscriber.mailbox.push(topic.clone(), event.clone());
+            let _ = subscriber.notify.try_send(());
+        }
+        if !state.unretained.contains(&topic) {
+            state.retained.insert(topic, (sequence, event));
+        }
+    }
+
+    /// Returns the last event published on `topic`, `None` for topics without replay.
+    pub fn last(&self, topic: &T) -> Option<E> {
+        lock(&self.state).retained.get(topic).map(|(_, event)| event.clone())
+    }
+
+    /// Returns the number of live subscriptions to `topic`.
+    pub fn subscriber_count(&self, topic: &T) -> usize {
+        lock(&self.state)
+            .subscribers
+            .iter()
+            .filter(|s| s.topics.contains(topic))
+            .count()
+    }
+}
+
+/// The receiving end of an `EventBus` subscription.
+///
+/// Dropping it unsubscribes from the bus.
+pub struct Subscription<T, E> {
+    id: u64,
+    mailbox: Arc<Mailbox<T, E>>,
+    notified: Receiver<()>,
+    bus: Weak<Mutex<BusState<T, E>>>,
+}
+
+impl<T: Topic, E> Subscription<T, E> {
+    /// Waits for the next event.
+    /// Returns `None` once the bus is gone and every queued event was received.
+    pub async fn recv(&self) -> Option<E> {
+        loop {
+            if let Some(event) = self.mailbox.pop() {
+                return Some(event);
+            }
+            if self.notified.recv().await.is_err() {
+                return self.mailbox.pop();
+            }
+        }
+    }
+
+    /// Waits for the next event, but not past `deadline`.
+    /// Returns `None` if the deadline passed first, or the bus is gone.
+    ///
+    /// Cancellation safe: an event is only taken off the queue when it is returned.
+    pub async fn recv_until(&self, deadline: Instant) -> Option<E> {
+        future::or(self.recv(), async {
+            Timer::at(deadline).await;
+            None
+        })
+        .await
+    }
+
+    /// Returns the next queued event without waiting.
+    pub fn try_recv(&self) -> Option<E> {
+        self.mailbox.pop()
+    }
+}
+
+impl<T, E> Drop for Subscription<T, E> {
+    fn drop(&mut self) {
+        if let Some(bus) = self.bus.upgrade() {
+            lock(&bus).subscribers.retain(|subscriber| subscriber.id != self.id);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+pub mod bus;
+
+pub use bus::{EventBus, OverflowPolicy, SubscribeOptions, Subscription, Topic};
+
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+pub enum UIUpdateEventType {
+    WorkspaceChanged,
+    WindowFocusChanged,
+    WindowOpened,
+    WindowChanged,
+    WindowClosed,
+    KeyboardLayoutChanged,
+}
+
+/// A workspace as shown by the workspace widget.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceInfo {
+    /// Compositor id, stable for the lifetime of the workspace.
+    pub id: u64,
+    /// 1-based position on its output.
+    pub idx: u8,
+    /// Name set in the compositor's config, if any.
+    pub name: Option<String>,
+    pub output: Option<String>,
+    /// Shown on its output. The focused workspace is also active.
+    pub is_active: bool,
+    pub is_focused: bool,
+    /// Number of windows on the workspace.
+    pub windows: usize,
+}
+
+impl WorkspaceInfo {
+    /// The name if the workspace has one, otherwise its index.
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.idx.to_string())
+    }
+}
+
+/// A window as shown by the window list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowInfo {
+    /// Compositor id, stable for the lifetime of the window.
+    pub id: u64,
+    pub title: Option<String>,
+    pub app_id: Option<String>,
+    /// `None` while the window is not on any workspace.
+    pub workspace_id: Option<u64>,
+    pub is_focused: bool,
+    /// Always `false` on niri, which does not report fullscreen windows. Those cover
+    /// the bar in `AutoHideMode::Fullscreen` instead.
+    pub is_fullscreen: bool,
+}
+
+/// The configured keyboard layouts and which one is active.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyboardLayouts {
+    /// Full layout names, e.g. `English (US)`.
+    pub names: Vec<String>,
+    /// Index of the active layout in `names`.
+    pub current: usize,
+}
+
+impl KeyboardLayouts {
+    pub fn current_name(&self) -> Option<&str> {
+        self.names.get(self.current).map(String::as_str)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum UIUpdateEvent {
+    /// All workspaces, in the order they are shown.
+    WorkspaceChanged {
+        workspaces: Vec<WorkspaceInfo>,
+    },
+    WindowFocusChanged {
+        app_id: Option<String>,
+        title: Option<String>,
+    },
+    WindowOpened {
+        window: WindowInfo,
+    },
+    /// Title, app id, workspace or focus of an open window changed.
+    WindowChanged {
+        window: WindowInfo,
+    },
+    WindowClosed {
+        id: u64,
+    },
+    KeyboardLayoutChanged {
+        layouts: KeyboardLayouts,
+    },
+}
+
+pub trait EventListener<T: Topic, EVENT: Clone + Send + 'static>: Send + Sync {
+    /// Returns the bus the service publishes its events on.
+    fn event_bus(&self) -> &EventBus<T, EVENT>;
+
+    /// Subscribes to the given event types.
+    ///
+    /// Services with a command channel override this to queue a `HandlerRegistered`
+    /// event carrying their command sender ahead of everything else.
+    fn subscribe(&mut self, event_types: Vec<T>, options: SubscribeOptions) -> Subscription<T, EVENT> {
+        self.event_bus().subscribe(event_types, options)
+    }
+}
+
+/// A bare bus, for handlers that only follow a service's events without sending it
+/// commands.
+impl<T: Topic, EVENT: Clone + Send + 'static> EventListener<T, EVENT> for EventBus<T, EVENT> {
+    fn event_bus(&self) -> &EventBus<T, EVENT> {
+        self
+    }
+}
+
+pub trait EventHandler<T: Topic, EVENT: Clone + Send + 'static> {
+    fn register_to_listener(&mut self, listener: &mut impl EventListener<T, EVENT>);
+
+}
+
+pub trait EventHandlerExt<T: Topic, EVENT: Clone + Send + 'static>: EventHandler<T, EVENT> {
+    async fn listen(&self);
+}
+
+pub trait EventHandlerMutExt<T: Topic, EVENT: Clone + Send + 'static>: EventHandler<T, EVENT> {
+    async fn listen_mut(&mut self);
+}
\ No newline at end of file
@@ -1,3 +1,6 @@
+pub mod compositor;
+pub mod control;
+pub mod desktop_entry;
 pub mod event;
-pub mod niri;
-pub mod network;
\ No newline at end of file
+pub mod network;
+pub mod rfkill;
\ No newline at end of file
@@ -8,7 +8,7 @@ use zbus::zvariant::OwnedObjectPath;
 
 use super::{
     DBUS_CONNECTION, NetworkService, NetworkServiceInterEvent, WirelessWatchDogExt,
-    endpoints::event::NetworkDeviceType, ethernet::EthernetWatchDogExt,
+    endpoints::event::NetworkDeviceType, ethernet::EthernetWatchDogExt, modem::ModemWatchDogExt,
 };
 
 enum DeviceEvent {
@@ -17,7 +17,7 @@ enum DeviceEvent {
 }
 
 #[async_trait::async_trait]
-pub trait NetworkServiceDeviceExt: WirelessWatchDogExt + EthernetWatchDogExt
+pub trait NetworkServiceDeviceExt: WirelessWatchDogExt + EthernetWatchDogExt + ModemWatchDogExt
 where
     Self: 'static,
 {
@@ -52,7 +52,7 @@ where
     /// Processes a newly detected network device.
     /// It retrieves device details (interface name, type) via D-Bus and then
     /// calls `register_device` to register it with the service, spawning an
-    /// appropriate watchdog task (Ethernet or Wi-Fi).
+    /// appropriate watchdog task (Ethernet, Wi-Fi or modem).
     ///
     /// # Arguments
     /// * `sender` - The sender channel for internal service events.
@@ -99,6 +99,17 @@ where
                 )
                 .await;
             }
+            Some(NetworkDeviceType::Modem) => {
+                // Register mobile broadband device and spawn a modem watchdog.
+                Self::register_interface(
+                    &sender,
+                    device_path.to_string(),
+                    interface.clone(),
+                    NetworkDeviceType::Modem,
+                    smol::spawn(Self::modem_watchdog(sender.clone(), device_path)),
+                )
+                .await;
+            }
             t => warn!(
                 "Unknown device type: {:?} for interface {}",
                 t, interface
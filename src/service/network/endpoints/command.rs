@@ -6,7 +6,8 @@ use tracing::{debug, error, info, instrument};
 use zbus::zvariant::ObjectPath;
 
 use crate::service::network::{
-    endpoints::event::{WiFiConnServiceMessage, WiFiConnServiceResponse}, wireless::ap::{AccessPoint, AccessPointSecurity}, AccessPointConnectResult, NetworkService, WirelessConnExt, WirelessScanExt, RadioExt
+    ipconfig::IpSettings,
+    endpoints::event::{WiFiConnServiceMessage, WiFiConnServiceResponse}, ethernet::EthernetConnExt, metered::{MeteredExt, MeteredState}, modem::ModemConnExt, snapshot::NetworkSnapshot, wireless::{ap::{AccessPoint, AccessPointSecurity, HwAddress}, uri::WiFiUri}, AccessPointConnectResult, NetworkService, WirelessConnExt, RadioExt
 };
 
 use super::{
@@ -16,7 +17,7 @@ use super::{
 
 #[async_trait::async_trait]
 pub(in super::super) trait NetworkServiceCommandEndpointHelperExt:
-    WirelessConnExt + WirelessScanExt
+    WirelessConnExt + EthernetConnExt + ModemConnExt + MeteredExt
 {
     #[instrument(skip_all)]
     async fn handle_connect(
@@ -34,44 +35,164 @@ pub(in super::super) trait NetworkServiceCommandEndpointHelperExt:
             .await;
 
         let mut aps: Vec<AccessPoint> = Vec::new();
+        let mut pinned_bssid: Option<HwAddress> = None;
+        let mut requested_ip_settings: Option<IpSettings> = None;
         // loop for client events with timeout
         while let Some(Ok(evt)) = evt_rx.recv().timeout(Duration::from_secs(30)).await {
             match evt.into_request() {
-                Some(WiFiConnServiceRequest::WiFiConnect { ssid, key_mgmt }) => {
-                    info!("Connecting to SSID: {}", ssid);
-                    if let Some(ap_list) =
+                Some(WiFiConnServiceRequest::WiFiConnect { ssid, key_mgmt, bssid, ip_settings }) => {
+                    info!("Connecting to SSID: {}, BSSID: {:?}", ssid, bssid);
+                    if let Some(mut ap_list) =
                         Self::get_access_points(&inter_sender, &interface, ssid, key_mgmt).await
                     {
+                        if let Some(bssid) = &bssid {
+                            ap_list.retain(|ap| ap.bssid.eq_ignore_ascii_case(bssid));
+                        }
+                        if ap_list.is_empty() {
+                            error!("Requested BSSID not found");
+                            break;
+                        }
                         debug!("Found access points: {:?}", ap_list);
                         aps = ap_list;
-                        Self::try_connect(&inter_sender, &interface, None, &aps, &client_chan)
-                            .await;
+                        pinned_bssid = bssid;
+                        requested_ip_settings = ip_settings;
+                        Self::try_connect(
+                            &inter_sender,
+                            &interface,
+                            None,
+                            &aps,
+                            pinned_bssid.clone(),
+                            requested_ip_settings.clone(),
+                            &client_chan,
+                        )
+                        .await;
                     } else {
                         error!("No access points found");
                         break;
                     }
                 }
-                Some(WiFiConnServiceRequest::ProvideAuthenticationInfo { psk }) => {
-                    Self::try_connect(&inter_sender, &interface, Some(psk), &aps, &client_chan)
-                        .await;
+                Some(WiFiConnServiceRequest::ProvideAuthenticationInfo { psk, ip_settings }) => {
+                    if ip_settings.is_some() {
+                        requested_ip_settings = ip_settings;
+                    }
+                    Self::try_connect(
+                        &inter_sender,
+                        &interface,
+                        Some(psk),
+                        &aps,
+                        pinned_bssid.clone(),
+                        requested_ip_settings.clone(),
+                        &client_chan,
+                    )
+                    .await;
                 }
                 e => error!("Unhandled event: {:?}", e),
             }
         }
     }
 
+    async fn handle_connect_uri(
+        inter_sender: Sender<NetworkServiceInterEvent>,
+        interface: String,
+        uri: WiFiUri,
+        client_chan: Sender<bool>,
+    ) {
+        let connected = match Self::get_dbus_path(&inter_sender, &interface).await {
+            Some(path) => matches!(
+                Self::connect_uri(ObjectPath::try_from(path).unwrap().into(), uri).await,
+                AccessPointConnectResult::Connected
+            ),
+            None => {
+                error!("Unknown interface: {}", interface);
+                false
+            }
+        };
+        let _ = client_chan.send(connected).await;
+    }
+
+    async fn handle_share_uri(
+        ssid: String,
+        key_mgmt: AccessPointSecurity,
+        client_chan: Sender<Option<WiFiUri>>,
+    ) {
+        let uri = Self::profile_share_uri(&ssid, key_mgmt)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to read saved profile for {}: {:?}", ssid, e);
+                None
+            });
+        let _ = client_chan.send(uri).await;
+    }
+
     async fn handle_disconnect(inter_sender: Sender<NetworkServiceInterEvent>, interface: String) {
         if let Some(path) = Self::get_dbus_path(&inter_sender, &interface).await {
             let _ = Self::disconnect(ObjectPath::try_from(path).unwrap().into()).await;
         }
     }
 
-    async fn handle_scan(inter_sender: Sender<NetworkServiceInterEvent>, interface: String) {
-        if let Some(path) = Self::get_dbus_path(&inter_sender, &interface).await {
-            let _ = Self::request_scan(ObjectPath::try_from(path).unwrap().into()).await;
+    async fn handle_ethernet_connect(inter_sender: Sender<NetworkServiceInterEvent>, interface: String) {
+        let Some(path) = Self::get_dbus_path(&inter_sender, &interface).await else {
+            error!("Unknown interface: {}", interface);
+            return;
+        };
+        if let Err(e) = Self::connect_ethernet(ObjectPath::try_from(path).unwrap().into()).await {
+            error!("Failed to connect {}: {:?}", interface, e);
+        }
+    }
+
+    async fn handle_modem_connect(inter_sender: Sender<NetworkServiceInterEvent>, interface: String) {
+        let Some(path) = Self::get_dbus_path(&inter_sender, &interface).await else {
+            error!("Unknown interface: {}", interface);
+            return;
+        };
+        match Self::connect_modem(ObjectPath::try_from(path).unwrap().into()).await {
+            Ok(true) => {}
+            Ok(false) => error!("No mobile broadband profile available for {}", interface),
+            Err(e) => error!("Failed to connect {}: {:?}", interface, e),
+        }
+    }
+
+    async fn handle_get_snapshot(inter_sender: Sender<NetworkServiceInterEvent>, client_chan: Sender<NetworkSnapshot>) {
+        let (tx, rx) = smol::channel::unbounded();
+        let _ = inter_sender
+            .send(NetworkServiceInterEvent::GetSnapshot { sender: tx })
+            .await;
+        if let Ok(snapshot) = rx.recv().await {
+            let _ = client_chan.send(snapshot).await;
+        }
+    }
+
+    async fn handle_get_metered(inter_sender: Sender<NetworkServiceInterEvent>, client_chan: Sender<MeteredState>) {
+        let (tx, rx) = smol::channel::unbounded();
+        let _ = inter_sender
+            .send(NetworkServiceInterEvent::GetMeteredState { sender: tx })
+            .await;
+        if let Ok(state) = rx.recv().await {
+            let _ = client_chan.send(state).await;
+        }
+    }
+
+    async fn handle_set_metered(inter_sender: Sender<NetworkServiceInterEvent>, metered: Option<bool>) {
+        match Self::set_primary_metered(metered).await {
+            Ok(Some(state)) => {
+                // The device property does not change if NetworkManager already guessed the
+                // same value, so report the new profile setting ourselves.
+                let _ = inter_sender
+                    .send(NetworkServiceInterEvent::UpdateMeteredState { state })
+                    .await;
+            }
+            Ok(None) => error!("No primary connection to mark as metered"),
+            Err(e) => error!("Failed to set metered state: {:?}", e),
         }
     }
 
+    async fn handle_scan(inter_sender: Sender<NetworkServiceInterEvent>, interface: String) {
+        // Scans go through the scheduler so its rate limits apply to client requests too.
+        let _ = inter_sender
+            .send(NetworkServiceInterEvent::ScanNow { interface })
+            .await;
+    }
+
     async fn get_dbus_path(
         inter_sender: &Sender<NetworkServiceInterEvent>,
         interface: &str,
@@ -110,6 +231,8 @@ pub(in super::super) trait NetworkServiceCommandEndpointHelperExt:
         interface: &str,
         psk: Option<String>,
         aps: &[AccessPoint],
+        bssid: Option<HwAddress>,
+        ip_settings: Option<IpSettings>,
         client_chan: &Sender<WiFiConnServiceMessage>,
     ) {
         if let Some(dbus_path) = Self::get_dbus_path(inter_sender, interface).await {
@@ -121,6 +244,8 @@ pub(in super::super) trait NetworkServiceCommandEndpointHelperExt:
                     obj.into(),
                     aps.len() == 1,
                     psk.clone(),
+                    bssid.clone(),
+                    ip_settings.clone(),
                 )
                 .await
                 {
@@ -172,17 +297,57 @@ where
                     let inter = inter_sender.clone();
                     smol::spawn(Self::handle_connect(inter, interface, channel)).detach();
                 }
+                NetworkServiceRequest::WiFiConnectUri { interface, uri, channel } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_connect_uri(inter, interface, uri, channel)).detach();
+                }
+                NetworkServiceRequest::GetWiFiShareUri { ssid, key_mgmt, channel } => {
+                    smol::spawn(Self::handle_share_uri(ssid, key_mgmt, channel)).detach();
+                }
                 NetworkServiceRequest::WiFiDisconnect { interface } => {
                     let inter = inter_sender.clone();
                     smol::spawn(Self::handle_disconnect(inter, interface)).detach();
                 }
+                NetworkServiceRequest::EthernetConnect { interface } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_ethernet_connect(inter, interface)).detach();
+                }
+                NetworkServiceRequest::EthernetDisconnect { interface } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_disconnect(inter, interface)).detach();
+                }
+                NetworkServiceRequest::ModemConnect { interface } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_modem_connect(inter, interface)).detach();
+                }
+                NetworkServiceRequest::ModemDisconnect { interface } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_disconnect(inter, interface)).detach();
+                }
                 NetworkServiceRequest::WiFiScan { interface } => {
                     let inter = inter_sender.clone();
                     smol::spawn(Self::handle_scan(inter, interface)).detach();
                 }
+                NetworkServiceRequest::GetSnapshot { channel } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_get_snapshot(inter, channel)).detach();
+                }
+                NetworkServiceRequest::GetMeteredState { channel } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_get_metered(inter, channel)).detach();
+                }
+                NetworkServiceRequest::SetMetered { metered } => {
+                    let inter = inter_sender.clone();
+                    smol::spawn(Self::handle_set_metered(inter, metered)).detach();
+                }
                 NetworkServiceRequest::SetGlobalWirelessEnabledState { enabled } => {
                     smol::spawn(Self::set_global_radio_state(enabled)).detach();
                 }
+                NetworkServiceRequest::MenuVisibilityChanged { visible } => {
+                    let _ = inter_sender
+                        .send(NetworkServiceInterEvent::MenuVisibilityChanged { visible })
+                        .await;
+                }
             }
         }
     }
@@ -1,10 +1,23 @@
-use std::{collections::HashSet, fmt::Debug};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
 
 use num_enum::TryFromPrimitive;
 use rusty_network_manager::dbus_interface_types::NMDeviceStateReason;
 use smol::channel::Sender;
 
-use crate::service::network::wireless::ap::{AccessPoint, AccessPointSecurity};
+use crate::service::network::{
+    ethernet::WiredLink,
+    ipconfig::IpSettings,
+    metered::MeteredState,
+    modem::ModemStatus,
+    snapshot::NetworkSnapshot,
+    wireless::{
+        ap::{AccessPoint, AccessPointSecurity, HwAddress},
+        uri::WiFiUri,
+    },
+};
 
 /// Represents the type of a network device.
 #[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, TryFromPrimitive)]
@@ -82,15 +95,33 @@ pub enum NetworkServiceEventType {
     DeviceRemoved,
     DeviceStateChanged,
     AccessPointScanReport,
+    AccessPointUpdated,
     ActiveAccessPointChanged,
     GlobalWirelessEnabledStateChanged,
+    PrimaryConnectionChanged,
+    ScanStateChanged,
+    ModemStatusChanged,
+    MeteredStateChanged,
+    WiredLinkChanged,
+    SavedProfilesChanged,
 }
 
 pub enum WiFiConnServiceRequest {
     /// A client request to connect to a Wi-Fi network.
-    WiFiConnect { ssid: String, key_mgmt: AccessPointSecurity },
-    /// Authentication information (e.g., PSK) provided by the client.
-    ProvideAuthenticationInfo { psk: String },
+    /// If `bssid` is set, only that access point is used and the profile is pinned to it.
+    /// If `ip_settings` is set, it replaces the IPv4/IPv6 configuration of the profile.
+    WiFiConnect {
+        ssid: String,
+        key_mgmt: AccessPointSecurity,
+        bssid: Option<HwAddress>,
+        ip_settings: Option<IpSettings>,
+    },
+    /// Authentication information (e.g., PSK) provided by the client,
+    /// optionally with IP settings entered alongside it.
+    ProvideAuthenticationInfo {
+        psk: String,
+        ip_settings: Option<IpSettings>,
+    },
 }
 
 impl WiFiConnServiceRequest {
@@ -102,11 +133,19 @@ impl WiFiConnServiceRequest {
 impl Debug for WiFiConnServiceRequest {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::WiFiConnect { ssid, key_mgmt } => {
-                write!(f, "WiFiConnect {{ ssid: {}, key_mgmt: {:?} }}", ssid, key_mgmt)
+            Self::WiFiConnect { ssid, key_mgmt, bssid, ip_settings } => {
+                write!(
+                    f,
+                    "WiFiConnect {{ ssid: {}, key_mgmt: {:?}, bssid: {:?}, ip_settings: {:?} }}",
+                    ssid, key_mgmt, bssid, ip_settings
+                )
             }
-            Self::ProvideAuthenticationInfo { psk: _ } => {
-                write!(f, "ProvideAuthenticationInfo {{ psk: {{ ... }} }}")
+            Self::ProvideAuthenticationInfo { psk: _, ip_settings } => {
+                write!(
+                    f,
+                    "ProvideAuthenticationInfo {{ psk: {{ ... }}, ip_settings: {:?} }}",
+                    ip_settings
+                )
             }
         }
     }
@@ -161,6 +200,20 @@ pub enum NetworkServiceRequest {
         interface: String,
         channel: Sender<WiFiConnServiceMessage>,
     },
+    /// Request to create a profile from a `WIFI:` URI and connect to it.
+    /// The channel receives whether the connection was activated.
+    WiFiConnectUri {
+        interface: String,
+        uri: WiFiUri,
+        channel: Sender<bool>,
+    },
+    /// Request the `WIFI:` URI of a saved network, including its password.
+    /// The channel receives `None` if there is no saved profile for the network.
+    GetWiFiShareUri {
+        ssid: String,
+        key_mgmt: AccessPointSecurity,
+        channel: Sender<Option<WiFiUri>>,
+    },
     /// Request to scan for Wi-Fi networks on a specific interface.
     WiFiScan {
         interface: String,
@@ -169,10 +222,47 @@ pub enum NetworkServiceRequest {
     WiFiDisconnect {
         interface: String,
     },
+    /// Request to activate a wired profile on an Ethernet device.
+    EthernetConnect {
+        interface: String,
+    },
+    /// Request to disconnect an Ethernet device.
+    EthernetDisconnect {
+        interface: String,
+    },
+    /// Request to activate the mobile broadband (GSM) profile of a modem.
+    ModemConnect {
+        interface: String,
+    },
+    /// Request to disconnect a modem.
+    ModemDisconnect {
+        interface: String,
+    },
+    /// Request a snapshot of the current service state: registered interfaces with their
+    /// state, active access point and scan results, the radio state and saved profiles.
+    /// Meant for listeners that register after the service started.
+    GetSnapshot {
+        channel: Sender<NetworkSnapshot>,
+    },
+    /// Request the metered state of the primary connection.
+    /// Meant for modules that want to avoid large downloads on metered connections.
+    GetMeteredState {
+        channel: Sender<MeteredState>,
+    },
+    /// Request to set `connection.metered` on the profile of the primary connection.
+    /// `None` lets NetworkManager guess again.
+    SetMetered {
+        metered: Option<bool>,
+    },
     /// Request to set the global wireless enabled state.
     SetGlobalWirelessEnabledState {
         enabled: bool,
     },
+    /// Tells the scan scheduler whether the network menu is open.
+    /// Opening the menu triggers a scan and keeps scanning periodically until it closes.
+    MenuVisibilityChanged {
+        visible: bool,
+    },
 }
 
 /// Represents events that occur within the network service, 
@@ -194,10 +284,16 @@ pub enum NetworkServiceEvent {
         state: NetworkDeviceState,
         reason: NMDeviceStateReason,
     },
-    /// Reports the results of a Wi-Fi access point scan.
+    /// Reports the results of a Wi-Fi access point scan,
+    /// grouped by `(SSID, security)` with every BSSID in the group.
     AccessPointScanReport {
         interface: String,
-        access_points: HashSet<(String, AccessPointSecurity)>,
+        access_points: HashMap<(String, AccessPointSecurity), Vec<AccessPoint>>,
+    },
+    /// Reports updated signal strength, last seen time or frequency of a known access point.
+    AccessPointUpdated {
+        interface: String,
+        ap: AccessPoint,
     },
     /// Reports a change in the global wireless enabled state.
     GlobalWirelessEnabledStateChanged {
@@ -208,6 +304,36 @@ pub enum NetworkServiceEvent {
         interface: String,
         ap: AccessPoint,
     },
+    /// Indicates that NetworkManager's primary connection has changed.
+    /// All fields are `None` when there is no primary connection.
+    PrimaryConnectionChanged {
+        interface: Option<String>,
+        device_type: Option<NetworkDeviceType>,
+        connection_id: Option<String>,
+    },
+    /// Indicates that a Wi-Fi scan started or finished on an interface.
+    ScanStateChanged {
+        interface: String,
+        scanning: bool,
+    },
+    /// Reports signal quality, access technology or operator changes of a modem.
+    ModemStatusChanged {
+        interface: String,
+        status: ModemStatus,
+    },
+    /// Reports carrier, speed or hardware address changes of an Ethernet device.
+    WiredLinkChanged {
+        interface: String,
+        link: WiredLink,
+    },
+    /// Indicates that the metered state of the primary connection has changed.
+    MeteredStateChanged {
+        state: MeteredState,
+    },
+    /// Lists the `(SSID, security)` pairs that have a saved Wi-Fi profile.
+    SavedProfilesChanged {
+        profiles: HashSet<(String, AccessPointSecurity)>,
+    },
     /// Return a command sender for registering event handlers.
     HandlerRegistered {
         command_sender: Sender<NetworkServiceRequest>,
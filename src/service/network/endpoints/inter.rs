@@ -2,10 +2,9 @@ use std::collections::{HashMap, HashSet};
 
 use smol::channel::Sender;
 use tracing::{error, info, instrument};
-use zbus::zvariant::ObjectPath;
 
 use crate::service::network::{
-    ethernet::EthernetWatchDogExt, wireless::ap::{AccessPoint, AccessPointSecurity}, NetworkService, WirelessScanExt, WirelessWatchDogExt
+    ethernet::EthernetWatchDogExt, metered::MeteredState, snapshot::NetworkSnapshot, wireless::ap::{AccessPoint, AccessPointSecurity}, NetworkService, ScanSchedulerEvent, WirelessWatchDogExt
 };
 
 use super::event::*;
@@ -32,6 +31,11 @@ pub(in super::super) enum NetworkServiceInterEvent {
         interface: String,
         access_points: HashMap<(String, AccessPointSecurity), Vec<AccessPoint>>,
     },
+    /// Updates a single access point whose properties changed.
+    UpdateAccessPoint {
+        interface: String,
+        ap: AccessPoint,
+    },
     /// Requests information about a specific access point.
     GetAccessPoints {
         interface: String,
@@ -60,8 +64,20 @@ pub(in super::super) enum NetworkServiceInterEvent {
         key_mgmt: AccessPointSecurity,
         sender: Sender<Option<bool>>, // Channel to send profile and validity
     },
+    /// Stores the metered state of the primary connection, notifying listeners if it changed.
+    UpdateMeteredState { state: MeteredState },
+    /// Requests the metered state of the primary connection.
+    GetMeteredState {
+        sender: Sender<MeteredState>, // Channel to send the state back
+    },
+    /// Requests a snapshot of the service state.
+    GetSnapshot {
+        sender: Sender<NetworkSnapshot>, // Channel to send the snapshot back
+    },
     /// Initiates an immediate Wi-Fi scan on the specified interface.
     ScanNow { interface: String },
+    /// Forwards the network menu visibility to the scan scheduler.
+    MenuVisibilityChanged { visible: bool },
 }
 
 #[async_trait::async_trait]
@@ -79,8 +95,18 @@ impl NetworkServiceInterEndpointExt for NetworkService {
         while let Ok(event) = self.inter_channel.1.recv().await {
             match event {
                 NetworkServiceInterEvent::SendMessage { event_type, event } => {
+                    // Remember the state for snapshots.
+                    self.storage.observe_event(&event);
+                    // The scan scheduler picks its devices by state.
+                    if let NetworkServiceEvent::DeviceStateChanged { .. } = event {
+                        let _ = self
+                            .scan_channel
+                            .0
+                            .send(ScanSchedulerEvent::DeviceStateChanged)
+                            .await;
+                    }
                     // Dispatch the network event to registered listeners.
-                    self.send_msg(event_type, event).await;
+                    self.send_msg(event_type, event);
                 }
                 NetworkServiceInterEvent::RegisterInterface {
                     dbus_path,
@@ -94,15 +120,14 @@ impl NetworkServiceInterEndpointExt for NetworkService {
                         interface, device_type
                     );
                     self.storage
-                        .register_interface(interface.clone(), dbus_path.clone(), task);
+                        .register_interface(interface.clone(), dbus_path.clone(), device_type, task);
                     self.send_msg(
                         NetworkServiceEventType::DeviceAdded,
                         NetworkServiceEvent::DeviceAdded {
                             interface,
                             device_type,
                         },
-                    )
-                    .await;
+                    );
                 }
                 NetworkServiceInterEvent::UnregisterInterface { dbus_path } => {
                     // Unregister an interface and notify listeners if it was found.
@@ -116,8 +141,7 @@ impl NetworkServiceInterEndpointExt for NetworkService {
                         self.send_msg(
                             NetworkServiceEventType::DeviceRemoved,
                             NetworkServiceEvent::DeviceRemoved { interface },
-                        )
-                        .await;
+                        );
                     }
                 }
                 NetworkServiceInterEvent::RefreshAccessPoints {
@@ -131,27 +155,43 @@ impl NetworkServiceInterEndpointExt for NetworkService {
                         NetworkServiceEventType::AccessPointScanReport,
                         NetworkServiceEvent::AccessPointScanReport {
                             interface: interface.clone(),
-                            access_points: access_points
-                                .keys()
-                                .map(|(ssid, key_mgmt)| (ssid.clone(), key_mgmt.clone()))
-                                .collect(),
+                            access_points,
                         },
-                    )
-                    .await;
+                    );
+                }
+                NetworkServiceInterEvent::UpdateAccessPoint { interface, ap } => {
+                    // Update the stored AP and notify listeners without a full scan report.
+                    self.storage.update_access_point(&interface, ap.clone());
+                    self.send_msg(
+                        NetworkServiceEventType::AccessPointUpdated,
+                        NetworkServiceEvent::AccessPointUpdated { interface, ap },
+                    );
                 }
                 NetworkServiceInterEvent::ScanNow { interface } => {
-                    // Request an immediate Wi-Fi scan on the specified interface.
-                    if let Some(dbus_addr) = self.storage.get_dbus_path_by_interface(&interface) {
-                        smol::spawn(Self::request_scan(
-                            ObjectPath::try_from(dbus_addr.clone()).unwrap().into(),
-                        ))
-                        .detach();
+                    // Let the scheduler scan the interface as soon as rate limits allow.
+                    if self.storage.get_dbus_path_by_interface(&interface).is_some() {
+                        let _ = self
+                            .scan_channel
+                            .0
+                            .send(ScanSchedulerEvent::ScanNow(interface))
+                            .await;
                     }
                 }
+                NetworkServiceInterEvent::MenuVisibilityChanged { visible } => {
+                    let _ = self
+                        .scan_channel
+                        .0
+                        .send(ScanSchedulerEvent::MenuVisibilityChanged(visible))
+                        .await;
+                }
                 NetworkServiceInterEvent::RefreshAPConnections { map } => {
                     // Update the storage with the latest AP connection profiles.
                     info!("Refreshing AP connections.");
-                    self.storage.refresh_ap_connections(map);
+                    self.storage.refresh_ap_connections(map.clone());
+                    self.send_msg(
+                        NetworkServiceEventType::SavedProfilesChanged,
+                        NetworkServiceEvent::SavedProfilesChanged { profiles: map },
+                    );
                 }
                 NetworkServiceInterEvent::GetAccessPoints {
                     interface,
@@ -196,6 +236,27 @@ impl NetworkServiceInterEndpointExt for NetworkService {
                         error!("Failed to send AP connection profile.");
                     }
                 }
+                NetworkServiceInterEvent::UpdateMeteredState { state } => {
+                    // Only notify listeners about actual changes.
+                    if self.storage.update_metered_state(state) {
+                        self.send_msg(
+                            NetworkServiceEventType::MeteredStateChanged,
+                            NetworkServiceEvent::MeteredStateChanged { state },
+                        );
+                    }
+                }
+                NetworkServiceInterEvent::GetMeteredState { sender } => {
+                    // Send the stored metered state to the requester.
+                    if sender.send(self.storage.metered_state()).await.is_err() {
+                        error!("Failed to send metered state.");
+                    }
+                }
+                NetworkServiceInterEvent::GetSnapshot { sender } => {
+                    // Build and send a snapshot of the stored state to the requester.
+                    if sender.send(self.storage.snapshot()).await.is_err() {
+                        error!("Failed to send snapshot.");
+                    }
+                }
                 NetworkServiceInterEvent::GetInterfaceDBusPath { interface, sender } => {
                     // Retrieve and send the D-Bus path for an interface to the requester.
                     let ret = sender
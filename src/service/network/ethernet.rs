@@ -1,20 +1,129 @@
-use rusty_network_manager::{DeviceProxy, dbus_interface_types::NMDeviceStateReason};
-use smol::{channel::Sender, stream::StreamExt};
-use tracing::{error, info, instrument};
-use zbus::zvariant::OwnedObjectPath;
+use std::pin::Pin;
+
+use futures_lite::future;
+use futures_util::{Stream, StreamExt};
+use rusty_network_manager::{DeviceProxy, NetworkManagerProxy, WiredProxy, dbus_interface_types::NMDeviceStateReason};
+use smol::channel::Sender;
+use tracing::{debug, error, info, instrument, warn};
+use zbus::{
+    Connection,
+    zvariant::{ObjectPath, OwnedObjectPath},
+};
 
 use crate::service::network::endpoints::event::{NetworkDeviceState, NetworkServiceEvent, NetworkServiceEventType};
 
 use super::{endpoints::inter::NetworkServiceInterEvent, NetworkService, DBUS_CONNECTION};
 
+/// Link information of a wired device, from `org.freedesktop.NetworkManager.Device.Wired`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WiredLink {
+    /// A cable is plugged in and the link is up.
+    pub carrier: bool,
+    /// Negotiated speed in Mb/s, `0` if unknown.
+    pub speed: u32,
+    /// Permanent hardware address of the device.
+    pub perm_hw_address: String,
+}
+
+/// A property change of a watched wired device.
+enum WiredPropertyChange {
+    Carrier(bool),
+    Speed(u32),
+    PermHwAddress(String),
+}
+
+impl WiredLink {
+    /// Formats the speed, e.g. `100 Mb/s`, `1 Gb/s` or `2.5 Gb/s`.
+    /// Returns `None` if the speed is unknown.
+    pub fn speed_label(&self) -> Option<String> {
+        match self.speed {
+            0 => None,
+            speed if speed < 1000 => Some(format!("{} Mb/s", speed)),
+            speed if speed % 1000 == 0 => Some(format!("{} Gb/s", speed / 1000)),
+            speed => Some(format!("{} Gb/s", speed as f64 / 1000.0)),
+        }
+    }
+
+    fn apply(&mut self, change: WiredPropertyChange) {
+        match change {
+            WiredPropertyChange::Carrier(carrier) => self.carrier = carrier,
+            WiredPropertyChange::Speed(speed) => self.speed = speed,
+            WiredPropertyChange::PermHwAddress(address) => self.perm_hw_address = address,
+        }
+    }
+
+    /// Describes the link, e.g. `1 Gb/s, cable connected` or `Cable unplugged`.
+    pub fn description(&self) -> String {
+        match (self.carrier, self.speed_label()) {
+            (false, _) => "Cable unplugged".to_string(),
+            (true, Some(speed)) => format!("{}, cable connected", speed),
+            (true, None) => "Cable connected".to_string(),
+        }
+    }
+}
+
+async fn read_link(wired: &WiredProxy<'_>) -> zbus::Result<WiredLink> {
+    Ok(WiredLink {
+        carrier: wired.carrier().await?,
+        speed: wired.speed().await?,
+        perm_hw_address: wired.perm_hw_address().await?,
+    })
+}
+
+/// Sends the link information of the wired device at `device_path` to `sender`,
+/// and again every time it changes.
+///
+/// Returns once the device disappears from the bus or `sender` is closed.
+pub async fn watch_wired_link(
+    connection: &Connection,
+    device_path: OwnedObjectPath,
+    sender: Sender<WiredLink>,
+) -> zbus::Result<()> {
+    let wired = WiredProxy::new_from_path(device_path, connection).await?;
+
+    // Subscribe before reading so no change can slip in between.
+    let streams: Vec<Pin<Box<dyn Stream<Item = WiredPropertyChange> + Send>>> = vec![
+        wired
+            .receive_carrier_changed()
+            .await
+            .filter_map(|c| async move { c.get().await.ok().map(WiredPropertyChange::Carrier) })
+            .boxed(),
+        wired
+            .receive_speed_changed()
+            .await
+            .filter_map(|c| async move { c.get().await.ok().map(WiredPropertyChange::Speed) })
+            .boxed(),
+        wired
+            .receive_perm_hw_address_changed()
+            .await
+            .filter_map(|c| async move { c.get().await.ok().map(WiredPropertyChange::PermHwAddress) })
+            .boxed(),
+    ];
+
+    let mut link = read_link(&wired).await?;
+    if sender.send(link.clone()).await.is_err() {
+        return Ok(());
+    }
+
+    let mut changes = futures_util::stream::select_all(streams);
+    while let Some(change) = changes.next().await {
+        let previous = link.clone();
+        link.apply(change);
+        if link != previous && sender.send(link.clone()).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
 #[async_trait::async_trait]
 pub(in super::super) trait EthernetWatchDogExt {
-    /// A watchdog function for a Ethernet device.
+    /// A watchdog function for the state of any device.
     ///
     /// Monitors device state changes.
     /// Sends events to the `NetworkService` to update its state accordingly.
     #[instrument(skip_all)]
-    async fn ethernet_watchdog(
+    async fn device_state_watchdog(
         sender: Sender<NetworkServiceInterEvent>,
         device_path: OwnedObjectPath,
     ) {
@@ -98,6 +207,70 @@ pub(in super::super) trait EthernetWatchDogExt {
             device_path
         );
     }
+
+    /// A watchdog function for a Ethernet device.
+    ///
+    /// Monitors device state changes like `device_state_watchdog`, and follows carrier,
+    /// speed and hardware address of the link, reported as `WiredLinkChanged` events.
+    #[instrument(skip_all)]
+    async fn ethernet_watchdog(
+        sender: Sender<NetworkServiceInterEvent>,
+        device_path: OwnedObjectPath,
+    ) {
+        let Ok(device) = DeviceProxy::new_from_path(device_path.clone(), &DBUS_CONNECTION).await else {
+            error!("Failed to create device proxy for {:?}", device_path);
+            return;
+        };
+        let Ok(interface) = device.interface().await else {
+            error!("Failed to get interface for {:?}", device_path);
+            return;
+        };
+
+        let (link_tx, link_rx) = smol::channel::unbounded();
+        let watch_link = async {
+            if let Err(e) = watch_wired_link(&DBUS_CONNECTION, device_path.clone(), link_tx).await {
+                warn!("Failed to watch wired link of {}: {}", interface, e);
+            }
+        };
+        let report_link = async {
+            while let Ok(link) = link_rx.recv().await {
+                debug!("Wired link changed: {:?} for interface {}", link, interface);
+                let ret = sender
+                    .send(NetworkServiceInterEvent::SendMessage {
+                        event_type: NetworkServiceEventType::WiredLinkChanged,
+                        event: NetworkServiceEvent::WiredLinkChanged {
+                            interface: interface.clone(),
+                            link,
+                        },
+                    })
+                    .await;
+                if ret.is_err() {
+                    break;
+                }
+            }
+        };
+
+        future::zip(
+            Self::device_state_watchdog(sender.clone(), device_path.clone()),
+            future::zip(watch_link, report_link),
+        )
+        .await;
+    }
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait EthernetConnExt {
+    /// Activates the best available profile on a wired device.
+    ///
+    /// NetworkManager picks the profile itself when no connection path is given.
+    async fn connect_ethernet(device_path: OwnedObjectPath) -> zbus::Result<()> {
+        let nm = NetworkManagerProxy::new(&DBUS_CONNECTION).await?;
+        let root = ObjectPath::try_from("/").unwrap();
+        info!("Activating wired connection on {:?}", device_path);
+        nm.activate_connection(&root, &device_path, &root).await?;
+        Ok(())
+    }
 }
 
-impl EthernetWatchDogExt for NetworkService {}
\ No newline at end of file
+impl EthernetWatchDogExt for NetworkService {}
+impl EthernetConnExt for NetworkService {}
@@ -0,0 +1,209 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use zbus::zvariant::Value;
+
+/// How a connection obtains addresses for one IP family.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IpMethod {
+    /// DHCP / SLAAC.
+    #[default]
+    Auto,
+    /// Only the addresses configured in the profile.
+    Manual,
+    /// Link-local addresses only.
+    LinkLocal,
+    /// The IP family is disabled.
+    Disabled,
+}
+
+impl IpMethod {
+    /// Returns NetworkManager's name for this method.
+    pub fn as_nm_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Manual => "manual",
+            Self::LinkLocal => "link-local",
+            Self::Disabled => "disabled",
+        }
+    }
+}
+
+/// An IP family usable in an `IpConfig`.
+pub trait IpFamily: Copy + Display + FromStr + PartialEq + std::fmt::Debug {
+    /// The largest valid prefix length.
+    const MAX_PREFIX: u32;
+
+    /// Encodes DNS servers the way NetworkManager expects them in the `dns` property.
+    fn dns_value(servers: &[Self]) -> Value<'static>;
+}
+
+impl IpFamily for Ipv4Addr {
+    const MAX_PREFIX: u32 = 32;
+
+    fn dns_value(servers: &[Self]) -> Value<'static> {
+        // `au`, each address in network byte order.
+        servers
+            .iter()
+            .map(|addr| u32::from_ne_bytes(addr.octets()))
+            .collect::<Vec<u32>>()
+            .into()
+    }
+}
+
+impl IpFamily for Ipv6Addr {
+    const MAX_PREFIX: u32 = 128;
+
+    fn dns_value(servers: &[Self]) -> Value<'static> {
+        // `aay`, each address as 16 raw bytes.
+        servers
+            .iter()
+            .map(|addr| addr.octets().to_vec())
+            .collect::<Vec<Vec<u8>>>()
+            .into()
+    }
+}
+
+/// A static address with its prefix length.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IpAddressEntry<A: IpFamily> {
+    pub address: A,
+    pub prefix: u32,
+}
+
+impl<A: IpFamily> FromStr for IpAddressEntry<A> {
+    type Err = IpConfigError;
+
+    /// Parses `address/prefix`, e.g. `192.168.1.10/24`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix) = s
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| IpConfigError::InvalidAddress(s.to_string()))?;
+        let address = address
+            .parse()
+            .map_err(|_| IpConfigError::InvalidAddress(s.to_string()))?;
+        let prefix = prefix
+            .parse()
+            .ok()
+            .filter(|p| *p <= A::MAX_PREFIX)
+            .ok_or_else(|| IpConfigError::InvalidPrefix(s.to_string()))?;
+        Ok(Self { address, prefix })
+    }
+}
+
+/// Errors raised while parsing or validating an `IpConfig`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IpConfigError {
+    /// The address could not be parsed.
+    InvalidAddress(String),
+    /// The prefix is missing or out of range.
+    InvalidPrefix(String),
+    /// The route metric is not an integer.
+    InvalidRouteMetric(String),
+    /// The `manual` method was selected without any address.
+    MissingAddress,
+}
+
+impl Display for IpConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidAddress(s) => write!(f, "invalid address: {}", s),
+            Self::InvalidPrefix(s) => write!(f, "invalid prefix: {}", s),
+            Self::InvalidRouteMetric(s) => write!(f, "invalid route metric: {}", s),
+            Self::MissingAddress => write!(f, "manual configuration requires an address"),
+        }
+    }
+}
+
+impl std::error::Error for IpConfigError {}
+
+/// Settings of one IP family of a connection profile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IpConfig<A: IpFamily> {
+    pub method: IpMethod,
+    pub addresses: Vec<IpAddressEntry<A>>,
+    pub gateway: Option<A>,
+    pub dns: Vec<A>,
+    /// Ignore DNS servers received from DHCP / router advertisements.
+    pub ignore_auto_dns: bool,
+    /// Metric of the default route, `None` lets NetworkManager choose.
+    pub route_metric: Option<i64>,
+}
+
+impl<A: IpFamily> Default for IpConfig<A> {
+    fn default() -> Self {
+        Self {
+            method: IpMethod::Auto,
+            addresses: Vec::new(),
+            gateway: None,
+            dns: Vec::new(),
+            ignore_auto_dns: false,
+            route_metric: None,
+        }
+    }
+}
+
+impl<A: IpFamily> IpConfig<A> {
+    /// Checks that the configuration can be applied.
+    pub fn validate(&self) -> Result<(), IpConfigError> {
+        if self.method == IpMethod::Manual && self.addresses.is_empty() {
+            return Err(IpConfigError::MissingAddress);
+        }
+        Ok(())
+    }
+
+    /// Converts the configuration into an `ipv4` / `ipv6` settings section.
+    pub fn to_settings(&self) -> HashMap<&'static str, Value<'static>> {
+        let mut section = HashMap::new();
+        section.insert("method", Value::from(self.method.as_nm_str()));
+
+        if self.method == IpMethod::Manual {
+            let address_data: Vec<HashMap<String, Value<'static>>> = self
+                .addresses
+                .iter()
+                .map(|entry| {
+                    HashMap::from([
+                        ("address".to_string(), Value::from(entry.address.to_string())),
+                        ("prefix".to_string(), Value::from(entry.prefix)),
+                    ])
+                })
+                .collect();
+            section.insert("address-data", Value::from(address_data));
+            if let Some(gateway) = &self.gateway {
+                section.insert("gateway", Value::from(gateway.to_string()));
+            }
+        }
+
+        if !self.dns.is_empty() {
+            section.insert("dns", A::dns_value(&self.dns));
+        }
+        if self.ignore_auto_dns {
+            section.insert("ignore-auto-dns", Value::from(true));
+        }
+        if let Some(metric) = self.route_metric {
+            section.insert("route-metric", Value::from(metric));
+        }
+
+        section
+    }
+}
+
+/// IPv4 and IPv6 settings of a connection profile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IpSettings {
+    pub ipv4: IpConfig<Ipv4Addr>,
+    pub ipv6: IpConfig<Ipv6Addr>,
+}
+
+impl IpSettings {
+    /// Checks that both families can be applied.
+    pub fn validate(&self) -> Result<(), IpConfigError> {
+        self.ipv4.validate()?;
+        self.ipv6.validate()
+    }
+}
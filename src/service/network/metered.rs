@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use num_enum::TryFromPrimitive;
+use rusty_network_manager::{ActiveProxy, DeviceProxy, NetworkManagerProxy, SettingsConnectionProxy};
+use tracing::{info, warn};
+use zbus::zvariant::Value;
+
+use super::{DBUS_CONNECTION, NetworkService};
+
+/// `NMMetered`, used both for the `Metered` property of devices and for the
+/// `connection.metered` setting of profiles (which only uses `Unknown`, `Yes` and `No`).
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, Default, TryFromPrimitive)]
+#[repr(u32)]
+pub enum NetworkMetered {
+    /// For profiles: let NetworkManager decide.
+    #[default]
+    Unknown = 0,
+    Yes = 1,
+    No = 2,
+    GuessYes = 3,
+    GuessNo = 4,
+}
+
+impl NetworkMetered {
+    /// Whether traffic should be treated as metered.
+    pub fn is_metered(&self) -> bool {
+        matches!(self, Self::Yes | Self::GuessYes)
+    }
+}
+
+/// Metered state of the primary connection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MeteredState {
+    /// What NetworkManager decided for the primary device, including guesses.
+    pub device: NetworkMetered,
+    /// `connection.metered` of the active profile, `Unknown` if it is left automatic.
+    pub profile: NetworkMetered,
+}
+
+impl MeteredState {
+    pub fn is_metered(&self) -> bool {
+        self.device.is_metered()
+    }
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait MeteredExt {
+    /// Returns the first device of an active connection.
+    async fn primary_device(active: Option<&ActiveProxy<'static>>) -> Option<DeviceProxy<'static>> {
+        let path = active?.devices().await.ok()?.into_iter().next()?;
+        DeviceProxy::new_from_path(path, &DBUS_CONNECTION).await.ok()
+    }
+
+    /// Reads the metered state of the primary device and the profile it is running.
+    async fn metered_state(
+        active: Option<&ActiveProxy<'static>>,
+        device: Option<&DeviceProxy<'static>>,
+    ) -> MeteredState {
+        let device_metered = match device {
+            Some(device) => device
+                .metered()
+                .await
+                .ok()
+                .and_then(|m| NetworkMetered::try_from(m).ok())
+                .unwrap_or_default(),
+            None => NetworkMetered::Unknown,
+        };
+
+        let profile_metered = match active {
+            Some(active) => Self::profile_metered(active).await.unwrap_or_default(),
+            None => NetworkMetered::Unknown,
+        };
+
+        MeteredState {
+            device: device_metered,
+            profile: profile_metered,
+        }
+    }
+
+    /// Reads `connection.metered` from the profile of an active connection.
+    async fn profile_metered(active: &ActiveProxy<'static>) -> Option<NetworkMetered> {
+        let path = active.connection().await.ok()?;
+        let profile = SettingsConnectionProxy::new_from_path(path, &DBUS_CONNECTION).await.ok()?;
+        let cfg = profile.get_settings().await.ok()?;
+        let metered = i32::try_from(cfg.get("connection")?.get("metered")?).ok()?;
+        NetworkMetered::try_from(metered as u32).ok()
+    }
+
+    /// Sets `connection.metered` on the profile of the primary connection and reapplies it.
+    ///
+    /// `None` hands the decision back to NetworkManager.
+    /// Returns the resulting state, or `None` if there is no primary connection.
+    async fn set_primary_metered(metered: Option<bool>) -> zbus::Result<Option<MeteredState>> {
+        let nm = NetworkManagerProxy::new(&DBUS_CONNECTION).await?;
+        let primary = nm.primary_connection().await?;
+        if primary.as_str() == "/" {
+            return Ok(None);
+        }
+        let active = ActiveProxy::new_from_path(primary, &DBUS_CONNECTION).await?;
+        let profile = SettingsConnectionProxy::new_from_path(active.connection().await?, &DBUS_CONNECTION).await?;
+
+        let setting = match metered {
+            None => NetworkMetered::Unknown,
+            Some(true) => NetworkMetered::Yes,
+            Some(false) => NetworkMetered::No,
+        };
+        let cfg = profile.get_settings().await?;
+        let mut updated: HashMap<&str, HashMap<&str, Value>> = cfg
+            .iter()
+            .map(|(section, values)| {
+                let values = values
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.as_str(), Value::try_from(v).ok()?)))
+                    .collect();
+                (section.as_str(), values)
+            })
+            .collect();
+        updated
+            .entry("connection")
+            .or_default()
+            .insert("metered", Value::from(setting as i32));
+        profile.update(updated).await?;
+        info!("Set connection.metered to {:?} on the primary connection", setting);
+
+        // The new value only reaches the device once the profile is reapplied.
+        let device = Self::primary_device(Some(&active)).await;
+        let reapplied = match device.as_ref() {
+            Some(device) => device.reapply(HashMap::new(), 0, 0).await,
+            None => Ok(()),
+        };
+        if let Err(e) = reapplied {
+            warn!("Failed to reapply the primary connection: {}", e);
+        }
+        Ok(Some(Self::metered_state(Some(&active), device.as_ref()).await))
+    }
+}
+
+impl MeteredExt for NetworkService {}
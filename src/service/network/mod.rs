@@ -2,6 +2,11 @@ pub mod ethernet;
 pub mod wireless;
 pub mod endpoints;
 pub mod devices;
+pub mod primary;
+pub mod ipconfig;
+pub mod modem;
+pub mod metered;
+pub mod snapshot;
 
 use std::collections::{HashMap, HashSet};
 
@@ -11,15 +16,17 @@ use smol::{
     channel::{Receiver, Sender},
     stream::StreamExt,
 };
-use tracing::{info, instrument, warn};
 use zbus::Connection;
 
 use wireless::prelude::*;
 
 use devices::NetworkServiceDeviceExt;
+use metered::MeteredState;
+use snapshot::{NetworkDeviceSnapshot, NetworkSnapshot, PrimaryConnectionSnapshot};
+use primary::PrimaryConnectionExt;
 use endpoints::{event::*, inter::{NetworkServiceInterEndpointExt, NetworkServiceInterEvent}, command::NetworkServiceCommandEndpointExt};
 
-use super::event::EventListener;
+use super::event::{EventBus, EventListener, SubscribeOptions, Subscription};
 
 lazy_static::lazy_static! {
     static ref DBUS_CONNECTION: Connection = smol::block_on(Connection::system())
@@ -34,6 +41,10 @@ pub struct NetworkServiceStorage {
     dbus_interface_map: BiHashMap<String, String>, // Bidirectional map: D-Bus path <=> interface name
     interface_ap_map: HashMap<String, HashMap<(String, AccessPointSecurity), Vec<AccessPoint>>>, // Map of interface name to its APs
     ap_connection_map: HashMap<(String, AccessPointSecurity), bool>, // Map of profile exists AP (SSID, KeyMgmt) to profile validation status
+    metered: MeteredState, // Metered state of the primary connection
+    devices: HashMap<String, NetworkDeviceSnapshot>, // Map of interface name to its last reported details
+    wireless_enabled: Option<bool>, // Last reported global wireless state
+    primary_connection: PrimaryConnectionSnapshot, // Last reported primary connection
 }
 
 impl NetworkServiceStorage {
@@ -47,16 +58,20 @@ impl NetworkServiceStorage {
     /// # Arguments
     /// * `interface` - The name of the interface (e.g., "wlan0").
     /// * `dbus_path` - The D-Bus object path for the interface.
+    /// * `device_type` - The type of the device.
     /// * `task` - The smol task associated with managing this interface.
     pub fn register_interface(
         &mut self,
         interface: String,
         dbus_path: String,
+        device_type: NetworkDeviceType,
         task: smol::Task<()>,
     ) {
         self.interfaces.insert(interface.clone(), task);
         self.dbus_interface_map
             .insert(dbus_path.clone(), interface.clone());
+        self.devices
+            .insert(interface.clone(), NetworkDeviceSnapshot::new(interface, device_type));
     }
 
     /// Unregisters a network interface by its name.
@@ -65,6 +80,7 @@ impl NetworkServiceStorage {
         self.interfaces.remove(interface);
         if let Some((k, _)) = self.dbus_interface_map.remove_by_right(interface) {
             self.interface_ap_map.remove(interface);
+            self.devices.remove(interface);
             return Some(interface.to_string());
         }
         None
@@ -76,6 +92,7 @@ impl NetworkServiceStorage {
         if let Some((interface, _)) = self.dbus_interface_map.remove_by_left(dbus_path) {
             self.interfaces.remove(&interface);
             self.interface_ap_map.remove(&interface);
+            self.devices.remove(&interface);
             return Some(interface);
         }
         None
@@ -91,6 +108,19 @@ impl NetworkServiceStorage {
             .insert(interface.clone(), access_points);
     }
 
+    /// Replaces a stored access point with an updated copy, matched by its D-Bus path.
+    pub fn update_access_point(&mut self, interface: &str, ap: AccessPoint) {
+        if let Some(aps) = self
+            .interface_ap_map
+            .get_mut(interface)
+            .and_then(|map| map.get_mut(&(ap.ssid.clone(), ap.key_management())))
+        {
+            if let Some(stored) = aps.iter_mut().find(|stored| stored.dbus_path == ap.dbus_path) {
+                *stored = ap;
+            }
+        }
+    }
+
     /// Tries to find an access point by its hardware address on a given interface.
     /// Returns a reference to the map of access points for the interface if the AP is found.
     pub fn try_find_access_points(
@@ -151,14 +181,107 @@ impl NetworkServiceStorage {
     ) -> Option<bool> {
         self.ap_connection_map.get(&(ssid.to_string(), key_mgmt)).cloned()
     }
+
+    /// Stores the metered state of the primary connection.
+    /// Returns `true` if it differs from the previously stored state.
+    pub fn update_metered_state(&mut self, state: MeteredState) -> bool {
+        std::mem::replace(&mut self.metered, state) != state
+    }
+
+    /// Gets the metered state of the primary connection.
+    pub fn metered_state(&self) -> MeteredState {
+        self.metered
+    }
+
+    /// Records the state carried by an event before it is sent to listeners,
+    /// so that `snapshot` can hand it to listeners registering later.
+    /// Events for unregistered interfaces are ignored.
+    pub fn observe_event(&mut self, event: &NetworkServiceEvent) {
+        match event {
+            NetworkServiceEvent::DeviceStateChanged { interface, state, .. } => {
+                if let Some(device) = self.devices.get_mut(interface) {
+                    device.state = Some(*state);
+                }
+            }
+            NetworkServiceEvent::ActiveAccessPointChanged { interface, ap } => {
+                if let Some(device) = self.devices.get_mut(interface) {
+                    device.active_access_point = Some(ap.clone());
+                }
+            }
+            NetworkServiceEvent::AccessPointUpdated { interface, ap } => {
+                if let Some(active) = self
+                    .devices
+                    .get_mut(interface)
+                    .and_then(|device| device.active_access_point.as_mut())
+                    .filter(|active| active.dbus_path == ap.dbus_path)
+                {
+                    *active = ap.clone();
+                }
+            }
+            NetworkServiceEvent::ScanStateChanged { interface, scanning } => {
+                if let Some(device) = self.devices.get_mut(interface) {
+                    device.scanning = *scanning;
+                }
+            }
+            NetworkServiceEvent::WiredLinkChanged { interface, link } => {
+                if let Some(device) = self.devices.get_mut(interface) {
+                    device.wired_link = Some(link.clone());
+                }
+            }
+            NetworkServiceEvent::ModemStatusChanged { interface, status } => {
+                if let Some(device) = self.devices.get_mut(interface) {
+                    device.modem_status = Some(status.clone());
+                }
+            }
+            NetworkServiceEvent::GlobalWirelessEnabledStateChanged { enabled } => {
+                self.wireless_enabled = Some(*enabled);
+            }
+            NetworkServiceEvent::PrimaryConnectionChanged { interface, device_type, connection_id } => {
+                self.primary_connection = PrimaryConnectionSnapshot {
+                    interface: interface.clone(),
+                    device_type: *device_type,
+                    connection_id: connection_id.clone(),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Builds a snapshot of everything the service currently knows.
+    pub fn snapshot(&self) -> NetworkSnapshot {
+        let mut devices: Vec<NetworkDeviceSnapshot> = self
+            .devices
+            .values()
+            .map(|device| {
+                let mut device = device.clone();
+                device.access_points = self
+                    .interface_ap_map
+                    .get(&device.interface)
+                    .cloned()
+                    .unwrap_or_default();
+                device
+            })
+            .collect();
+        devices.sort_by(|a, b| a.interface.cmp(&b.interface));
+
+        NetworkSnapshot {
+            devices,
+            wireless_enabled: self.wireless_enabled,
+            primary_connection: self.primary_connection.clone(),
+            metered: self.metered,
+            saved_profiles: self.ap_connection_map.clone(),
+        }
+    }
 }
 
 /// Manages network connectivity, devices, and events.
 /// It interacts with NetworkManager via D-Bus to monitor and control network interfaces.
 pub struct NetworkService {
-    handlers: HashMap<NetworkServiceEventType, Vec<Sender<NetworkServiceEvent>>>, // Event listeners
+    events: EventBus<NetworkServiceEventType, NetworkServiceEvent>, // Event listeners
     inter_channel: (Sender<NetworkServiceInterEvent>, Receiver<NetworkServiceInterEvent>), // Internal communication
     command_channel: (Sender<NetworkServiceRequest>, Receiver<NetworkServiceRequest>), // For receiving external commands
+    scan_channel: (Sender<ScanSchedulerEvent>, Receiver<ScanSchedulerEvent>), // For controlling the scan scheduler
+    scan_schedule: ScanScheduleConfig, // Background scan intervals
     storage: NetworkServiceStorage, // Holds the service's state
 }
 
@@ -167,13 +290,32 @@ impl NetworkService {
     /// Initializes internal channels and storage.
     pub fn new() -> Self {
         Self {
-            handlers: HashMap::new(),
+            // Per-device events would only replay the last device; `GetSnapshot` covers those.
+            events: EventBus::new().without_replay([
+                NetworkServiceEventType::DeviceAdded,
+                NetworkServiceEventType::DeviceRemoved,
+                NetworkServiceEventType::DeviceStateChanged,
+                NetworkServiceEventType::AccessPointScanReport,
+                NetworkServiceEventType::AccessPointUpdated,
+                NetworkServiceEventType::ActiveAccessPointChanged,
+                NetworkServiceEventType::ScanStateChanged,
+                NetworkServiceEventType::ModemStatusChanged,
+                NetworkServiceEventType::WiredLinkChanged,
+            ]),
             inter_channel: smol::channel::unbounded::<NetworkServiceInterEvent>(),
             command_channel: smol::channel::unbounded::<NetworkServiceRequest>(),
+            scan_channel: smol::channel::unbounded::<ScanSchedulerEvent>(),
+            scan_schedule: ScanScheduleConfig::default(),
             storage: NetworkServiceStorage::default(),
         }
     }
 
+    /// Replaces the default background scan intervals.
+    pub fn with_scan_schedule(mut self, config: ScanScheduleConfig) -> Self {
+        self.scan_schedule = config;
+        self
+    }
+
     /// Starts the network service, listening for internal events and commands.
     /// This is the main loop of the service.
     /// It spawns tasks for watching devices, syncing connections, and handling commands.
@@ -184,6 +326,14 @@ impl NetworkService {
         smol::spawn(Self::sync_connections(self.inter_channel.0.clone())).detach();
         // Spawn a task to monitor global wireless radio state.
         smol::spawn(Self::radio_watchdog(self.inter_channel.0.clone())).detach();
+        // Spawn a task to follow NetworkManager's primary connection.
+        smol::spawn(Self::primary_connection_watchdog(self.inter_channel.0.clone())).detach();
+        // Spawn a task to schedule background Wi-Fi scans.
+        smol::spawn(Self::scan_scheduler(
+            self.inter_channel.0.clone(),
+            self.scan_channel.1.clone(),
+            self.scan_schedule,
+        )).detach();
         // Spawn a task to handle incoming commands.
         smol::spawn(Self::command_endpoint(
             self.inter_channel.0.clone(),
@@ -193,66 +343,32 @@ impl NetworkService {
         self.inter_event_service().await;
     }
 
-    /// Sends a `NetworkServiceEvent` to all registered listeners for that event type.
-    /// If a listener's channel is closed (send fails), it is removed.
+    /// Publishes a `NetworkServiceEvent` to all listeners subscribed to its event type.
     ///
     /// # Arguments
     /// * `event_type` - The type of the event to send.
     /// * `event` - The actual `NetworkServiceEvent` data.
-    #[instrument(skip_all)]
-    async fn send_msg(&mut self, event_type: NetworkServiceEventType, event: NetworkServiceEvent) {
-        let mut remove_key_if_empty = false;
-        if let Some(senders_vec) = self.handlers.get_mut(&event_type) {
-            let mut active_senders = Vec::with_capacity(senders_vec.len());
-            for sender in senders_vec.drain(..) {
-                // Drains the original vector
-                if sender.send(event.clone()).await.is_ok() {
-                    active_senders.push(sender);
-                } else {
-                    // Listener is removed because send failed (e.g., channel closed)
-                    warn!(
-                        "A listener for {:?} was removed due to send failure.",
-                        event_type
-                    );
-                }
-            }
-            *senders_vec = active_senders;
-
-            if senders_vec.is_empty() {
-                remove_key_if_empty = true;
-            }
-        }
-
-        if remove_key_if_empty {
-            self.handlers.remove(&event_type);
-        }
+    fn send_msg(&self, event_type: NetworkServiceEventType, event: NetworkServiceEvent) {
+        self.events.publish(event_type, event);
     }
 }
 
 impl EventListener<NetworkServiceEventType, NetworkServiceEvent> for NetworkService {
-    fn register_event_handler(&mut self, event_type: NetworkServiceEventType, sender: Sender<NetworkServiceEvent>) {
-        smol::block_on(sender.send(NetworkServiceEvent::HandlerRegistered {
-            command_sender: self.command_channel.0.clone(),
-        })).expect("Handler registration failed.");
-        self.handlers
-            .entry(event_type)
-            .or_insert_with(Vec::new)
-            .push(sender);
+    fn event_bus(&self) -> &EventBus<NetworkServiceEventType, NetworkServiceEvent> {
+        &self.events
     }
 
-    fn register_event_handler_many(
+    fn subscribe(
         &mut self,
         event_types: Vec<NetworkServiceEventType>,
-        sender: Sender<NetworkServiceEvent>,
-    ) {
-        smol::block_on(sender.send(NetworkServiceEvent::HandlerRegistered {
-            command_sender: self.command_channel.0.clone(),
-        })).expect("Handler registration failed.");
-        for event_type in event_types {
-            self.handlers
-                .entry(event_type)
-                .or_insert_with(Vec::new)
-                .push(sender.clone());
-        }
+        options: SubscribeOptions,
+    ) -> Subscription<NetworkServiceEventType, NetworkServiceEvent> {
+        self.events.subscribe_with_initial(
+            event_types,
+            options,
+            vec![NetworkServiceEvent::HandlerRegistered {
+                command_sender: self.command_channel.0.clone(),
+            }],
+        )
     }
-}
\ No newline at end of file
+}
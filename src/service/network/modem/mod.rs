@@ -0,0 +1,292 @@
+pub mod proxy;
+
+use std::pin::Pin;
+
+use futures_lite::future;
+use futures_util::{Stream, StreamExt};
+use num_enum::TryFromPrimitive;
+use rusty_network_manager::{DeviceProxy, NetworkManagerProxy, SettingsConnectionProxy};
+use smol::channel::Sender;
+use tracing::{debug, error, info, instrument, warn};
+use zbus::{
+    Connection,
+    zvariant::{ObjectPath, OwnedObjectPath},
+};
+
+use proxy::{Modem3gppProxy, ModemProxy};
+
+use super::{
+    DBUS_CONNECTION, NetworkService,
+    endpoints::{
+        event::{NetworkServiceEvent, NetworkServiceEventType},
+        inter::NetworkServiceInterEvent,
+    },
+    ethernet::EthernetWatchDogExt,
+};
+
+/// `MMModemState` as reported by ModemManager.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, Default, TryFromPrimitive)]
+#[repr(i32)]
+pub enum ModemState {
+    Failed = -1,
+    #[default]
+    Unknown = 0,
+    Initializing = 1,
+    Locked = 2,
+    Disabled = 3,
+    Disabling = 4,
+    Enabling = 5,
+    Enabled = 6,
+    Searching = 7,
+    Registered = 8,
+    Disconnecting = 9,
+    Connecting = 10,
+    Connected = 11,
+}
+
+/// The radio generation a modem is using, derived from `MMModemAccessTechnology` flags.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum AccessTechnology {
+    #[default]
+    Unknown,
+    /// GSM, GPRS, EDGE and CDMA 1xRTT.
+    Gen2G,
+    /// UMTS, HSPA and EV-DO.
+    Gen3G,
+    /// LTE, including Cat-M and NB-IoT.
+    Lte,
+    /// 5G NR, standalone or alongside LTE.
+    Gen5G,
+}
+
+impl AccessTechnology {
+    const FLAGS_2G: u32 = 1 << 1 | 1 << 2 | 1 << 3 | 1 << 4 | 1 << 10;
+    const FLAGS_3G: u32 = 1 << 5 | 1 << 6 | 1 << 7 | 1 << 8 | 1 << 9 | 1 << 11 | 1 << 12 | 1 << 13;
+    const FLAGS_LTE: u32 = 1 << 14 | 1 << 16 | 1 << 17;
+    const FLAGS_5G: u32 = 1 << 15;
+
+    /// Picks the newest generation out of a `MMModemAccessTechnology` bitmask.
+    pub fn from_flags(flags: u32) -> Self {
+        if flags & Self::FLAGS_5G != 0 {
+            Self::Gen5G
+        } else if flags & Self::FLAGS_LTE != 0 {
+            Self::Lte
+        } else if flags & Self::FLAGS_3G != 0 {
+            Self::Gen3G
+        } else if flags & Self::FLAGS_2G != 0 {
+            Self::Gen2G
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Returns the short label shown next to the signal icon.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Unknown => "",
+            Self::Gen2G => "2G",
+            Self::Gen3G => "3G",
+            Self::Lte => "LTE",
+            Self::Gen5G => "5G",
+        }
+    }
+}
+
+/// Registration and signal information of a modem.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModemStatus {
+    pub state: ModemState,
+    /// Signal quality in percent.
+    pub signal_quality: u32,
+    pub access_technology: AccessTechnology,
+    /// `None` while not registered or for modems without 3GPP support.
+    pub operator_name: Option<String>,
+}
+
+/// A property change of a watched modem.
+enum ModemPropertyChange {
+    State(i32),
+    SignalQuality(u32),
+    AccessTechnologies(u32),
+    OperatorName(String),
+}
+
+impl ModemStatus {
+    fn apply(&mut self, change: ModemPropertyChange) {
+        match change {
+            ModemPropertyChange::State(state) => {
+                self.state = ModemState::try_from(state).unwrap_or_default();
+            }
+            ModemPropertyChange::SignalQuality(quality) => self.signal_quality = quality.min(100),
+            ModemPropertyChange::AccessTechnologies(flags) => {
+                self.access_technology = AccessTechnology::from_flags(flags);
+            }
+            ModemPropertyChange::OperatorName(name) => {
+                self.operator_name = Some(name).filter(|name| !name.is_empty());
+            }
+        }
+    }
+}
+
+async fn read_status(modem: &ModemProxy<'_>, modem_3gpp: &Modem3gppProxy<'_>) -> zbus::Result<ModemStatus> {
+    let mut status = ModemStatus::default();
+    status.apply(ModemPropertyChange::State(modem.state().await?));
+    status.apply(ModemPropertyChange::SignalQuality(modem.signal_quality().await?.0));
+    status.apply(ModemPropertyChange::AccessTechnologies(modem.access_technologies().await?));
+    // CDMA-only modems don't implement the 3GPP interface.
+    if let Ok(name) = modem_3gpp.operator_name().await {
+        status.apply(ModemPropertyChange::OperatorName(name));
+    }
+    Ok(status)
+}
+
+/// Reads the current status of the ModemManager modem at `modem_path`.
+pub async fn read_modem_status(
+    connection: &Connection,
+    modem_path: OwnedObjectPath,
+) -> zbus::Result<ModemStatus> {
+    let modem = ModemProxy::new(connection, modem_path.clone()).await?;
+    let modem_3gpp = Modem3gppProxy::new(connection, modem_path).await?;
+    read_status(&modem, &modem_3gpp).await
+}
+
+/// Sends the status of the modem at `modem_path` to `sender`, and again every time it changes.
+///
+/// Returns once the modem disappears from the bus or `sender` is closed.
+pub async fn watch_modem_status(
+    connection: &Connection,
+    modem_path: OwnedObjectPath,
+    sender: Sender<ModemStatus>,
+) -> zbus::Result<()> {
+    let modem = ModemProxy::new(connection, modem_path.clone()).await?;
+    let modem_3gpp = Modem3gppProxy::new(connection, modem_path).await?;
+
+    // Subscribe before reading so no change can slip in between.
+    let streams: Vec<Pin<Box<dyn Stream<Item = ModemPropertyChange> + Send>>> = vec![
+        modem
+            .receive_state_changed()
+            .await
+            .filter_map(|c| async move { c.get().await.ok().map(ModemPropertyChange::State) })
+            .boxed(),
+        modem
+            .receive_signal_quality_changed()
+            .await
+            .filter_map(|c| async move {
+                c.get().await.ok().map(|(quality, _)| ModemPropertyChange::SignalQuality(quality))
+            })
+            .boxed(),
+        modem
+            .receive_access_technologies_changed()
+            .await
+            .filter_map(|c| async move {
+                c.get().await.ok().map(ModemPropertyChange::AccessTechnologies)
+            })
+            .boxed(),
+        modem_3gpp
+            .receive_operator_name_changed()
+            .await
+            .filter_map(|c| async move { c.get().await.ok().map(ModemPropertyChange::OperatorName) })
+            .boxed(),
+    ];
+
+    let mut status = read_status(&modem, &modem_3gpp).await?;
+    if sender.send(status.clone()).await.is_err() {
+        return Ok(());
+    }
+
+    let mut changes = futures_util::stream::select_all(streams);
+    while let Some(change) = changes.next().await {
+        let previous = status.clone();
+        status.apply(change);
+        if status != previous && sender.send(status.clone()).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait ModemWatchDogExt: EthernetWatchDogExt {
+    /// A watchdog function for a mobile broadband device.
+    ///
+    /// Device state changes are reported by `device_state_watchdog`. Signal quality,
+    /// access technology and operator are read from ModemManager, whose object path
+    /// NetworkManager exposes as the device's `Udi`.
+    #[instrument(skip_all)]
+    async fn modem_watchdog(sender: Sender<NetworkServiceInterEvent>, device_path: OwnedObjectPath) {
+        let Ok(device) = DeviceProxy::new_from_path(device_path.clone(), &DBUS_CONNECTION).await else {
+            error!("Failed to create device proxy for {:?}", device_path);
+            return;
+        };
+        let (Ok(interface), Ok(udi)) = (device.interface().await, device.udi().await) else {
+            error!("Failed to read modem properties for {:?}", device_path);
+            return;
+        };
+        let Ok(modem_path) = OwnedObjectPath::try_from(udi.clone()) else {
+            warn!("Modem {} is not managed by ModemManager: {}", interface, udi);
+            return Self::device_state_watchdog(sender, device_path).await;
+        };
+
+        let (status_tx, status_rx) = smol::channel::unbounded();
+        let watch_status = async {
+            if let Err(e) = watch_modem_status(&DBUS_CONNECTION, modem_path, status_tx).await {
+                warn!("Failed to watch modem {}: {}", interface, e);
+            }
+        };
+        let report_status = async {
+            while let Ok(status) = status_rx.recv().await {
+                debug!("Modem status changed: {:?} for interface {}", status, interface);
+                let ret = sender
+                    .send(NetworkServiceInterEvent::SendMessage {
+                        event_type: NetworkServiceEventType::ModemStatusChanged,
+                        event: NetworkServiceEvent::ModemStatusChanged {
+                            interface: interface.clone(),
+                            status,
+                        },
+                    })
+                    .await;
+                if ret.is_err() {
+                    break;
+                }
+            }
+        };
+
+        future::zip(
+            Self::device_state_watchdog(sender.clone(), device_path),
+            future::zip(watch_status, report_status),
+        )
+        .await;
+    }
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait ModemConnExt {
+    /// Activates the first GSM profile available on the modem device.
+    ///
+    /// Returns `false` if no profile can be used with the device.
+    async fn connect_modem(device_path: OwnedObjectPath) -> zbus::Result<bool> {
+        let device = DeviceProxy::new_from_path(device_path.clone(), &DBUS_CONNECTION).await?;
+        for path in device.available_connections().await? {
+            let profile = SettingsConnectionProxy::new_from_path(path.clone(), &DBUS_CONNECTION).await?;
+            let settings = profile.get_settings().await?;
+            let is_gsm = settings
+                .get("connection")
+                .and_then(|c| c.get("type"))
+                .and_then(|v| <&str>::try_from(v).ok())
+                == Some("gsm");
+            if !is_gsm {
+                continue;
+            }
+
+            info!("Activating mobile broadband profile {:?}", path);
+            let nm = NetworkManagerProxy::new(&DBUS_CONNECTION).await?;
+            nm.activate_connection(&path, &device_path, &ObjectPath::try_from("/").unwrap())
+                .await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+impl ModemWatchDogExt for NetworkService {}
+impl ModemConnExt for NetworkService {}
@@ -0,0 +1,29 @@
+use zbus::proxy;
+
+#[proxy(
+    interface = "org.freedesktop.ModemManager1.Modem",
+    default_service = "org.freedesktop.ModemManager1"
+)]
+pub trait Modem {
+    /// `MMModemState` of the modem.
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<i32>;
+
+    /// Signal quality in percent and whether the value was measured recently.
+    #[zbus(property)]
+    fn signal_quality(&self) -> zbus::Result<(u32, bool)>;
+
+    /// Bitmask of `MMModemAccessTechnology` values currently in use.
+    #[zbus(property)]
+    fn access_technologies(&self) -> zbus::Result<u32>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.ModemManager1.Modem.Modem3gpp",
+    default_service = "org.freedesktop.ModemManager1"
+)]
+pub trait Modem3gpp {
+    /// Name of the operator the modem is registered with.
+    #[zbus(property)]
+    fn operator_name(&self) -> zbus::Result<String>;
+}
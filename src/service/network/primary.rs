@@ -0,0 +1,208 @@
+use futures_lite::future;
+use futures_util::StreamExt;
+use rusty_network_manager::{ActiveProxy, DeviceProxy, NetworkManagerProxy};
+use smol::channel::Sender;
+use tracing::{error, info, instrument, warn};
+use zbus::zvariant::OwnedObjectPath;
+
+use super::{
+    DBUS_CONNECTION, NetworkService,
+    endpoints::{
+        event::{NetworkDeviceType, NetworkServiceEvent, NetworkServiceEventType},
+        inter::NetworkServiceInterEvent,
+    },
+    metered::{MeteredExt, MeteredState},
+};
+
+/// Events that require the primary connection to be resolved again.
+enum PrimaryConnectionEvent {
+    PrimaryChanged(Option<OwnedObjectPath>),
+    DevicesChanged,
+    MeteredChanged,
+}
+
+/// The resolved state of NetworkManager's primary connection.
+#[derive(Debug, Default)]
+struct PrimaryConnection {
+    interface: Option<String>,
+    device_type: Option<NetworkDeviceType>,
+    connection_id: Option<String>,
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait PrimaryConnectionHelperExt {
+    /// Resolves an active connection path into the interface, device type and profile id
+    /// of its first device.
+    ///
+    /// Returns an empty `PrimaryConnection` if there is no primary connection (path is `/`)
+    /// or if the connection disappeared while resolving it.
+    async fn resolve_primary(active: Option<&ActiveProxy<'static>>) -> PrimaryConnection {
+        let Some(active) = active else {
+            return PrimaryConnection::default();
+        };
+
+        let connection_id = active.id().await.ok();
+        let device_path = active
+            .devices()
+            .await
+            .ok()
+            .and_then(|devices| devices.into_iter().next());
+
+        let Some(device_path) = device_path else {
+            return PrimaryConnection {
+                connection_id,
+                ..Default::default()
+            };
+        };
+
+        let Ok(device) = DeviceProxy::new_from_path(device_path.clone(), &DBUS_CONNECTION).await
+        else {
+            warn!("Failed to create device proxy for {:?}", device_path);
+            return PrimaryConnection {
+                connection_id,
+                ..Default::default()
+            };
+        };
+
+        PrimaryConnection {
+            interface: device.interface().await.ok(),
+            device_type: device
+                .device_type()
+                .await
+                .ok()
+                .and_then(|t| NetworkDeviceType::try_from(t).ok()),
+            connection_id,
+        }
+    }
+
+    /// Creates a proxy for the given active connection path.
+    /// Returns `None` for the empty path `/`, which NetworkManager uses for "no connection".
+    async fn active_proxy(path: Option<OwnedObjectPath>) -> Option<ActiveProxy<'static>> {
+        let path = path.filter(|p| p.as_str() != "/")?;
+        ActiveProxy::new_from_path(path, &DBUS_CONNECTION).await.ok()
+    }
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait PrimaryConnectionExt: PrimaryConnectionHelperExt + MeteredExt {
+    /// Follows NetworkManager's `PrimaryConnection` property and the device list of the
+    /// connection it points to, emitting a `PrimaryConnectionChanged` event whenever the
+    /// resolved interface, device type or profile changes.
+    ///
+    /// The metered state of the primary device and profile is followed as well and
+    /// stored by the service, which emits `MeteredStateChanged` when it changes.
+    ///
+    /// # Arguments
+    /// * `sender` - Sender channel for internal `NetworkServiceInterEvent`s.
+    #[instrument(skip_all)]
+    async fn primary_connection_watchdog(sender: Sender<NetworkServiceInterEvent>) {
+        let nm = NetworkManagerProxy::new(&DBUS_CONNECTION)
+            .await
+            .expect("Failed to create NetworkManager proxy");
+
+        let mut primary_path = nm.primary_connection().await.ok();
+        let mut last_emitted: Option<(Option<String>, Option<NetworkDeviceType>, Option<String>)> =
+            None;
+        let mut last_metered: Option<MeteredState> = None;
+
+        loop {
+            let active = Self::active_proxy(primary_path.clone()).await;
+            let primary = Self::resolve_primary(active.as_ref()).await;
+            let current = (
+                primary.interface.clone(),
+                primary.device_type,
+                primary.connection_id.clone(),
+            );
+
+            if last_emitted.as_ref() != Some(&current) {
+                info!(
+                    "Primary connection changed: {:?} ({:?}) on interface {:?}",
+                    primary.connection_id, primary.device_type, primary.interface
+                );
+                if sender
+                    .send(NetworkServiceInterEvent::SendMessage {
+                        event_type: NetworkServiceEventType::PrimaryConnectionChanged,
+                        event: NetworkServiceEvent::PrimaryConnectionChanged {
+                            interface: primary.interface,
+                            device_type: primary.device_type,
+                            connection_id: primary.connection_id,
+                        },
+                    })
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                last_emitted = Some(current);
+            }
+
+            let device = Self::primary_device(active.as_ref()).await;
+            let metered = Self::metered_state(active.as_ref(), device.as_ref()).await;
+            if last_metered != Some(metered) {
+                info!("Metered state changed: {:?}", metered);
+                if sender
+                    .send(NetworkServiceInterEvent::UpdateMeteredState { state: metered })
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                last_metered = Some(metered);
+            }
+
+            // Wait for the primary connection to be replaced, for the device list
+            // of the current one to change (e.g. a bond gaining its first slave), or for
+            // NetworkManager to reconsider whether the device is metered.
+            let mut primary_stream = nm.receive_primary_connection_changed().await;
+            let event = match active.as_ref() {
+                Some(active) => {
+                    let mut devices_stream = active.receive_devices_changed().await;
+                    let mut metered_stream = match device.as_ref() {
+                        Some(device) => Some(device.receive_metered_changed().await),
+                        None => None,
+                    };
+                    future::or(
+                        async {
+                            match primary_stream.next().await {
+                                Some(signal) => {
+                                    PrimaryConnectionEvent::PrimaryChanged(signal.get().await.ok())
+                                }
+                                None => PrimaryConnectionEvent::PrimaryChanged(None),
+                            }
+                        },
+                        future::or(
+                            async {
+                                devices_stream.next().await;
+                                PrimaryConnectionEvent::DevicesChanged
+                            },
+                            async {
+                                match metered_stream.as_mut() {
+                                    Some(stream) => {
+                                        stream.next().await;
+                                    }
+                                    None => future::pending::<()>().await,
+                                }
+                                PrimaryConnectionEvent::MeteredChanged
+                            },
+                        ),
+                    )
+                    .await
+                }
+                None => match primary_stream.next().await {
+                    Some(signal) => PrimaryConnectionEvent::PrimaryChanged(signal.get().await.ok()),
+                    None => break,
+                },
+            };
+
+            match event {
+                PrimaryConnectionEvent::PrimaryChanged(path) => primary_path = path,
+                PrimaryConnectionEvent::DevicesChanged | PrimaryConnectionEvent::MeteredChanged => {}
+            }
+        }
+
+        error!("Primary connection monitoring unexpectedly stopped.");
+    }
+}
+
+impl PrimaryConnectionHelperExt for NetworkService {}
+impl PrimaryConnectionExt for NetworkService {}
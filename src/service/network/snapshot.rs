@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use super::{
+    endpoints::event::{NetworkDeviceState, NetworkDeviceType},
+    ethernet::WiredLink,
+    metered::MeteredState,
+    modem::ModemStatus,
+    wireless::ap::{AccessPoint, AccessPointSecurity},
+};
+
+/// Everything the service knows about one registered interface.
+#[derive(Clone, Debug)]
+pub struct NetworkDeviceSnapshot {
+    pub interface: String,
+    pub device_type: NetworkDeviceType,
+    /// `None` until the device watchdog reported the first state.
+    pub state: Option<NetworkDeviceState>,
+    /// Wi-Fi only.
+    pub active_access_point: Option<AccessPoint>,
+    /// Wi-Fi only, the latest scan results grouped like `AccessPointScanReport`.
+    pub access_points: HashMap<(String, AccessPointSecurity), Vec<AccessPoint>>,
+    /// Wi-Fi only, a scan is in progress.
+    pub scanning: bool,
+    /// Ethernet only.
+    pub wired_link: Option<WiredLink>,
+    /// Modems only.
+    pub modem_status: Option<ModemStatus>,
+}
+
+impl NetworkDeviceSnapshot {
+    pub fn new(interface: String, device_type: NetworkDeviceType) -> Self {
+        Self {
+            interface,
+            device_type,
+            state: None,
+            active_access_point: None,
+            access_points: HashMap::new(),
+            scanning: false,
+            wired_link: None,
+            modem_status: None,
+        }
+    }
+}
+
+/// NetworkManager's primary connection as last reported by the service.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrimaryConnectionSnapshot {
+    pub interface: Option<String>,
+    pub device_type: Option<NetworkDeviceType>,
+    pub connection_id: Option<String>,
+}
+
+/// The current state of the `NetworkService`, for listeners that register late.
+///
+/// Applying a snapshot and then following the events sent after it yields the same
+/// state as having listened from the start.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkSnapshot {
+    /// Registered interfaces, sorted by name.
+    pub devices: Vec<NetworkDeviceSnapshot>,
+    /// `None` until the radio watchdog reported the first state.
+    pub wireless_enabled: Option<bool>,
+    pub primary_connection: PrimaryConnectionSnapshot,
+    pub metered: MeteredState,
+    /// Saved Wi-Fi profiles and whether they are known to work.
+    pub saved_profiles: HashMap<(String, AccessPointSecurity), bool>,
+}
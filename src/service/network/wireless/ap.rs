@@ -1,10 +1,87 @@
-use rusty_network_manager::{dbus_interface_types::NMActiveConnectionStateReason, AccessPointProxy, NM80211ApSecurityFlags};
-use zbus::zvariant::{ObjectPath, OwnedObjectPath};
+use std::collections::HashMap;
+
+use rusty_network_manager::{dbus_interface_types::NMActiveConnectionStateReason, NM80211ApSecurityFlags};
+use zbus::{
+    fdo::PropertiesProxy,
+    names::InterfaceName,
+    proxy::CacheProperties,
+    zvariant::{ObjectPath, Optional, OwnedObjectPath, OwnedValue},
+};
 
 use crate::service::network::DBUS_CONNECTION;
 
 pub type HwAddress = String;
 
+/// D-Bus interface name of NetworkManager access point objects.
+pub const ACCESS_POINT_INTERFACE: &str = "org.freedesktop.NetworkManager.AccessPoint";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+/// Represents the frequency band an access point operates on.
+pub enum WirelessBand {
+    Band2_4GHz,
+    Band5GHz,
+    Band6GHz,
+}
+
+impl WirelessBand {
+    /// Determines the band from a center frequency in MHz.
+    /// Returns `None` for frequencies outside the 2.4, 5 and 6 GHz bands.
+    pub fn from_frequency(frequency: u32) -> Option<Self> {
+        match frequency {
+            2401..=2495 => Some(Self::Band2_4GHz),
+            5150..=5895 => Some(Self::Band5GHz),
+            5925..=7125 => Some(Self::Band6GHz),
+            _ => None,
+        }
+    }
+
+    /// Derives the IEEE 802.11 channel number from a center frequency in MHz.
+    /// Returns `None` for frequencies outside the channels of the 2.4, 5 and 6 GHz bands,
+    /// e.g. at the lower edge of a band.
+    pub fn channel(frequency: u32) -> Option<u32> {
+        match frequency {
+            2484 => Some(14),
+            2412..=2472 => Some((frequency - 2407) / 5),
+            5150..=5895 => Some((frequency - 5000) / 5),
+            5935 => Some(2),
+            5955..=7125 => Some((frequency - 5950) / 5),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for WirelessBand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Band2_4GHz => write!(f, "2.4 GHz"),
+            Self::Band5GHz => write!(f, "5 GHz"),
+            Self::Band6GHz => write!(f, "6 GHz"),
+        }
+    }
+}
+
+/// Parses a colon separated hardware address (e.g. `AA:BB:CC:DD:EE:FF`) into its bytes.
+/// Returns `None` if the address is not exactly six hexadecimal octets.
+pub fn parse_hw_address(address: &str) -> Option<Vec<u8>> {
+    let bytes = address
+        .split(':')
+        .map(|octet| match octet.len() {
+            2 => u8::from_str_radix(octet, 16).ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    (bytes.len() == 6).then_some(bytes)
+}
+
+/// Formats hardware address bytes the way NetworkManager shows them, e.g. `AA:BB:CC:DD:EE:FF`.
+pub fn format_hw_address(bytes: &[u8]) -> HwAddress {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 /// Represents the security type of a wireless access point.
 pub enum AccessPointSecurity {
@@ -95,39 +172,93 @@ pub struct AccessPoint {
 impl AccessPoint {
     /// Tries to create an `AccessPoint` from a D-Bus object path.
     ///
-    /// Fetches access point details from D-Bus.
+    /// Fetches all access point properties with a single `GetAll` call.
     /// Returns `None` if the path is invalid or fetching details fails.
     pub async fn try_from_path(path: String) -> Option<Self> {
-        let path = ObjectPath::try_from(path.clone()).ok()?;
-        let access_point = AccessPointProxy::new_from_path(path.clone().into(), &DBUS_CONNECTION)
+        let path = ObjectPath::try_from(path).ok()?;
+        let properties = PropertiesProxy::builder(&DBUS_CONNECTION)
+            .destination("org.freedesktop.NetworkManager")
+            .ok()?
+            .path(path.clone())
+            .ok()?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .ok()?;
+
+        let props = properties
+            .get_all(Optional::from(Some(InterfaceName::from_static_str_unchecked(
+                ACCESS_POINT_INTERFACE,
+            ))))
             .await
             .ok()?;
 
-        let flags = access_point.flags().await.ok()?;
-        let wpa_flags = access_point.wpa_flags().await.ok()?;
-        let rsn_flags = access_point.rsn_flags().await.ok()?;
-        let ssid = access_point.ssid().await.ok()?;
-        let ssid_str = String::from_utf8_lossy(&ssid).to_string();
-        let freq = access_point.frequency().await.ok()?;
-        let mode = access_point.mode().await.ok()?;
-        let bssid = access_point.hw_address().await.ok()?;
-        let signal_strength = access_point.strength().await.ok()?;
-        let last_seen = access_point.last_seen().await.ok()?;
+        Self::from_properties(path.into(), &props)
+    }
+
+    /// Builds an `AccessPoint` from a property map as returned by `GetAll`.
+    ///
+    /// Returns `None` if any required property is missing or has an unexpected type.
+    pub fn from_properties(
+        path: OwnedObjectPath,
+        props: &HashMap<String, OwnedValue>,
+    ) -> Option<Self> {
+        fn property<'a, T: TryFrom<&'a OwnedValue>>(
+            props: &'a HashMap<String, OwnedValue>,
+            name: &str,
+        ) -> Option<T> {
+            props.get(name).and_then(|v| T::try_from(v).ok())
+        }
+
+        let ssid = props
+            .get("Ssid")
+            .and_then(|v| v.try_clone().ok())
+            .and_then(|v| Vec::<u8>::try_from(v).ok())?;
 
         Some(Self {
-            ssid: ssid_str,
-            flags,
-            wpa_flags,
-            rsn_flags,
-            mode,
-            bssid,
-            frequency: freq,
-            signal_strength,
-            last_seen,
-            dbus_path: path.into(),
+            ssid: String::from_utf8_lossy(&ssid).to_string(),
+            flags: property(props, "Flags")?,
+            wpa_flags: property(props, "WpaFlags")?,
+            rsn_flags: property(props, "RsnFlags")?,
+            mode: property(props, "Mode")?,
+            bssid: property::<&str>(props, "HwAddress")?.to_string(),
+            frequency: property(props, "Frequency")?,
+            signal_strength: property(props, "Strength")?,
+            last_seen: property(props, "LastSeen")?,
+            dbus_path: path,
         })
     }
 
+    /// Applies the properties NetworkManager updates while an access point stays in range
+    /// (`Strength`, `LastSeen` and `Frequency`) from a `PropertiesChanged` signal.
+    ///
+    /// Returns `false` if the signal carries any other property, in which case the
+    /// access point should be fetched again instead.
+    pub fn apply_changed_properties(&mut self, changed: &HashMap<String, OwnedValue>) -> bool {
+        for (name, value) in changed {
+            let applied = match name.as_str() {
+                "Strength" => u8::try_from(value).map(|v| self.signal_strength = v).is_ok(),
+                "LastSeen" => i32::try_from(value).map(|v| self.last_seen = v).is_ok(),
+                "Frequency" => u32::try_from(value).map(|v| self.frequency = v).is_ok(),
+                _ => false,
+            };
+            if !applied {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns the frequency band of the access point, if known.
+    pub fn band(&self) -> Option<WirelessBand> {
+        WirelessBand::from_frequency(self.frequency)
+    }
+
+    /// Returns the channel number of the access point, if known.
+    pub fn channel(&self) -> Option<u32> {
+        WirelessBand::channel(self.frequency)
+    }
+
     /// Checks if the access point has a hidden SSID.
     pub fn is_hidden(&self) -> bool {
         self.ssid.is_empty()
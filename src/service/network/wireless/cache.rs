@@ -0,0 +1,74 @@
+use std::collections::{HashMap, HashSet};
+
+use futures_util::future::join_all;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+use super::ap::{AccessPoint, AccessPointSecurity};
+
+/// Caches access point details keyed by their D-Bus object path.
+///
+/// Entries are fetched lazily when a path is first seen, kept up to date from
+/// `PropertiesChanged` signals and dropped when the access point disappears.
+#[derive(Debug, Default)]
+pub struct AccessPointCache {
+    entries: HashMap<OwnedObjectPath, AccessPoint>,
+}
+
+impl AccessPointCache {
+    /// Creates a new, empty `AccessPointCache`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drops the cached entry for the given path, forcing it to be fetched again.
+    pub fn invalidate(&mut self, path: &OwnedObjectPath) {
+        self.entries.remove(path);
+    }
+
+    /// Applies a `PropertiesChanged` signal to the cached entry for the given path.
+    ///
+    /// Returns the updated access point, or `None` if the path is not cached or the
+    /// signal could not be applied in place (the entry is then invalidated).
+    pub fn apply_changes(
+        &mut self,
+        path: &OwnedObjectPath,
+        changed: &HashMap<String, OwnedValue>,
+    ) -> Option<AccessPoint> {
+        let ap = self.entries.get_mut(path)?;
+        if ap.apply_changed_properties(changed) {
+            Some(ap.clone())
+        } else {
+            self.invalidate(path);
+            None
+        }
+    }
+
+    /// Makes the cache match the given list of access point paths.
+    ///
+    /// Entries for paths that are no longer listed are dropped, and paths that are not
+    /// cached yet are fetched concurrently.
+    pub async fn sync(&mut self, paths: &[OwnedObjectPath]) {
+        let listed: HashSet<&OwnedObjectPath> = paths.iter().collect();
+        self.entries.retain(|path, _| listed.contains(path));
+
+        let missing = paths
+            .iter()
+            .filter(|path| !self.entries.contains_key(*path))
+            .map(|path| AccessPoint::try_from_path(path.to_string()));
+
+        for ap in join_all(missing).await.into_iter().flatten() {
+            self.entries.insert(ap.dbus_path.clone(), ap);
+        }
+    }
+
+    /// Groups every cached access point by `(SSID, security)`.
+    pub fn grouped(&self) -> HashMap<(String, AccessPointSecurity), Vec<AccessPoint>> {
+        let mut map: HashMap<(String, AccessPointSecurity), Vec<AccessPoint>> = HashMap::new();
+        for ap in self.entries.values() {
+            map.entry((ap.ssid.clone(), ap.key_management()))
+                .or_default()
+                .push(ap.clone());
+        }
+        map
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
-use super::ap::{AccessPoint, AccessPointConnectResult};
+use super::ap::{parse_hw_address, AccessPoint, AccessPointConnectResult, HwAddress};
+use super::sync::{pinned_bssid_data, WirelessProfileHelperExt};
+use super::uri::WiFiUri;
 use crate::service::network::endpoints::inter::NetworkServiceInterEvent;
+use crate::service::network::ipconfig::IpSettings;
 use crate::service::network::{
     NetworkService, DBUS_CONNECTION
 };
@@ -9,7 +12,7 @@ use futures_util::StreamExt;
 use rusty_network_manager::{ActiveProxy, DeviceProxy, NetworkManagerProxy, SettingsConnectionProxy, SettingsProxy};
 use rusty_network_manager::dbus_interface_types::{NMActiveConnectionState, NMActiveConnectionStateReason};
 use smol::channel::Sender;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 use zbus::zvariant::{ObjectPath, OwnedObjectPath, Value};
 
 
@@ -21,10 +24,14 @@ pub struct WirelessConnectionSettings<'a> {
     pub uuid: Value<'a>,
     pub autoconnect: Value<'a>,
     pub ssid: Value<'a>,
+    pub bssid: Option<Value<'a>>,
+    pub hidden: Option<Value<'a>>,
+    /// `user.data` noting the pinned BSSID, set together with `bssid`.
+    pub user_data: Option<Value<'a>>,
     pub key_mgmt: Value<'a>,
     pub psk: Value<'a>,
-    pub ipv4: Value<'a>,
-    pub ipv6: Value<'a>,
+    pub ipv4: HashMap<&'a str, Value<'a>>,
+    pub ipv6: HashMap<&'a str, Value<'a>>,
     pub has_psk: bool,
     _marker: std::marker::PhantomData<&'a ()>,
 }
@@ -43,6 +50,15 @@ impl<'a> WirelessConnectionSettings<'a> {
 
         let mut wireless = HashMap::new();
         wireless.insert("ssid", &self.ssid);
+        if let Some(bssid) = &self.bssid {
+            wireless.insert("bssid", bssid);
+        }
+        if let Some(hidden) = &self.hidden {
+            wireless.insert("hidden", hidden);
+        }
+        if let Some(data) = &self.user_data {
+            settings.insert("user", HashMap::from([("data", data)]));
+        }
 
         let mut wireless_security = HashMap::new();
         wireless_security.insert("key-mgmt", &self.key_mgmt);
@@ -53,13 +69,8 @@ impl<'a> WirelessConnectionSettings<'a> {
         settings.insert("802-11-wireless", wireless);
         settings.insert("802-11-wireless-security", wireless_security);
 
-        let mut ipv4 = HashMap::new();
-        ipv4.insert("method", &self.ipv4);
-        settings.insert("ipv4", ipv4);
-
-        let mut ipv6 = HashMap::new();
-        ipv6.insert("method", &self.ipv6);
-        settings.insert("ipv6", ipv6);
+        settings.insert("ipv4", self.ipv4.iter().map(|(k, v)| (*k, v)).collect());
+        settings.insert("ipv6", self.ipv6.iter().map(|(k, v)| (*k, v)).collect());
 
         settings
     }
@@ -77,6 +88,15 @@ impl<'a> WirelessConnectionSettings<'a> {
 
         let mut wireless = HashMap::new();
         wireless.insert("ssid", self.ssid);
+        if let Some(bssid) = self.bssid {
+            wireless.insert("bssid", bssid);
+        }
+        if let Some(hidden) = self.hidden {
+            wireless.insert("hidden", hidden);
+        }
+        if let Some(data) = self.user_data {
+            settings.insert("user", HashMap::from([("data", data)]));
+        }
 
         let mut wireless_security = HashMap::new();
         wireless_security.insert("key-mgmt", self.key_mgmt);
@@ -87,13 +107,8 @@ impl<'a> WirelessConnectionSettings<'a> {
         settings.insert("802-11-wireless", wireless);
         settings.insert("802-11-wireless-security", wireless_security);
 
-        let mut ipv4 = HashMap::new();
-        ipv4.insert("method", self.ipv4);
-        settings.insert("ipv4", ipv4);
-
-        let mut ipv6 = HashMap::new();
-        ipv6.insert("method", self.ipv6);
-        settings.insert("ipv6", ipv6);
+        settings.insert("ipv4", self.ipv4);
+        settings.insert("ipv6", self.ipv6);
 
         settings
     }
@@ -104,8 +119,11 @@ impl<'a> WirelessConnectionSettings<'a> {
 pub struct WirelessConnectionSettingsBuilder<'a> {
     id: Option<String>,
     ssid: Option<String>,
+    bssid: Option<Vec<u8>>,
+    hidden: bool,
     key_mgmt: Option<String>,
     psk: Option<String>,
+    ip_settings: Option<IpSettings>,
     _marker: std::marker::PhantomData<&'a ()>,
 }
 
@@ -127,6 +145,18 @@ impl<'a> WirelessConnectionSettingsBuilder<'a> {
         self
     }
 
+    /// Pins the connection to a single access point by its BSSID bytes.
+    pub fn bssid(mut self, bssid: Vec<u8>) -> Self {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Marks the network as hidden, so NetworkManager probes for it by SSID.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
     /// Sets the key management type for the wireless connection.
     pub fn key_mgmt(mut self, key_mgmt: String) -> Self {
         self.key_mgmt = Some(key_mgmt);
@@ -139,16 +169,24 @@ impl<'a> WirelessConnectionSettingsBuilder<'a> {
         self
     }
 
+    /// Sets static addresses, DNS servers and methods for IPv4 and IPv6.
+    /// Both families use `auto` if this is not set.
+    pub fn ip_settings(mut self, ip_settings: IpSettings) -> Self {
+        self.ip_settings = Some(ip_settings);
+        self
+    }
+
     /// Builds the `WirelessConnectionSettings` from the builder.
     ///
     /// # Panics
     ///
-    /// Panics if `bssid` or `ssid` are not set.
+    /// Panics if `ssid` is not set.
     pub fn build(self) -> WirelessConnectionSettings<'a> {
         let ssid = self.ssid.unwrap();
         let id = self.id.unwrap_or(ssid.clone());
         let psk = self.psk.unwrap_or("".to_string());
         let has_psk = !psk.is_empty();
+        let ip_settings = self.ip_settings.unwrap_or_default();
 
         WirelessConnectionSettings {
             id: id.into(),
@@ -156,10 +194,13 @@ impl<'a> WirelessConnectionSettingsBuilder<'a> {
             uuid: uuid::Uuid::new_v4().to_string().into(),
             autoconnect: true.into(),
             ssid: ssid.into_bytes().into(),
+            user_data: self.bssid.as_deref().map(|bssid| Value::from(pinned_bssid_data(bssid))),
+            bssid: self.bssid.map(Value::from),
+            hidden: self.hidden.then(|| Value::from(true)),
             key_mgmt: self.key_mgmt.unwrap_or("wpa-psk".to_string()).into(),
             psk: psk.into(),
-            ipv4: "auto".into(),
-            ipv6: "auto".into(),
+            ipv4: ip_settings.ipv4.to_settings(),
+            ipv6: ip_settings.ipv6.to_settings(),
             has_psk,
             _marker: std::marker::PhantomData,
         }
@@ -167,8 +208,8 @@ impl<'a> WirelessConnectionSettingsBuilder<'a> {
 }
 
 #[async_trait::async_trait]
-pub(in super::super) trait WirelessConnHelperExt {
-        async fn fetch_profile(
+pub(in super::super) trait WirelessConnHelperExt: WirelessProfileHelperExt {
+    async fn fetch_profile(
         sender: &Sender<NetworkServiceInterEvent>,
         ap: &AccessPoint,
     ) -> Option<bool> {
@@ -194,8 +235,19 @@ pub(in super::super) trait WirelessConnHelperExt {
         device: &OwnedObjectPath,
         has_profile: Option<bool>,
         psk: Option<String>,
+        bssid: Option<&[u8]>,
+        ip_settings: Option<&IpSettings>,
     ) -> Result<OwnedObjectPath, AccessPointConnectResult> {
         if let Some(true) = has_profile {
+            // Connecting without a BSSID releases a pin the bar set before.
+            if let Err(e) = Self::pin_profile_bssid(&ap.ssid, ap.key_management(), bssid).await {
+                warn!("Failed to update the BSSID of the existing profile: {:?}", e);
+            }
+            if let Some(ip_settings) = ip_settings {
+                if let Err(e) = Self::set_profile_ip_settings(&ap.ssid, ap.key_management(), ip_settings).await {
+                    warn!("Failed to apply IP settings to existing profile: {:?}", e);
+                }
+            }
             Ok(nm
                 .activate_connection(&ObjectPath::try_from("/").unwrap(), device, &ap.dbus_path)
                 .await
@@ -204,12 +256,18 @@ pub(in super::super) trait WirelessConnHelperExt {
             let psk = psk.ok_or(AccessPointConnectResult::Failed(
                 NMActiveConnectionStateReason::LOGIN_FAILED,
             ))?;
-            let settings = WirelessConnectionSettingsBuilder::new()
+            let mut builder = WirelessConnectionSettingsBuilder::new()
                 .id(ap.ssid.clone())
                 .ssid(ap.ssid.clone())
                 .key_mgmt(ap.key_management().try_into().unwrap())
-                .psk(psk)
-                .build();
+                .psk(psk);
+            if let Some(bssid) = bssid {
+                builder = builder.bssid(bssid.to_vec());
+            }
+            if let Some(ip_settings) = ip_settings {
+                builder = builder.ip_settings(ip_settings.clone());
+            }
+            let settings = builder.build();
 
             let settings_proxy = SettingsProxy::new(&DBUS_CONNECTION)
                 .await
@@ -231,12 +289,20 @@ pub(in super::super) trait WirelessConnHelperExt {
         nm: &NetworkManagerProxy<'_>,
         ap: &AccessPoint,
         device: &OwnedObjectPath,
+        bssid: Option<&[u8]>,
+        ip_settings: Option<&IpSettings>,
     ) -> OwnedObjectPath {
-        let settings = WirelessConnectionSettingsBuilder::new()
+        let mut builder = WirelessConnectionSettingsBuilder::new()
             .id(ap.ssid.clone())
             .ssid(ap.ssid.clone())
-            .key_mgmt(ap.key_management().try_into().unwrap())
-            .build();
+            .key_mgmt(ap.key_management().try_into().unwrap());
+        if let Some(bssid) = bssid {
+            builder = builder.bssid(bssid.to_vec());
+        }
+        if let Some(ip_settings) = ip_settings {
+            builder = builder.ip_settings(ip_settings.clone());
+        }
+        let settings = builder.build();
 
         let (_conn_settings, path) = nm
             .add_and_activate_connection(settings.into_map(), device, &ap.dbus_path)
@@ -310,6 +376,8 @@ pub(in super::super) trait WirelessConnExt: WirelessConnHelperExt {
     /// * `device_path` - The D-Bus path of the wireless device.
     /// * `auto_update` - Flag to indicate if the connection should be automatically updated.
     /// * `psk` - Optional pre-shared key for authentication.
+    /// * `bssid` - Optional BSSID to pin the connection profile to, `None` releases a pin the bar set.
+    /// * `ip_settings` - Optional static IP / DNS configuration for the profile.
     ///
     /// # Errors
     ///
@@ -321,19 +389,29 @@ pub(in super::super) trait WirelessConnExt: WirelessConnHelperExt {
         device_path: OwnedObjectPath,
         auto_update: bool,
         psk: Option<String>,
+        bssid: Option<HwAddress>,
+        ip_settings: Option<IpSettings>,
     ) -> AccessPointConnectResult {
         let nm = NetworkManagerProxy::new(&DBUS_CONNECTION)
             .await
             .expect("Failed to create NetworkManager proxy");
+        let bssid = bssid.as_deref().and_then(parse_hw_address);
+        let ip_settings = ip_settings.filter(|ip| match ip.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Ignoring invalid IP settings: {}", e);
+                false
+            }
+        });
 
         // 1. Fetch or create profile indicator
         let has_profile = Self::fetch_profile(&inter_sender, &ap).await;
 
         // 2. Determine activation path
         let active_conn = if ap.authentication_required() {
-            Self::connect_with_auth(&nm, &ap, &device_path, has_profile, psk).await
+            Self::connect_with_auth(&nm, &ap, &device_path, has_profile, psk, bssid.as_deref(), ip_settings.as_ref()).await
         } else {
-            Ok(Self::connect_without_auth(&nm, &ap, &device_path).await)
+            Ok(Self::connect_without_auth(&nm, &ap, &device_path, bssid.as_deref(), ip_settings.as_ref()).await)
         };
 
         // 3. Wait for activation or fail
@@ -357,6 +435,57 @@ pub(in super::super) trait WirelessConnExt: WirelessConnHelperExt {
         }
     }
 
+    /// Activates the network of a `WIFI:` URI on the given device, updating the saved
+    /// profile of the network with the URI's password or creating one.
+    ///
+    /// NetworkManager picks the access point by SSID, so this also works for hidden
+    /// networks that do not show up in scan results. A created profile is removed again
+    /// if activation fails.
+    async fn connect_uri(device_path: OwnedObjectPath, uri: WiFiUri) -> AccessPointConnectResult {
+        let builder = match uri.settings_builder() {
+            Ok(builder) => builder.hidden(uri.hidden),
+            Err(e) => {
+                warn!("Cannot connect to {}: {}", uri.ssid, e);
+                return AccessPointConnectResult::Failed(NMActiveConnectionStateReason::UNKNOWN);
+            }
+        };
+        let nm = NetworkManagerProxy::new(&DBUS_CONNECTION)
+            .await
+            .expect("Failed to create NetworkManager proxy");
+
+        let profile = Self::find_profile(&uri.ssid, uri.security).await.unwrap_or_else(|e| {
+            warn!("Failed to look up saved profiles for {}: {:?}", uri.ssid, e);
+            None
+        });
+        let created = profile.is_none();
+        let activation = match profile {
+            // Reuse the saved profile rather than adding a duplicate of it.
+            Some(profile) => {
+                if let Err(e) = Self::set_profile_credentials(&uri).await {
+                    warn!("Failed to update the saved profile of {}: {:?}", uri.ssid, e);
+                }
+                nm.activate_connection(&profile, &device_path, &ObjectPath::try_from("/").unwrap())
+                    .await
+            }
+            None => nm
+                .add_and_activate_connection(
+                    builder.build().into_map(),
+                    &device_path,
+                    &ObjectPath::try_from("/").unwrap(),
+                )
+                .await
+                .map(|(_conn_settings, active_path)| active_path),
+        };
+
+        match activation {
+            Ok(active_path) => Self::wait_for_active(active_path, created).await,
+            Err(e) => {
+                warn!("Failed to activate connection for {}: {:?}", uri.ssid, e);
+                AccessPointConnectResult::Failed(NMActiveConnectionStateReason::UNKNOWN)
+            }
+        }
+    }
+
     /// Disconnects a network device.
     ///
     /// # Arguments
@@ -3,5 +3,8 @@ mod watchdog;
 mod sync;
 mod scan;
 mod radio;
+mod cache;
+mod scheduler;
 pub mod ap;
+pub mod uri;
 pub mod prelude;
\ No newline at end of file
@@ -3,4 +3,5 @@ pub use super::watchdog::*;
 pub use super::sync::*;
 pub use super::scan::*;
 pub use super::radio::*;
+pub use super::scheduler::*;
 pub use super::ap::*;
\ No newline at end of file
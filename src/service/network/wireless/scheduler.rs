@@ -0,0 +1,303 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use futures_util::StreamExt;
+use rusty_network_manager::{DeviceProxy, NetworkManagerProxy, WirelessProxy};
+use smol::channel::{Receiver, Sender};
+use smol_timeout::TimeoutExt;
+use tracing::{debug, error, info, instrument, warn};
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::service::network::{
+    DBUS_CONNECTION, NetworkService,
+    endpoints::{
+        event::{NetworkDeviceState, NetworkDeviceType, NetworkServiceEvent, NetworkServiceEventType},
+        inter::NetworkServiceInterEvent,
+    },
+};
+
+use super::scan::WirelessScanExt;
+
+/// How long to wait for `LastScan` to change before considering a scan finished anyway.
+const SCAN_COMPLETION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Intervals used by the background scan scheduler.
+#[derive(Clone, Copy, Debug)]
+pub struct ScanScheduleConfig {
+    /// Interval between scans while the network menu is open.
+    pub menu_interval: Duration,
+    /// Interval between background scans while a wireless device is disconnected.
+    pub disconnected_interval: Duration,
+    /// Minimum time between two scan requests on the same device.
+    /// NetworkManager rejects requests that come in too quickly.
+    pub min_interval: Duration,
+}
+
+impl Default for ScanScheduleConfig {
+    fn default() -> Self {
+        Self {
+            menu_interval: Duration::from_secs(20),
+            disconnected_interval: Duration::from_secs(120),
+            min_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Control messages for the scan scheduler.
+#[derive(Debug)]
+pub(in super::super) enum ScanSchedulerEvent {
+    /// The network menu was opened or closed.
+    MenuVisibilityChanged(bool),
+    /// Scan the given interface as soon as the rate limit allows it.
+    ScanNow(String),
+    /// A network device changed state, so the devices to scan may have changed.
+    DeviceStateChanged,
+}
+
+/// A wireless device considered by the scheduler.
+pub(in super::super) struct ScanTarget {
+    interface: String,
+    dbus_path: OwnedObjectPath,
+    connected: bool,
+    last_scan: i64,
+}
+
+/// Returns the current `CLOCK_BOOTTIME` in milliseconds, which is the clock NetworkManager
+/// uses for `LastScan`.
+fn boottime_msec() -> Option<i64> {
+    let uptime = std::fs::read_to_string("/proc/uptime").ok()?;
+    let seconds: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    Some((seconds * 1000.0) as i64)
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait WirelessScanSchedulerHelperExt: WirelessScanExt
+where
+    Self: 'static,
+{
+    /// Lists wireless devices that are able to scan, i.e. that are at least disconnected
+    /// (devices with their radio turned off are `Unavailable`).
+    async fn scan_targets() -> Vec<ScanTarget> {
+        let Ok(nm) = NetworkManagerProxy::new(&DBUS_CONNECTION).await else {
+            warn!("Failed to create NetworkManager proxy");
+            return Vec::new();
+        };
+        let Ok(paths) = nm.get_all_devices().await else {
+            warn!("Failed to list network devices");
+            return Vec::new();
+        };
+
+        let mut targets = Vec::new();
+        for path in paths {
+            let Ok(device) = DeviceProxy::new_from_path(path.clone(), &DBUS_CONNECTION).await else {
+                continue;
+            };
+            let is_wireless = device
+                .device_type()
+                .await
+                .ok()
+                .and_then(|t| NetworkDeviceType::try_from(t).ok())
+                == Some(NetworkDeviceType::WiFi);
+            if !is_wireless {
+                continue;
+            }
+
+            let state = device
+                .state()
+                .await
+                .ok()
+                .and_then(|s| NetworkDeviceState::try_from(s).ok());
+            let Some(state) = state else { continue };
+            if matches!(
+                state,
+                NetworkDeviceState::Unknown
+                    | NetworkDeviceState::Unmanaged
+                    | NetworkDeviceState::Unavailable
+            ) {
+                continue;
+            }
+
+            let Ok(wireless) = WirelessProxy::new_from_path(path.clone(), &DBUS_CONNECTION).await
+            else {
+                continue;
+            };
+            let (Ok(interface), Ok(last_scan)) = (device.interface().await, wireless.last_scan().await)
+            else {
+                continue;
+            };
+
+            targets.push(ScanTarget {
+                interface,
+                dbus_path: path,
+                connected: state == NetworkDeviceState::Activated,
+                last_scan,
+            });
+        }
+        targets
+    }
+
+    /// Requests a scan and, once NetworkManager accepted it, reports the scanning state until
+    /// `LastScan` changes.
+    #[instrument(skip(sender, dbus_path))]
+    async fn scan_and_report(
+        sender: Sender<NetworkServiceInterEvent>,
+        interface: String,
+        dbus_path: OwnedObjectPath,
+    ) {
+        async fn report(sender: &Sender<NetworkServiceInterEvent>, interface: &str, scanning: bool) {
+            let _ = sender
+                .send(NetworkServiceInterEvent::SendMessage {
+                    event_type: NetworkServiceEventType::ScanStateChanged,
+                    event: NetworkServiceEvent::ScanStateChanged {
+                        interface: interface.to_string(),
+                        scanning,
+                    },
+                })
+                .await;
+        }
+
+        let Ok(wireless) = WirelessProxy::new_from_path(dbus_path.clone(), &DBUS_CONNECTION).await
+        else {
+            warn!("Failed to create wireless proxy for {:?}", dbus_path);
+            return;
+        };
+        // Subscribe before requesting so a fast scan can't be missed. The stream only
+        // yields changes, so its next item marks the end of this scan.
+        let mut last_scan_stream = wireless.receive_last_scan_changed().await;
+
+        if let Err(e) = Self::request_scan(dbus_path).await {
+            // NetworkManager refuses scans while one is running or right after the last one.
+            debug!("Scan request rejected: {}", e);
+            return;
+        }
+        report(&sender, &interface, true).await;
+        if last_scan_stream
+            .next()
+            .timeout(SCAN_COMPLETION_TIMEOUT)
+            .await
+            .is_none()
+        {
+            warn!("Scan did not complete within {:?}", SCAN_COMPLETION_TIMEOUT);
+        }
+        report(&sender, &interface, false).await;
+    }
+}
+
+#[async_trait::async_trait]
+pub(in super::super) trait WirelessScanSchedulerExt: WirelessScanSchedulerHelperExt
+where
+    Self: 'static,
+{
+    /// Schedules background Wi-Fi scans.
+    ///
+    /// A scan is requested when the network menu opens, every `menu_interval` while it stays
+    /// open, and every `disconnected_interval` for disconnected devices otherwise. A device is
+    /// skipped if NetworkManager's `LastScan` is more recent than the interval (someone else
+    /// scanned already) or if it was asked to scan less than `min_interval` ago.
+    ///
+    /// The devices are looked up again whenever one changes state, without postponing a
+    /// scan that is already due.
+    ///
+    /// # Arguments
+    /// * `sender` - Sender channel for internal `NetworkServiceInterEvent`s.
+    /// * `control` - Receiver for `ScanSchedulerEvent`s.
+    /// * `config` - Scan intervals.
+    #[instrument(skip_all)]
+    async fn scan_scheduler(
+        sender: Sender<NetworkServiceInterEvent>,
+        control: Receiver<ScanSchedulerEvent>,
+        config: ScanScheduleConfig,
+    ) {
+        let mut menu_open = false;
+        let mut last_requested: HashMap<String, Instant> = HashMap::new();
+        let mut pending_scans: HashMap<String, smol::Task<()>> = HashMap::new();
+        let mut next_scan: Option<Instant> = None;
+
+        loop {
+            let targets = Self::scan_targets().await;
+            let interval = if menu_open {
+                Some(config.menu_interval)
+            } else if targets.iter().any(|t| !t.connected) {
+                Some(config.disconnected_interval)
+            } else {
+                None
+            };
+
+            if interval.is_none() {
+                next_scan = None;
+            }
+
+            let event = match interval {
+                Some(interval) => {
+                    let deadline = *next_scan.get_or_insert_with(|| Instant::now() + interval);
+                    control
+                        .recv()
+                        .timeout(deadline.saturating_duration_since(Instant::now()))
+                        .await
+                }
+                None => Some(control.recv().await),
+            };
+            if !matches!(event, Some(Ok(ScanSchedulerEvent::DeviceStateChanged))) {
+                next_scan = None;
+            }
+
+            // Decide which interfaces to scan and how old `LastScan` may be before scanning again.
+            let (wanted, max_age): (Vec<&ScanTarget>, Option<Duration>) = match event {
+                Some(Err(_)) => break,
+                Some(Ok(ScanSchedulerEvent::DeviceStateChanged)) => continue,
+                Some(Ok(ScanSchedulerEvent::MenuVisibilityChanged(open))) => {
+                    menu_open = open;
+                    if !open {
+                        continue;
+                    }
+                    (targets.iter().collect(), Some(config.min_interval))
+                }
+                Some(Ok(ScanSchedulerEvent::ScanNow(interface))) => (
+                    targets.iter().filter(|t| t.interface == interface).collect(),
+                    None,
+                ),
+                None if menu_open => (targets.iter().collect(), interval),
+                None => (targets.iter().filter(|t| !t.connected).collect(), interval),
+            };
+
+            pending_scans.retain(|_, task| !task.is_finished());
+            let now_boottime = boottime_msec();
+            for target in wanted {
+                if pending_scans.contains_key(&target.interface) {
+                    continue;
+                }
+                if last_requested
+                    .get(&target.interface)
+                    .is_some_and(|at| at.elapsed() < config.min_interval)
+                {
+                    debug!("Skipping scan on {}: rate limited", target.interface);
+                    continue;
+                }
+                if let (Some(max_age), Some(now)) = (max_age, now_boottime) {
+                    if target.last_scan >= 0 && now - target.last_scan < max_age.as_millis() as i64 {
+                        debug!("Skipping scan on {}: results are recent", target.interface);
+                        continue;
+                    }
+                }
+
+                info!("Scheduling scan on {}", target.interface);
+                last_requested.insert(target.interface.clone(), Instant::now());
+                pending_scans.insert(
+                    target.interface.clone(),
+                    smol::spawn(Self::scan_and_report(
+                        sender.clone(),
+                        target.interface.clone(),
+                        target.dbus_path.clone(),
+                    )),
+                );
+            }
+        }
+
+        error!("Scan scheduler unexpectedly stopped.");
+    }
+}
+
+impl WirelessScanSchedulerHelperExt for NetworkService {}
+impl WirelessScanSchedulerExt for NetworkService {}
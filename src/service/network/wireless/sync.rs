@@ -1,15 +1,56 @@
 use futures_util::StreamExt;
 use rusty_network_manager::{SettingsConnectionProxy, SettingsProxy};
 use smol::channel::Sender;
-use std::collections::HashSet;
-use zbus::zvariant::OwnedObjectPath;
+use std::collections::{HashMap, HashSet};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
 
-use crate::service::network::{endpoints::inter::NetworkServiceInterEvent, NetworkService, DBUS_CONNECTION};
+use crate::service::network::{endpoints::inter::NetworkServiceInterEvent, ipconfig::IpSettings, NetworkService, DBUS_CONNECTION};
 
-use super::ap::AccessPointSecurity;
+use super::{
+    ap::{format_hw_address, parse_hw_address, AccessPointSecurity},
+    uri::WiFiUri,
+};
+
+/// Connection settings in the borrowed form accepted by `Update`.
+type ProfileSettings<'s> = HashMap<&'s str, HashMap<&'s str, Value<'s>>>;
+
+/// Key in a profile's `user.data` holding the BSSID the bar pinned the profile to.
+const PINNED_BSSID_KEY: &str = "io.github.bigsaltyfishes.molyuubar.pinned-bssid";
 
 #[async_trait::async_trait]
 pub(in super::super) trait WirelessProfileHelperExt {
+    /// Extracts the `(SSID, security)` pair of a connection profile.
+    ///
+    /// Returns `None` for non-wireless profiles and unsupported key management types.
+    /// Profiles without a security section are treated as open networks.
+    fn wireless_profile_key(
+        cfg: &HashMap<String, HashMap<String, OwnedValue>>,
+    ) -> Option<(String, AccessPointSecurity)> {
+        let connection_type = cfg
+            .get("connection")
+            .and_then(|c| c.get("type"))
+            .and_then(|v| <&str>::try_from(v).ok())?;
+        if connection_type != "802-11-wireless" {
+            return None;
+        }
+
+        let ssid = cfg
+            .get("802-11-wireless")
+            .and_then(|w| w.get("ssid"))
+            .and_then(|v| v.try_clone().ok())
+            .and_then(|v| Vec::<u8>::try_from(v).ok())?;
+        let key_mgmt = cfg
+            .get("802-11-wireless-security")
+            .and_then(|w| w.get("key-mgmt"))
+            .and_then(|v| <&str>::try_from(v).ok())
+            .unwrap_or("none");
+
+        Some((
+            String::from_utf8(ssid).ok()?,
+            AccessPointSecurity::try_from(key_mgmt).ok()?,
+        ))
+    }
+
     async fn collect_wireless(
         paths: Vec<OwnedObjectPath>,
     ) -> HashSet<(String, AccessPointSecurity)> {
@@ -19,30 +60,8 @@ pub(in super::super) trait WirelessProfileHelperExt {
             if let Ok(proxy) = SettingsConnectionProxy::new_from_path(path, &DBUS_CONNECTION).await
             {
                 if let Ok(cfg) = proxy.get_settings().await {
-                    let is_wireless = cfg
-                        .get("connection")
-                        .and_then(|c| c.get("type"))
-                        .map(|v| v.to_string().trim_matches('"') == "802-11-wireless")
-                        .unwrap_or(false);
-
-                    if is_wireless {
-                        if let (Some(ss_raw), Some(km_raw)) = (
-                            cfg.get("802-11-wireless")
-                                .and_then(|w| w.get("ssid"))
-                                .map(|v| v.to_string()),
-                            cfg.get("802-11-wireless-security")
-                                .and_then(|w| w.get("key-mgmt"))
-                                .map(|v| v.to_string()),
-                        ) {
-                            if let (Ok(ss), Ok(sec)) = (
-                                String::from_utf8(ss_raw.into_bytes()).map_err(|_| ()),
-                                <AccessPointSecurity as TryFrom<&str>>::try_from(
-                                    km_raw.trim_matches('"'),
-                                ),
-                            ) {
-                                set.insert((ss, sec));
-                            }
-                        }
+                    if let Some(key) = Self::wireless_profile_key(&cfg) {
+                        set.insert(key);
                     }
                 }
             }
@@ -50,6 +69,203 @@ pub(in super::super) trait WirelessProfileHelperExt {
 
         set
     }
+
+    /// Builds a `WIFI:` URI from the first saved profile matching the given SSID and security,
+    /// including its password.
+    ///
+    /// Returns `None` if there is no such profile or its password is not available.
+    async fn profile_share_uri(
+        ssid: &str,
+        key_mgmt: AccessPointSecurity,
+    ) -> zbus::Result<Option<WiFiUri>> {
+        let settings = SettingsProxy::new(&DBUS_CONNECTION).await?;
+
+        for path in settings.list_connections().await? {
+            let proxy = SettingsConnectionProxy::new_from_path(path, &DBUS_CONNECTION).await?;
+            let cfg = proxy.get_settings().await?;
+            if Self::wireless_profile_key(&cfg) != Some((ssid.to_string(), key_mgmt)) {
+                continue;
+            }
+
+            let hidden = cfg
+                .get("802-11-wireless")
+                .and_then(|w| w.get("hidden"))
+                .and_then(|v| bool::try_from(v).ok())
+                .unwrap_or(false);
+            let password = if key_mgmt == AccessPointSecurity::None {
+                None
+            } else {
+                // Secrets are not part of `GetSettings` and must be requested separately.
+                let secrets = proxy.get_secrets("802-11-wireless-security").await?;
+                let psk = secrets
+                    .get("802-11-wireless-security")
+                    .and_then(|s| s.get("psk"))
+                    .and_then(|v| <&str>::try_from(v).ok())
+                    .map(str::to_string);
+                match psk {
+                    Some(psk) => Some(psk),
+                    None => return Ok(None),
+                }
+            };
+
+            return Ok(Some(WiFiUri {
+                ssid: ssid.to_string(),
+                security: key_mgmt,
+                password,
+                hidden,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Finds the first saved profile matching the given SSID and security.
+    async fn find_profile(ssid: &str, key_mgmt: AccessPointSecurity) -> zbus::Result<Option<OwnedObjectPath>> {
+        let settings = SettingsProxy::new(&DBUS_CONNECTION).await?;
+
+        for path in settings.list_connections().await? {
+            let proxy = SettingsConnectionProxy::new_from_path(path.clone(), &DBUS_CONNECTION).await?;
+            if Self::wireless_profile_key(&proxy.get_settings().await?) == Some((ssid.to_string(), key_mgmt)) {
+                return Ok(Some(path));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Stores the password of `uri`, and whether its network is hidden, in the saved
+    /// profiles of its network.
+    ///
+    /// Returns `true` if at least one profile was updated.
+    async fn set_profile_credentials(uri: &WiFiUri) -> zbus::Result<bool> {
+        Self::update_profile(&uri.ssid, uri.security, &|cfg| {
+            if uri.hidden {
+                cfg.entry("802-11-wireless")
+                    .or_default()
+                    .insert("hidden", Value::from(true));
+            }
+            if let Some(password) = &uri.password {
+                cfg.entry("802-11-wireless-security")
+                    .or_default()
+                    .insert("psk", Value::from(password.clone()));
+            }
+            true
+        })
+        .await
+    }
+
+    /// Applies `edit` to every saved profile matching `(ssid, key_mgmt)` and saves it back,
+    /// unless `edit` returns `false` because it left the profile as it was.
+    ///
+    /// Returns `true` if at least one profile was updated.
+    async fn update_profile(
+        ssid: &str,
+        key_mgmt: AccessPointSecurity,
+        edit: &(dyn for<'m, 's> Fn(&'m mut ProfileSettings<'s>) -> bool + Send + Sync),
+    ) -> zbus::Result<bool> {
+        let settings = SettingsProxy::new(&DBUS_CONNECTION).await?;
+        let mut updated_any = false;
+
+        for path in settings.list_connections().await? {
+            let proxy = SettingsConnectionProxy::new_from_path(path, &DBUS_CONNECTION).await?;
+            let cfg = proxy.get_settings().await?;
+            if Self::wireless_profile_key(&cfg) != Some((ssid.to_string(), key_mgmt)) {
+                continue;
+            }
+
+            let mut updated: ProfileSettings = cfg
+                .iter()
+                .map(|(section, values)| {
+                    let values = values
+                        .iter()
+                        .filter_map(|(k, v)| Some((k.as_str(), Value::try_from(v).ok()?)))
+                        .collect();
+                    (section.as_str(), values)
+                })
+                .collect();
+            if !edit(&mut updated) {
+                continue;
+            }
+
+            proxy.update(updated).await?;
+            updated_any = true;
+        }
+
+        Ok(updated_any)
+    }
+
+    /// Pins `802-11-wireless.bssid` on the saved profiles matching the given SSID and security,
+    /// so NetworkManager only associates with that access point. `None` releases a BSSID the
+    /// bar pinned before, see `set_pinned_bssid`.
+    ///
+    /// Returns `true` if at least one profile was updated.
+    async fn pin_profile_bssid(
+        ssid: &str,
+        key_mgmt: AccessPointSecurity,
+        bssid: Option<&[u8]>,
+    ) -> zbus::Result<bool> {
+        Self::update_profile(ssid, key_mgmt, &|cfg| set_pinned_bssid(cfg, bssid)).await
+    }
+
+    /// Replaces the `ipv4` and `ipv6` sections of the saved profiles matching the given
+    /// SSID and security with the given configuration.
+    ///
+    /// Returns `true` if at least one profile was updated.
+    async fn set_profile_ip_settings(
+        ssid: &str,
+        key_mgmt: AccessPointSecurity,
+        ip_settings: &IpSettings,
+    ) -> zbus::Result<bool> {
+        Self::update_profile(ssid, key_mgmt, &|cfg| {
+            cfg.insert("ipv4", ip_settings.ipv4.to_settings());
+            cfg.insert("ipv6", ip_settings.ipv6.to_settings());
+            true
+        })
+        .await
+    }
+}
+
+/// The `user.data` entry noting that the bar pinned a profile to `bssid`.
+pub(super) fn pinned_bssid_data(bssid: &[u8]) -> HashMap<String, String> {
+    HashMap::from([(PINNED_BSSID_KEY.to_string(), format_hw_address(bssid))])
+}
+
+/// Pins `cfg` to `bssid`, noting the pin in the profile's `user.data`. `None` removes the
+/// BSSID again, but only if it is still the one the bar pinned: a BSSID set with nmcli or
+/// nm-connection-editor is left alone.
+///
+/// Returns `false` if `cfg` was left as it was.
+fn set_pinned_bssid(cfg: &mut ProfileSettings, bssid: Option<&[u8]>) -> bool {
+    let mut data: HashMap<String, String> = cfg
+        .get("user")
+        .and_then(|user| user.get("data"))
+        .and_then(|data| data.try_clone().ok())
+        .and_then(|data| HashMap::try_from(data).ok())
+        .unwrap_or_default();
+
+    match bssid {
+        Some(bssid) => {
+            cfg.entry("802-11-wireless")
+                .or_default()
+                .insert("bssid", Value::from(bssid.to_vec()));
+            data.extend(pinned_bssid_data(bssid));
+        }
+        None => {
+            let Some(pinned) = data.remove(PINNED_BSSID_KEY) else {
+                return false;
+            };
+            let wireless = cfg.entry("802-11-wireless").or_default();
+            let current = wireless
+                .get("bssid")
+                .and_then(|bssid| bssid.try_clone().ok())
+                .and_then(|bssid| Vec::<u8>::try_from(bssid).ok());
+            if current.is_some_and(|current| parse_hw_address(&pinned) == Some(current)) {
+                wireless.remove("bssid");
+            }
+        }
+    }
+    cfg.entry("user").or_default().insert("data", Value::from(data));
+    true
 }
 
 #[async_trait::async_trait]
@@ -0,0 +1,204 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::{ap::AccessPointSecurity, connect::WirelessConnectionSettingsBuilder};
+
+const URI_SCHEME: &str = "WIFI:";
+
+/// Wi-Fi credentials in the `WIFI:T:WPA;S:ssid;P:pass;;` format used by QR codes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WiFiUri {
+    pub ssid: String,
+    pub security: AccessPointSecurity,
+    pub password: Option<String>,
+    pub hidden: bool,
+}
+
+/// Errors raised while parsing a `WIFI:` URI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WiFiUriError {
+    /// The string does not start with `WIFI:`.
+    MissingScheme,
+    /// A field is not in `key:value` form or ends in a dangling escape.
+    MalformedField(String),
+    /// The `S` field is missing or empty.
+    MissingSsid,
+    /// A secured network without a `P` field.
+    MissingPassword,
+    /// The `T` field names a security type that cannot be connected to (e.g. `WEP`).
+    UnsupportedSecurity(String),
+}
+
+impl Display for WiFiUriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingScheme => write!(f, "not a WIFI: URI"),
+            Self::MalformedField(field) => write!(f, "malformed field: {}", field),
+            Self::MissingSsid => write!(f, "missing network name"),
+            Self::MissingPassword => write!(f, "missing password"),
+            Self::UnsupportedSecurity(t) => write!(f, "unsupported security type: {}", t),
+        }
+    }
+}
+
+impl std::error::Error for WiFiUriError {}
+
+impl WiFiUri {
+    /// Maps the `T` field to an `AccessPointSecurity`. An empty type means an open network.
+    fn parse_security(value: &str) -> Result<AccessPointSecurity, WiFiUriError> {
+        match value.to_ascii_uppercase().as_str() {
+            "" | "NOPASS" => Ok(AccessPointSecurity::None),
+            "WPA" | "WPA2" => Ok(AccessPointSecurity::WPA),
+            "SAE" | "WPA3" => Ok(AccessPointSecurity::WPA3),
+            _ => Err(WiFiUriError::UnsupportedSecurity(value.to_string())),
+        }
+    }
+
+    fn security_str(security: AccessPointSecurity) -> &'static str {
+        match security {
+            AccessPointSecurity::None | AccessPointSecurity::Unsupported => "nopass",
+            AccessPointSecurity::WPA => "WPA",
+            AccessPointSecurity::WPA3 => "SAE",
+        }
+    }
+
+    /// Splits the body on unescaped `;` and resolves `\` escapes in each field.
+    fn split_fields(body: &str) -> Result<Vec<String>, WiFiUriError> {
+        let mut fields = Vec::new();
+        let mut current = String::new();
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(escaped) => {
+                        // Keep escapes so `:` inside values is not taken as the key separator.
+                        current.push('\\');
+                        current.push(escaped);
+                    }
+                    None => return Err(WiFiUriError::MalformedField(current)),
+                },
+                ';' => fields.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            }
+        }
+        if !current.is_empty() {
+            fields.push(current);
+        }
+        Ok(fields)
+    }
+
+    fn unescape(value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => out.extend(chars.next()),
+                c => out.push(c),
+            }
+        }
+        // Some generators quote the SSID / password to avoid it being read as hex.
+        match out.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            Some(inner) if !value.ends_with("\\\"") => inner.to_string(),
+            _ => out,
+        }
+    }
+
+    fn escape(value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        for c in value.chars() {
+            if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    /// Creates a settings builder for a new profile with these credentials.
+    pub fn settings_builder<'a>(&self) -> Result<WirelessConnectionSettingsBuilder<'a>, WiFiUriError> {
+        let key_mgmt: String = self
+            .security
+            .try_into()
+            .map_err(|_| WiFiUriError::UnsupportedSecurity(Self::security_str(self.security).to_string()))?;
+        let mut builder = WirelessConnectionSettingsBuilder::new()
+            .id(self.ssid.clone())
+            .ssid(self.ssid.clone())
+            .key_mgmt(key_mgmt);
+        if let Some(password) = &self.password {
+            builder = builder.psk(password.clone());
+        }
+        Ok(builder)
+    }
+}
+
+impl FromStr for WiFiUri {
+    type Err = WiFiUriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let body = s
+            .get(..URI_SCHEME.len())
+            .filter(|scheme| scheme.eq_ignore_ascii_case(URI_SCHEME))
+            .map(|_| &s[URI_SCHEME.len()..])
+            .ok_or(WiFiUriError::MissingScheme)?;
+
+        let mut ssid = None;
+        let mut security = None;
+        let mut password = None;
+        let mut hidden = false;
+
+        for field in Self::split_fields(body)? {
+            if field.is_empty() {
+                continue;
+            }
+            let (key, value) = field
+                .split_once(':')
+                .ok_or_else(|| WiFiUriError::MalformedField(field.clone()))?;
+            match key.to_ascii_uppercase().as_str() {
+                "S" => ssid = Some(Self::unescape(value)),
+                "T" => security = Some(Self::parse_security(value)?),
+                "P" => password = Some(Self::unescape(value)),
+                "H" => hidden = value.eq_ignore_ascii_case("true"),
+                // Unknown fields (e.g. WPA3's `R:` transition-disable flag) are ignored.
+                _ => {}
+            }
+        }
+
+        let ssid = ssid.filter(|s| !s.is_empty()).ok_or(WiFiUriError::MissingSsid)?;
+        let security = security.unwrap_or(if password.is_some() {
+            AccessPointSecurity::WPA
+        } else {
+            AccessPointSecurity::None
+        });
+        let password = match security {
+            AccessPointSecurity::None => None,
+            _ => Some(password.filter(|p| !p.is_empty()).ok_or(WiFiUriError::MissingPassword)?),
+        };
+
+        Ok(Self {
+            ssid,
+            security,
+            password,
+            hidden,
+        })
+    }
+}
+
+impl Display for WiFiUri {
+    /// Serializes the credentials, e.g. `WIFI:T:WPA;S:my\;net;P:secret;;`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}T:{};S:{};",
+            URI_SCHEME,
+            Self::security_str(self.security),
+            Self::escape(&self.ssid)
+        )?;
+        if let Some(password) = &self.password {
+            write!(f, "P:{};", Self::escape(password))?;
+        }
+        if self.hidden {
+            write!(f, "H:true;")?;
+        }
+        write!(f, ";")
+    }
+}
@@ -1,12 +1,20 @@
-use std::{collections::HashMap, pin::Pin};
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    time::{Duration, Instant},
+};
 
 use futures_util::{Stream, StreamExt};
 use rusty_network_manager::{
     DeviceProxy, WirelessProxy, dbus_interface_types::NMDeviceStateReason,
 };
-use smol::channel::Sender;
-use tracing::{info, instrument};
-use zbus::zvariant::OwnedObjectPath;
+use smol::channel::{SendError, Sender};
+use smol_timeout::TimeoutExt;
+use tracing::{info, instrument, warn};
+use zbus::{
+    MatchRule, MessageStream,
+    zvariant::{OwnedObjectPath, OwnedValue},
+};
 
 use crate::service::network::{
     DBUS_CONNECTION, NetworkService,
@@ -16,13 +24,20 @@ use crate::service::network::{
     },
 };
 
-use super::ap::{AccessPoint, AccessPointSecurity};
+use super::{
+    ap::{ACCESS_POINT_INTERFACE, AccessPoint},
+    cache::AccessPointCache,
+};
+
+/// Window during which bursts of `AccessPointsChanged` signals are merged into one refresh.
+const ACCESS_POINTS_DEBOUNCE: Duration = Duration::from_millis(250);
 
 /// Unified event type for the watchdog
 enum WatchdogEvent {
     StateChanged(NetworkDeviceState, NMDeviceStateReason),
     ActiveApChanged(OwnedObjectPath),
     AccessPointsChanged(Vec<OwnedObjectPath>),
+    AccessPointPropertiesChanged(OwnedObjectPath, HashMap<String, OwnedValue>),
 }
 
 #[async_trait::async_trait]
@@ -45,6 +60,28 @@ pub(in super::super) trait WirelessWatchDogHelperExt {
             .await
             .expect("Inter Service channel closed");
     }
+
+    /// Syncs the access point cache with the given list and reports the grouped result.
+    async fn refresh_access_points(
+        sender: &Sender<NetworkServiceInterEvent>,
+        iface: &str,
+        cache: &mut AccessPointCache,
+        paths: &[OwnedObjectPath],
+    ) -> Result<(), SendError<NetworkServiceInterEvent>> {
+        cache.sync(paths).await;
+        let access_points = cache.grouped();
+        info!(
+            "Access points changed for interface {}, num: {}",
+            iface,
+            access_points.len()
+        );
+        sender
+            .send(NetworkServiceInterEvent::RefreshAccessPoints {
+                interface: iface.to_string(),
+                access_points,
+            })
+            .await
+    }
 }
 
 #[async_trait::async_trait]
@@ -72,6 +109,19 @@ pub(in super::super) trait WirelessWatchDogExt: WirelessWatchDogHelperExt {
             }
         }
 
+        // Signals for every access point object; only paths known to the cache matter.
+        let ap_properties_rule = MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .interface("org.freedesktop.DBus.Properties")
+            .and_then(|b| b.member("PropertiesChanged"))
+            .and_then(|b| b.path_namespace("/org/freedesktop/NetworkManager/AccessPoint"))
+            .and_then(|b| b.arg(0, ACCESS_POINT_INTERFACE))
+            .expect("failed to build access point match rule")
+            .build();
+        let ap_properties_stream = MessageStream::for_match_rule(ap_properties_rule, conn, None)
+            .await
+            .expect("failed to subscribe to access point property changes");
+
         // Map each signal stream into WatchdogEvent
         let streams: Vec<Pin<Box<dyn Stream<Item = WatchdogEvent> + Send>>> = vec![
             device
@@ -116,12 +166,56 @@ pub(in super::super) trait WirelessWatchDogExt: WirelessWatchDogHelperExt {
                     sig.get().await.ok().map(WatchdogEvent::AccessPointsChanged)
                 })
                 .boxed(),
+            ap_properties_stream
+                .filter_map(|msg| async move {
+                    let msg = msg.ok()?;
+                    let path = msg.header().path()?.to_owned();
+                    let (_, changed, _): (String, HashMap<String, OwnedValue>, Vec<String>) =
+                        msg.body().deserialize().ok()?;
+                    Some(WatchdogEvent::AccessPointPropertiesChanged(path.into(), changed))
+                })
+                .boxed(),
         ];
 
         let mut streams = futures_util::stream::select_all(streams);
+        let mut cache = AccessPointCache::new();
+
+        // Access points already known before the watchdog started
+        let mut pending_access_points = match wireless.access_points().await {
+            Ok(list) => Some(list),
+            Err(e) => {
+                warn!("Failed to get access points for interface {}: {:?}", interface, e);
+                None
+            }
+        };
+        let mut refresh_deadline = Instant::now();
 
         // Process merged events
-        while let Some(evt) = streams.next().await {
+        loop {
+            let evt = if pending_access_points.is_some() {
+                let remaining = refresh_deadline.saturating_duration_since(Instant::now());
+                match streams.next().timeout(remaining).await {
+                    Some(evt) => evt,
+                    None => {
+                        // Debounce window elapsed, refresh once with the latest list
+                        let list = pending_access_points.take().unwrap_or_default();
+                        if Self::refresh_access_points(&sender, &interface, &mut cache, &list)
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                        continue;
+                    }
+                }
+            } else {
+                streams.next().await
+            };
+
+            let Some(evt) = evt else {
+                break;
+            };
+
             match evt {
                 WatchdogEvent::StateChanged(ds, rs) => {
                     info!(
@@ -157,30 +251,25 @@ pub(in super::super) trait WirelessWatchDogExt: WirelessWatchDogHelperExt {
                     }
                 }
                 WatchdogEvent::AccessPointsChanged(list) => {
-                    // build map
-                    let mut map: HashMap<(String, AccessPointSecurity), Vec<AccessPoint>> =
-                        HashMap::new();
-                    for p in list {
-                        if let Some(ap) = AccessPoint::try_from_path(p.to_string()).await {
-                            map.entry((ap.ssid.clone(), ap.key_management()))
-                                .or_default()
-                                .push(ap);
-                        }
+                    // Start the debounce window on the first signal of a burst
+                    if pending_access_points.is_none() {
+                        refresh_deadline = Instant::now() + ACCESS_POINTS_DEBOUNCE;
                     }
-                    info!(
-                        "Access points changed for interface {}, num: {}",
-                        interface,
-                        map.len()
-                    );
-                    if sender
-                        .send(NetworkServiceInterEvent::RefreshAccessPoints {
-                            interface: interface.clone(),
-                            access_points: map,
-                        })
-                        .await
-                        .is_err()
-                    {
-                        break;
+                    pending_access_points = Some(list);
+                }
+                WatchdogEvent::AccessPointPropertiesChanged(path, changed) => {
+                    // Signals for access points of other devices are ignored by the cache
+                    if let Some(ap) = cache.apply_changes(&path, &changed) {
+                        if sender
+                            .send(NetworkServiceInterEvent::UpdateAccessPoint {
+                                interface: interface.clone(),
+                                ap,
+                            })
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
                     }
                 }
             }
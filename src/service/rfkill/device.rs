@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use num_enum::TryFromPrimitive;
+
+/// Size of the original `struct rfkill_event`. Newer kernels append extra fields but keep
+/// reads and writes of this size working.
+pub const RFKILL_EVENT_SIZE: usize = 8;
+
+/// Radio types known to the kernel (`enum rfkill_type`).
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, TryFromPrimitive)]
+#[repr(u8)]
+pub enum RadioType {
+    /// Only valid in `ChangeAll` requests, targets every radio.
+    All = 0,
+    Wlan = 1,
+    Bluetooth = 2,
+    Uwb = 3,
+    Wimax = 4,
+    Wwan = 5,
+    Gps = 6,
+    Fm = 7,
+    Nfc = 8,
+}
+
+impl RadioType {
+    /// Radios turned off by airplane mode.
+    pub const AIRPLANE_MODE: [RadioType; 3] = [RadioType::Wlan, RadioType::Bluetooth, RadioType::Wwan];
+}
+
+/// Operations of an rfkill event (`enum rfkill_operation`).
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug, TryFromPrimitive)]
+#[repr(u8)]
+pub enum RfkillOperation {
+    Add = 0,
+    Del = 1,
+    Change = 2,
+    ChangeAll = 3,
+}
+
+/// An event read from or written to `/dev/rfkill`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RfkillEvent {
+    pub idx: u32,
+    pub radio: RadioType,
+    pub op: RfkillOperation,
+    /// Blocked by software, e.g. by us or NetworkManager.
+    pub soft: bool,
+    /// Blocked by a physical switch or the firmware; cannot be changed from userspace.
+    pub hard: bool,
+}
+
+impl RfkillEvent {
+    /// Creates a request that soft-blocks or unblocks every radio of the given type.
+    pub fn change_all(radio: RadioType, blocked: bool) -> Self {
+        Self {
+            idx: 0,
+            radio,
+            op: RfkillOperation::ChangeAll,
+            soft: blocked,
+            hard: false,
+        }
+    }
+
+    /// Decodes an event. Returns `None` for unknown radio types or operations.
+    pub fn from_bytes(bytes: &[u8; RFKILL_EVENT_SIZE]) -> Option<Self> {
+        Some(Self {
+            idx: u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            radio: RadioType::try_from(bytes[4]).ok()?,
+            op: RfkillOperation::try_from(bytes[5]).ok()?,
+            soft: bytes[6] != 0,
+            hard: bytes[7] != 0,
+        })
+    }
+
+    /// Encodes the event in the kernel's layout.
+    pub fn to_bytes(self) -> [u8; RFKILL_EVENT_SIZE] {
+        let idx = self.idx.to_ne_bytes();
+        [
+            idx[0],
+            idx[1],
+            idx[2],
+            idx[3],
+            self.radio as u8,
+            self.op as u8,
+            self.soft as u8,
+            self.hard as u8,
+        ]
+    }
+}
+
+/// Reads `RfkillEvent`s from `/dev/rfkill` or any other byte stream.
+///
+/// `next_event` is cancel-safe: partially read events are kept until the rest arrives.
+pub struct RfkillReader<R> {
+    inner: R,
+    buffer: [u8; RFKILL_EVENT_SIZE],
+    filled: usize,
+}
+
+impl<R: AsyncRead + Unpin> RfkillReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: [0; RFKILL_EVENT_SIZE],
+            filled: 0,
+        }
+    }
+
+    /// Returns the next event, skipping events of unknown types.
+    /// Returns `Ok(None)` once the stream ends.
+    pub async fn next_event(&mut self) -> std::io::Result<Option<RfkillEvent>> {
+        loop {
+            // Never ask for more than one event, the kernel returns one event per read
+            // and would otherwise hand us the longer extended layout.
+            let read = self.inner.read(&mut self.buffer[self.filled..]).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.filled += read;
+            if self.filled < RFKILL_EVENT_SIZE {
+                continue;
+            }
+            self.filled = 0;
+            if let Some(event) = RfkillEvent::from_bytes(&self.buffer) {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+/// Writes an event to `/dev/rfkill` or any other byte sink.
+pub async fn write_event<W: AsyncWrite + Unpin>(writer: &mut W, event: &RfkillEvent) -> std::io::Result<()> {
+    writer.write_all(&event.to_bytes()).await?;
+    writer.flush().await
+}
+
+/// Block state of one rfkill device.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RfkillDevice {
+    pub radio: RadioType,
+    pub soft: bool,
+    pub hard: bool,
+}
+
+/// Aggregated block state of all devices of one radio type.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct RadioState {
+    /// At least one device of this type exists.
+    pub present: bool,
+    /// Every device of this type is blocked (by software or hardware).
+    pub blocked: bool,
+    /// At least one device of this type is blocked by hardware.
+    pub hard_blocked: bool,
+}
+
+/// Airplane mode as derived from the radios in `RadioType::AIRPLANE_MODE`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct AirplaneModeState {
+    /// There are radios and all of them are blocked.
+    pub enabled: bool,
+    /// A radio is held off by a hardware switch, airplane mode cannot be turned off.
+    pub hard_blocked: bool,
+}
+
+/// Tracks rfkill devices from the event stream.
+#[derive(Debug, Default)]
+pub struct RfkillState {
+    devices: HashMap<u32, RfkillDevice>,
+}
+
+impl RfkillState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies an event read from `/dev/rfkill`.
+    /// Returns the radio type whose state may have changed.
+    pub fn apply(&mut self, event: &RfkillEvent) -> Option<RadioType> {
+        match event.op {
+            RfkillOperation::Add | RfkillOperation::Change => {
+                let device = RfkillDevice {
+                    radio: event.radio,
+                    soft: event.soft,
+                    hard: event.hard,
+                };
+                (self.devices.insert(event.idx, device) != Some(device)).then_some(event.radio)
+            }
+            RfkillOperation::Del => self.devices.remove(&event.idx).map(|device| device.radio),
+            // Only sent by userspace, the kernel reports the resulting per-device changes.
+            RfkillOperation::ChangeAll => None,
+        }
+    }
+
+    pub fn devices(&self) -> &HashMap<u32, RfkillDevice> {
+        &self.devices
+    }
+
+    /// Returns the aggregated state of all devices of the given type.
+    pub fn radio_state(&self, radio: RadioType) -> RadioState {
+        let mut devices = self.devices.values().filter(|device| device.radio == radio).peekable();
+        let mut state = RadioState {
+            present: devices.peek().is_some(),
+            blocked: true,
+            hard_blocked: false,
+        };
+        for device in devices {
+            state.blocked &= device.soft || device.hard;
+            state.hard_blocked |= device.hard;
+        }
+        state.blocked &= state.present;
+        state
+    }
+
+    /// Returns the airplane mode state.
+    pub fn airplane_mode(&self) -> AirplaneModeState {
+        let radios: Vec<RadioState> = RadioType::AIRPLANE_MODE
+            .iter()
+            .map(|radio| self.radio_state(*radio))
+            .filter(|state| state.present)
+            .collect();
+        AirplaneModeState {
+            enabled: !radios.is_empty() && radios.iter().all(|state| state.blocked),
+            hard_blocked: radios.iter().any(|state| state.hard_blocked),
+        }
+    }
+}
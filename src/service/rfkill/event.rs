@@ -0,0 +1,43 @@
+use smol::channel::Sender;
+
+use super::device::{AirplaneModeState, RadioState, RadioType};
+
+/// Represents the type of an rfkill event.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RfkillServiceEventType {
+    RadioStateChanged,
+    AirplaneModeChanged,
+}
+
+#[derive(Debug)]
+pub enum RfkillServiceRequest {
+    /// Request to block or unblock every radio affected by airplane mode.
+    SetAirplaneMode {
+        enabled: bool,
+    },
+    /// Request to block or unblock all radios of one type.
+    SetRadioBlocked {
+        radio: RadioType,
+        blocked: bool,
+    },
+}
+
+/// Represents events that occur within the rfkill service,
+/// to be broadcast to listeners.
+#[derive(Clone, Debug)]
+pub enum RfkillServiceEvent {
+    /// Indicates that a radio type was added, removed, blocked or unblocked.
+    RadioStateChanged {
+        radio: RadioType,
+        state: RadioState,
+    },
+    /// Indicates that airplane mode was turned on or off, or that a hardware switch
+    /// started or stopped holding it on.
+    AirplaneModeChanged {
+        state: AirplaneModeState,
+    },
+    /// Return a command sender for registering event handlers.
+    HandlerRegistered {
+        command_sender: Sender<RfkillServiceRequest>,
+    },
+}
@@ -0,0 +1,175 @@
+pub mod device;
+pub mod event;
+
+use std::fs::OpenOptions;
+
+use futures_lite::{AsyncRead, AsyncWrite, future};
+use smol::{
+    Async,
+    channel::{Receiver, Sender},
+};
+use tracing::{error, info, instrument, warn};
+
+use device::{AirplaneModeState, RadioType, RfkillEvent, RfkillReader, RfkillState, write_event};
+use event::*;
+
+use super::event::{EventBus, EventListener, SubscribeOptions, Subscription};
+
+const RFKILL_DEVICE: &str = "/dev/rfkill";
+
+/// Something the main loop has to react to.
+enum RfkillWakeup {
+    Event(std::io::Result<Option<RfkillEvent>>),
+    Command(Result<RfkillServiceRequest, smol::channel::RecvError>),
+}
+
+/// Watches and controls radio kill switches through `/dev/rfkill`.
+///
+/// Opening the device makes the kernel report every existing switch, so listeners get
+/// the initial state right after `listen` starts.
+pub struct RfkillService {
+    events: EventBus<RfkillServiceEventType, RfkillServiceEvent>, // Event listeners
+    command_channel: (Sender<RfkillServiceRequest>, Receiver<RfkillServiceRequest>), // For receiving external commands
+    state: RfkillState, // Known rfkill devices
+    airplane_mode: Option<AirplaneModeState>, // Last reported airplane mode state
+}
+
+impl RfkillService {
+    /// Creates a new `RfkillService` instance.
+    pub fn new() -> Self {
+        Self {
+            events: EventBus::new(),
+            command_channel: smol::channel::unbounded::<RfkillServiceRequest>(),
+            state: RfkillState::new(),
+            airplane_mode: None,
+        }
+    }
+
+    /// Opens `/dev/rfkill` and runs the service until the device is closed.
+    pub async fn listen(&mut self) {
+        let reader = match OpenOptions::new().read(true).open(RFKILL_DEVICE).and_then(Async::new) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Failed to open {}: {}", RFKILL_DEVICE, e);
+                return;
+            }
+        };
+        // Writing needs extra permissions, the state is still reported without them.
+        let writer = match OpenOptions::new().write(true).open(RFKILL_DEVICE).and_then(Async::new) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                warn!("{} is not writable, radios cannot be toggled: {}", RFKILL_DEVICE, e);
+                None
+            }
+        };
+
+        self.run(RfkillReader::new(reader), writer).await;
+    }
+
+    /// Processes rfkill events from `reader` and writes requested changes to `writer`.
+    /// Returns when the event stream ends.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of rfkill events, normally `/dev/rfkill`.
+    /// * `writer` - Sink for block requests, `None` if radios cannot be toggled.
+    #[instrument(skip_all)]
+    pub async fn run<R, W>(&mut self, mut reader: RfkillReader<R>, mut writer: Option<W>)
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let commands = self.command_channel.1.clone();
+        loop {
+            let wakeup = future::or(
+                async { RfkillWakeup::Event(reader.next_event().await) },
+                async { RfkillWakeup::Command(commands.recv().await) },
+            )
+            .await;
+
+            match wakeup {
+                RfkillWakeup::Event(Ok(Some(event))) => self.handle_event(event),
+                RfkillWakeup::Event(Ok(None)) => {
+                    info!("rfkill event stream closed");
+                    break;
+                }
+                RfkillWakeup::Event(Err(e)) => {
+                    error!("Failed to read rfkill event: {}", e);
+                    break;
+                }
+                RfkillWakeup::Command(Ok(request)) => match writer.as_mut() {
+                    Some(writer) => Self::handle_request(writer, request).await,
+                    None => warn!("Ignoring {:?}: {} is not writable", request, RFKILL_DEVICE),
+                },
+                // We hold a sender ourselves, so this cannot happen.
+                RfkillWakeup::Command(Err(_)) => break,
+            }
+        }
+    }
+
+    /// Updates the state and notifies listeners about what changed.
+    fn handle_event(&mut self, event: RfkillEvent) {
+        let Some(radio) = self.state.apply(&event) else {
+            return;
+        };
+        self.send_msg(
+            RfkillServiceEventType::RadioStateChanged,
+            RfkillServiceEvent::RadioStateChanged {
+                radio,
+                state: self.state.radio_state(radio),
+            },
+        );
+
+        let airplane_mode = self.state.airplane_mode();
+        if self.airplane_mode != Some(airplane_mode) {
+            self.airplane_mode = Some(airplane_mode);
+            self.send_msg(
+                RfkillServiceEventType::AirplaneModeChanged,
+                RfkillServiceEvent::AirplaneModeChanged { state: airplane_mode },
+            );
+        }
+    }
+
+    /// Writes the block requests for a command.
+    /// The resulting state arrives as regular events afterwards.
+    async fn handle_request<W: AsyncWrite + Unpin>(writer: &mut W, request: RfkillServiceRequest) {
+        let changes: Vec<RfkillEvent> = match request {
+            RfkillServiceRequest::SetAirplaneMode { enabled } => RadioType::AIRPLANE_MODE
+                .iter()
+                .map(|radio| RfkillEvent::change_all(*radio, enabled))
+                .collect(),
+            RfkillServiceRequest::SetRadioBlocked { radio, blocked } => {
+                vec![RfkillEvent::change_all(radio, blocked)]
+            }
+        };
+        for change in changes {
+            if let Err(e) = write_event(writer, &change).await {
+                error!("Failed to write rfkill event {:?}: {}", change, e);
+            }
+        }
+    }
+
+    /// Publishes an `RfkillServiceEvent` to all listeners subscribed to its event type.
+    fn send_msg(&self, event_type: RfkillServiceEventType, event: RfkillServiceEvent) {
+        self.events.publish(event_type, event);
+    }
+}
+
+impl EventListener<RfkillServiceEventType, RfkillServiceEvent> for RfkillService {
+    fn event_bus(&self) -> &EventBus<RfkillServiceEventType, RfkillServiceEvent> {
+        &self.events
+    }
+
+    fn subscribe(
+        &mut self,
+        event_types: Vec<RfkillServiceEventType>,
+        options: SubscribeOptions,
+    ) -> Subscription<RfkillServiceEventType, RfkillServiceEvent> {
+        self.events.subscribe_with_initial(
+            event_types,
+            options,
+            vec![RfkillServiceEvent::HandlerRegistered {
+                command_sender: self.command_channel.0.clone(),
+            }],
+        )
+    }
+}
@@ -0,0 +1,206 @@
+use std::{cell::RefCell, time::Duration};
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use smol_timeout::TimeoutExt;
+use zbus::proxy;
+
+use crate::service::{
+    control::{
+        ControlHandler, ControlReply, ControlRequest,
+        dbus::{self, BarBus, BarState, BarStateFeed},
+    },
+    event::{EventBus, EventHandler, UIUpdateEvent, UIUpdateEventType},
+    network::{
+        endpoints::event::{NetworkDeviceType, NetworkServiceEvent, NetworkServiceEventType},
+        metered::{MeteredState, NetworkMetered},
+    },
+};
+
+use super::private_bus::PrivateBus;
+
+#[proxy(
+    interface = "io.github.bigsaltyfishes.molyuubar.Bar",
+    default_service = "io.github.bigsaltyfishes.molyuubar.Bar",
+    default_path = "/io/github/bigsaltyfishes/molyuubar/Bar"
+)]
+trait Bar {
+    fn open_popover(&self, name: &str) -> zbus::Result<()>;
+    fn show_message(&self, text: &str, timeout_ms: u32) -> zbus::Result<()>;
+    fn reload(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn visible(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn focused_app_id(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn focused_title(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn network_connection(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn network_type(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn wireless_enabled(&self) -> zbus::Result<bool>;
+    #[zbus(property)]
+    fn metered(&self) -> zbus::Result<bool>;
+}
+
+/// Records the requests it carries out and knows a single popover.
+#[derive(Default)]
+struct FakeBar {
+    requests: RefCell<Vec<ControlRequest>>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ControlHandler for FakeBar {
+    async fn handle(&self, request: ControlRequest) -> ControlReply {
+        self.requests.borrow_mut().push(request.clone());
+        match request {
+            ControlRequest::OpenPopover { name } if name != "network" => Err(format!("Unknown popover {:?}", name)),
+            ControlRequest::ReloadConfig => Err("Nothing to reload".to_string()),
+            _ => Ok(Value::Null),
+        }
+    }
+}
+
+#[test]
+fn test_bar_state_observes_events() {
+    let mut state = BarState::default();
+    state.observe_compositor(&UIUpdateEvent::WindowFocusChanged {
+        app_id: Some("firefox".to_string()),
+        title: Some("Mozilla Firefox".to_string()),
+    });
+    state.observe_network(&NetworkServiceEvent::PrimaryConnectionChanged {
+        interface: Some("wlan0".to_string()),
+        device_type: Some(NetworkDeviceType::WiFi),
+        connection_id: Some("Molyuu".to_string()),
+    });
+    state.observe_network(&NetworkServiceEvent::MeteredStateChanged {
+        state: MeteredState {
+            device: NetworkMetered::GuessYes,
+            profile: NetworkMetered::Unknown,
+        },
+    });
+    assert_eq!(
+        state,
+        BarState {
+            visible: false,
+            focused_app_id: "firefox".to_string(),
+            focused_title: "Mozilla Firefox".to_string(),
+            network_connection: "Molyuu".to_string(),
+            network_type: "WiFi".to_string(),
+            wireless_enabled: false,
+            metered: true,
+        }
+    );
+
+    // Going offline clears the connection.
+    state.observe_network(&NetworkServiceEvent::PrimaryConnectionChanged {
+        interface: None,
+        device_type: None,
+        connection_id: None,
+    });
+    assert_eq!(state.network_connection, "");
+    assert_eq!(state.network_type, "");
+}
+
+#[test]
+fn test_bar_bus_methods() {
+    let bus = PrivateBus::start().expect("This test needs dbus-daemon to run a private bus");
+
+    smol::block_on(async {
+        let (calls, call_receiver) = smol::channel::unbounded();
+        let _server = BarBus::serve(bus.builder(), BarState::default(), calls).await.unwrap();
+        let client = bus.connect().await;
+        let proxy = BarProxy::new(&client).await.unwrap();
+        let bar = FakeBar::default();
+
+        futures_lite::future::or(
+            async {
+                dbus::answer_calls(call_receiver, &bar).await;
+            },
+            async {
+                proxy.open_popover("network").await.unwrap();
+                proxy.show_message("Hello", 0).await.unwrap();
+                proxy.show_message("Bye", 500).await.unwrap();
+                assert!(matches!(
+                    proxy.open_popover("calendar").await,
+                    Err(zbus::Error::MethodError(_, Some(message), _)) if message.contains("calendar")
+                ));
+                assert!(matches!(
+                    proxy.reload().await,
+                    Err(zbus::Error::MethodError(_, Some(message), _)) if message == "Nothing to reload"
+                ));
+            },
+        )
+        .await;
+
+        assert_eq!(
+            *bar.requests.borrow(),
+            vec![
+                ControlRequest::OpenPopover { name: "network".to_string() },
+                ControlRequest::ShowMessage { text: "Hello".to_string(), timeout_ms: 3000 },
+                ControlRequest::ShowMessage { text: "Bye".to_string(), timeout_ms: 500 },
+                ControlRequest::OpenPopover { name: "calendar".to_string() },
+                ControlRequest::ReloadConfig,
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_bar_bus_properties() {
+    let bus = PrivateBus::start().expect("This test needs dbus-daemon to run a private bus");
+
+    smol::block_on(async {
+        let compositor_events = EventBus::<UIUpdateEventType, UIUpdateEvent>::new();
+        let network_events = EventBus::<NetworkServiceEventType, NetworkServiceEvent>::new();
+        let mut feed = BarStateFeed::new();
+        feed.register_to_listener(&mut compositor_events.clone());
+        feed.register_to_listener(&mut network_events.clone());
+
+        let (calls, _call_receiver) = smol::channel::unbounded();
+        let state = BarState {
+            visible: true,
+            ..Default::default()
+        };
+        let server = BarBus::serve(bus.builder(), state, calls).await.unwrap();
+        let _feed = smol::spawn(feed.listen(server.clone()));
+
+        let client = bus.connect().await;
+        let proxy = BarProxy::new(&client).await.unwrap();
+        assert!(proxy.visible().await.unwrap());
+        assert_eq!(proxy.focused_title().await.unwrap(), "");
+
+        let timeout = Duration::from_secs(5);
+        let mut visible = proxy.receive_visible_changed().await;
+        server.update(|state| state.visible = false).await.unwrap();
+        let changed = visible.next().timeout(timeout).await.expect("Timed out waiting for Visible").unwrap();
+        assert!(!changed.get().await.unwrap());
+
+        let mut title = proxy.receive_focused_title_changed().await;
+        compositor_events.publish(
+            UIUpdateEventType::WindowFocusChanged,
+            UIUpdateEvent::WindowFocusChanged {
+                app_id: Some("foot".to_string()),
+                title: Some("~".to_string()),
+            },
+        );
+        let changed = title.next().timeout(timeout).await.expect("Timed out waiting for FocusedTitle").unwrap();
+        assert_eq!(changed.get().await.unwrap(), "~");
+        assert_eq!(proxy.focused_app_id().await.unwrap(), "foot");
+
+        let mut wireless = proxy.receive_wireless_enabled_changed().await;
+        network_events.publish(
+            NetworkServiceEventType::GlobalWirelessEnabledStateChanged,
+            NetworkServiceEvent::GlobalWirelessEnabledStateChanged { enabled: true },
+        );
+        let changed = wireless
+            .next()
+            .timeout(timeout)
+            .await
+            .expect("Timed out waiting for WirelessEnabled")
+            .unwrap();
+        assert!(changed.get().await.unwrap());
+    });
+}
@@ -0,0 +1,132 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::service::{
+    compositor::{CompositorKind, StatePublisher},
+    event::{EventBus, KeyboardLayouts, SubscribeOptions, UIUpdateEvent, UIUpdateEventType, WindowInfo, WorkspaceInfo},
+};
+
+fn detect(vars: &[(&str, &str)]) -> Option<CompositorKind> {
+    let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    CompositorKind::detect(|name| vars.get(name).cloned())
+}
+
+#[test]
+fn test_compositor_detection() {
+    assert_eq!(detect(&[]), None);
+    assert_eq!(detect(&[("NIRI_SOCKET", "")]), None);
+    assert_eq!(
+        detect(&[("SWAYSOCK", "/run/sway.sock"), ("NIRI_SOCKET", "/run/niri.sock")]),
+        Some(CompositorKind::Niri("/run/niri.sock".into()))
+    );
+    assert_eq!(
+        detect(&[("SWAYSOCK", "/run/sway.sock"), ("I3SOCK", "/run/i3.sock")]),
+        Some(CompositorKind::Sway("/run/sway.sock".into()))
+    );
+    assert_eq!(
+        detect(&[("I3SOCK", "/run/i3.sock")]),
+        Some(CompositorKind::Sway("/run/i3.sock".into()))
+    );
+
+    // Hyprland uses the runtime directory when the instance lives there, /tmp otherwise.
+    let runtime = std::env::temp_dir().join(format!("molyuu-bar-runtime-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(runtime.join("hypr/abc")).unwrap();
+    let runtime_str = runtime.to_str().unwrap();
+    assert_eq!(
+        detect(&[
+            ("HYPRLAND_INSTANCE_SIGNATURE", "abc"),
+            ("XDG_RUNTIME_DIR", runtime_str),
+            ("SWAYSOCK", "/run/sway.sock"),
+        ]),
+        Some(CompositorKind::Hyprland(runtime.join("hypr/abc")))
+    );
+    assert_eq!(
+        detect(&[("HYPRLAND_INSTANCE_SIGNATURE", "def"), ("XDG_RUNTIME_DIR", runtime_str)]),
+        Some(CompositorKind::Hyprland(PathBuf::from("/tmp/hypr/def")))
+    );
+    std::fs::remove_dir_all(runtime).unwrap();
+}
+
+fn window(id: u64, title: &str, is_focused: bool) -> WindowInfo {
+    WindowInfo {
+        id,
+        title: Some(title.to_string()),
+        app_id: Some("foot".to_string()),
+        workspace_id: Some(1),
+        is_focused,
+        is_fullscreen: false,
+    }
+}
+
+fn workspace(id: u64, is_focused: bool) -> WorkspaceInfo {
+    WorkspaceInfo {
+        id,
+        idx: id as u8,
+        name: None,
+        output: Some("DP-1".to_string()),
+        is_active: is_focused,
+        is_focused,
+        windows: 0,
+    }
+}
+
+#[test]
+fn test_state_publisher_sends_changes() {
+    let events = EventBus::new();
+    let subscription = events.subscribe(
+        vec![
+            UIUpdateEventType::WorkspaceChanged,
+            UIUpdateEventType::WindowFocusChanged,
+            UIUpdateEventType::WindowOpened,
+            UIUpdateEventType::WindowChanged,
+            UIUpdateEventType::WindowClosed,
+            UIUpdateEventType::KeyboardLayoutChanged,
+        ],
+        SubscribeOptions::new(),
+    );
+    let mut publisher = StatePublisher::new("Sway", events);
+
+    publisher.update_workspaces(vec![workspace(1, true), workspace(2, false)]);
+    publisher.update_workspaces(vec![workspace(1, true), workspace(2, false)]);
+    assert!(matches!(
+        subscription.try_recv(),
+        Some(UIUpdateEvent::WorkspaceChanged { workspaces }) if workspaces.len() == 2
+    ));
+    assert!(subscription.try_recv().is_none());
+
+    // Without a focused window the compositor name stands in for the app.
+    publisher.update_windows(Vec::new());
+    assert!(matches!(
+        subscription.try_recv(),
+        Some(UIUpdateEvent::WindowFocusChanged { app_id, title })
+            if app_id.as_deref() == Some("Sway") && title.as_deref() == Some("Desktop")
+    ));
+
+    publisher.update_windows(vec![window(1, "a", true)]);
+    assert!(matches!(subscription.try_recv(), Some(UIUpdateEvent::WindowOpened { window }) if window.id == 1));
+    assert!(matches!(
+        subscription.try_recv(),
+        Some(UIUpdateEvent::WindowFocusChanged { title, .. }) if title.as_deref() == Some("a")
+    ));
+
+    // A second window opening in the background changes nothing about the focus.
+    publisher.update_windows(vec![window(1, "a", true), window(2, "b", false)]);
+    assert!(matches!(subscription.try_recv(), Some(UIUpdateEvent::WindowOpened { window }) if window.id == 2));
+    assert!(subscription.try_recv().is_none());
+
+    publisher.update_windows(vec![window(2, "b", true)]);
+    assert!(matches!(subscription.try_recv(), Some(UIUpdateEvent::WindowClosed { id: 1 })));
+    assert!(matches!(subscription.try_recv(), Some(UIUpdateEvent::WindowChanged { window }) if window.id == 2));
+    assert!(matches!(
+        subscription.try_recv(),
+        Some(UIUpdateEvent::WindowFocusChanged { title, .. }) if title.as_deref() == Some("b")
+    ));
+
+    let layouts = KeyboardLayouts {
+        names: vec!["English (US)".to_string(), "German".to_string()],
+        current: 0,
+    };
+    publisher.update_keyboard_layouts(layouts.clone());
+    publisher.update_keyboard_layouts(layouts);
+    assert!(matches!(subscription.try_recv(), Some(UIUpdateEvent::KeyboardLayoutChanged { .. })));
+    assert!(subscription.try_recv().is_none());
+}
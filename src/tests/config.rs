@@ -0,0 +1,93 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::utils::{
+    config::{self, AUTO_HIDE_VAR, BarOptions, Config, SCROLL_VAR, WINDOW_LIST_VAR},
+    scroll::ScrollOptions,
+    visibility::AutoHideMode,
+};
+use crate::widgets::window_list::WindowListScope;
+
+fn vars(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    move |name| vars.get(name).cloned()
+}
+
+#[test]
+fn test_bar_options_read() {
+    assert_eq!(BarOptions::read(vars(&[])), BarOptions::default());
+
+    let options = BarOptions::read(vars(&[
+        (AUTO_HIDE_VAR, "fullscreen"),
+        (WINDOW_LIST_VAR, "all-workspaces"),
+        (SCROLL_VAR, "inverted"),
+    ]));
+    assert_eq!(options.auto_hide, AutoHideMode::Fullscreen);
+    assert_eq!(options.window_list_scope, WindowListScope::AllWorkspaces);
+    assert!(options.scroll.invert);
+
+    // An invalid value only resets its own option.
+    let options = BarOptions::read(vars(&[(AUTO_HIDE_VAR, "sometimes"), (SCROLL_VAR, "natural")]));
+    assert_eq!(options.auto_hide, AutoHideMode::Off);
+    assert!(options.scroll.invert);
+}
+
+#[test]
+fn test_parse_config() {
+    let config = config::parse_config(
+        "# molyuu-bar\n\
+         MOLYUU_BAR_AUTO_HIDE = always\n\
+         \n\
+         not an option\n\
+         MOLYUU_BAR_SCROLL=inverted\n",
+    );
+    assert_eq!(config.len(), 2);
+    assert_eq!(config[AUTO_HIDE_VAR], "always");
+    assert_eq!(config[SCROLL_VAR], "inverted");
+}
+
+#[test]
+fn test_config_dir() {
+    assert_eq!(
+        config::config_dir(vars(&[("XDG_CONFIG_HOME", "/etc/xdg"), ("HOME", "/home/user")])),
+        Some(PathBuf::from("/etc/xdg/molyuu-bar"))
+    );
+    assert_eq!(
+        config::config_dir(vars(&[("XDG_CONFIG_HOME", ""), ("HOME", "/home/user")])),
+        Some(PathBuf::from("/home/user/.config/molyuu-bar"))
+    );
+    assert_eq!(config::config_dir(vars(&[])), None);
+}
+
+#[test]
+fn test_config_load() {
+    let dir = std::env::temp_dir().join(format!("molyuu-bar-config-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir(&dir).unwrap();
+
+    // Without files, the defaults and the environment apply.
+    let config = Config::load(Some(&dir), vars(&[(SCROLL_VAR, "inverted")])).unwrap();
+    assert_eq!(config.stylesheet, "");
+    assert!(config.options.scroll.invert);
+    assert_eq!(Config::load(None, vars(&[])).unwrap(), Config::default());
+
+    std::fs::write(
+        dir.join("config"),
+        "MOLYUU_BAR_AUTO_HIDE=always\nMOLYUU_BAR_WINDOW_LIST=all\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("style.css"), "window { opacity: 0.9; }").unwrap();
+    let config = Config::load(Some(&dir), vars(&[(AUTO_HIDE_VAR, "fullscreen")])).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // The environment takes precedence over the config file.
+    assert_eq!(
+        config,
+        Config {
+            options: BarOptions {
+                auto_hide: AutoHideMode::Fullscreen,
+                window_list_scope: WindowListScope::AllWorkspaces,
+                scroll: ScrollOptions::default(),
+            },
+            stylesheet: "window { opacity: 0.9; }".to_string(),
+        }
+    );
+}
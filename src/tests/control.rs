@@ -0,0 +1,273 @@
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, time::Duration};
+
+use serde_json::{Value, json};
+use smol_timeout::TimeoutExt;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::service::{
+    control::{
+        self, ControlError, ControlHandler, ControlReply, ControlRequest, ControlServer,
+        state::{compositor_state, network_state},
+    },
+    event::{KeyboardLayouts, WindowInfo, WorkspaceInfo},
+    network::{
+        endpoints::event::{NetworkDeviceState, NetworkDeviceType},
+        snapshot::{NetworkDeviceSnapshot, NetworkSnapshot},
+        wireless::ap::{AccessPoint, AccessPointSecurity},
+    },
+};
+
+#[test]
+fn test_control_request_from_args() {
+    assert_eq!(ControlRequest::from_args(&["toggle-visibility"]), Ok(ControlRequest::ToggleVisibility));
+    assert_eq!(
+        ControlRequest::from_args(&["open-popover", "network"]),
+        Ok(ControlRequest::OpenPopover { name: "network".to_string() })
+    );
+    assert_eq!(ControlRequest::from_args(&["reload-config"]), Ok(ControlRequest::ReloadConfig));
+    assert_eq!(ControlRequest::from_args(&["dump-state"]), Ok(ControlRequest::DumpState));
+    assert!(ControlRequest::from_args(&["open-popover"]).is_err());
+    assert!(ControlRequest::from_args(&["dump-state", "now"]).is_err());
+    assert!(ControlRequest::from_args::<&str>(&[]).is_err());
+
+    let request = ControlRequest::OpenPopover { name: "network".to_string() };
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({"open-popover": {"name": "network"}})
+    );
+}
+
+#[test]
+fn test_control_socket_path() {
+    let path = |vars: &[(&str, &str)]| {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        control::socket_path(|name| vars.get(name).cloned())
+    };
+    assert_eq!(
+        path(&[("XDG_RUNTIME_DIR", "/run/user/1000"), ("WAYLAND_DISPLAY", "wayland-1")]),
+        PathBuf::from("/run/user/1000/molyuu-bar.wayland-1.sock")
+    );
+    assert_eq!(
+        path(&[("MOLYUU_BAR_SOCKET", "/tmp/bar.sock"), ("XDG_RUNTIME_DIR", "/run/user/1000")]),
+        PathBuf::from("/tmp/bar.sock")
+    );
+    assert_eq!(
+        path(&[("XDG_RUNTIME_DIR", "")]),
+        std::env::temp_dir().join("molyuu-bar.wayland-0.sock")
+    );
+}
+
+/// Records the requests and answers like a bar that starts out visible.
+#[derive(Default)]
+struct FakeBar {
+    hidden: RefCell<bool>,
+    requests: RefCell<Vec<ControlRequest>>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ControlHandler for FakeBar {
+    async fn handle(&self, request: ControlRequest) -> ControlReply {
+        self.requests.borrow_mut().push(request.clone());
+        match request {
+            ControlRequest::ToggleVisibility => {
+                let mut hidden = self.hidden.borrow_mut();
+                *hidden = !*hidden;
+                Ok(json!({"visible": !*hidden}))
+            }
+            ControlRequest::DumpState => Ok(json!({"bar": {"visible": !*self.hidden.borrow()}})),
+            _ => Err("Not supported".to_string()),
+        }
+    }
+}
+
+#[test]
+fn test_control_server() {
+    let path = std::env::temp_dir().join(format!("molyuu-bar-control-{}.sock", uuid::Uuid::new_v4()));
+    // A socket left behind by a crashed instance is replaced.
+    std::fs::write(&path, "").unwrap();
+    let server = ControlServer::bind(&path).unwrap();
+    let bar = FakeBar::default();
+
+    smol::block_on(futures_lite::future::or(
+        async {
+            server.serve(&bar).await;
+        },
+        async {
+            let reply = control::send(&path, &ControlRequest::ToggleVisibility).await.unwrap();
+            assert_eq!(reply, json!({"visible": false}));
+            let reply = control::send(&path, &ControlRequest::DumpState).await.unwrap();
+            assert_eq!(reply["bar"]["visible"], json!(false));
+            assert!(matches!(
+                control::send(&path, &ControlRequest::ReloadConfig).await,
+                Err(ControlError::Rejected(message)) if message == "Not supported"
+            ));
+
+            // Garbage is answered with an error instead of closing the connection.
+            let mut stream = smol::net::unix::UnixStream::connect(&path).await.unwrap();
+            smol::io::AsyncWriteExt::write_all(&mut stream, b"{\"sleep\": 1}\n").await.unwrap();
+            let mut reply = String::new();
+            smol::io::AsyncBufReadExt::read_line(&mut smol::io::BufReader::new(stream), &mut reply)
+                .await
+                .unwrap();
+            assert!(matches!(
+                serde_json::from_str::<ControlReply>(&reply).unwrap(),
+                Err(message) if message.starts_with("Invalid request")
+            ));
+        },
+    ));
+    assert_eq!(
+        *bar.requests.borrow(),
+        vec![ControlRequest::ToggleVisibility, ControlRequest::DumpState, ControlRequest::ReloadConfig]
+    );
+
+    drop(server);
+    assert!(!path.exists());
+    assert!(matches!(
+        smol::block_on(control::send(&path, &ControlRequest::DumpState)),
+        Err(ControlError::Io(_))
+    ));
+}
+
+#[test]
+fn test_control_server_answers_connections_concurrently() {
+    let path = std::env::temp_dir().join(format!("molyuu-bar-control-{}.sock", uuid::Uuid::new_v4()));
+    let server = ControlServer::bind(&path).unwrap();
+    let bar = FakeBar::default();
+
+    smol::block_on(futures_lite::future::or(
+        async {
+            server.serve(&bar).await;
+        },
+        async {
+            // A client that never sends its request does not hold up the next one.
+            let _idle = smol::net::unix::UnixStream::connect(&path).await.unwrap();
+            let reply = control::send(&path, &ControlRequest::ToggleVisibility)
+                .timeout(Duration::from_secs(1))
+                .await
+                .expect("Timed out behind the idle connection")
+                .unwrap();
+            assert_eq!(reply, json!({"visible": false}));
+        },
+    ));
+}
+
+#[test]
+fn test_control_server_refuses_running_bar() {
+    let path = std::env::temp_dir().join(format!("molyuu-bar-control-{}.sock", uuid::Uuid::new_v4()));
+    let server = ControlServer::bind(&path).unwrap();
+
+    // The socket of a bar that is still listening is left alone.
+    assert!(matches!(
+        ControlServer::bind(&path),
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse
+    ));
+    assert!(path.exists());
+
+    drop(server);
+
+    // A socket whose bar is gone is replaced.
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+    assert!(ControlServer::bind(&path).is_ok());
+}
+
+#[test]
+fn test_compositor_state() {
+    let workspaces = vec![WorkspaceInfo {
+        id: 1,
+        idx: 1,
+        name: Some("web".to_string()),
+        output: Some("DP-1".to_string()),
+        is_active: true,
+        is_focused: true,
+        windows: 1,
+    }];
+    let windows = vec![WindowInfo {
+        id: 7,
+        title: Some("Mozilla Firefox".to_string()),
+        app_id: Some("firefox".to_string()),
+        workspace_id: Some(1),
+        is_focused: true,
+        is_fullscreen: false,
+    }];
+    let layouts = KeyboardLayouts {
+        names: vec!["English (US)".to_string()],
+        current: 0,
+    };
+
+    let state = compositor_state("niri", &workspaces, &windows, Some(&layouts));
+    assert_eq!(state["name"], "niri");
+    assert_eq!(
+        state["workspaces"],
+        json!([{
+            "id": 1, "idx": 1, "name": "web", "output": "DP-1",
+            "is_active": true, "is_focused": true, "windows": 1,
+        }])
+    );
+    assert_eq!(state["windows"][0]["app_id"], "firefox");
+    assert_eq!(state["keyboard_layouts"], json!({"names": ["English (US)"], "current": 0}));
+    assert_eq!(compositor_state("Sway", &[], &[], None)["keyboard_layouts"], Value::Null);
+}
+
+fn access_point(ssid: &str, frequency: u32, signal_strength: u8) -> AccessPoint {
+    AccessPoint {
+        ssid: ssid.to_string(),
+        flags: 0,
+        wpa_flags: 0,
+        rsn_flags: 0,
+        mode: 2,
+        bssid: "00:11:22:33:44:55".to_string(),
+        frequency,
+        signal_strength,
+        last_seen: 0,
+        dbus_path: OwnedObjectPath::try_from("/org/freedesktop/NetworkManager/AccessPoint/1").unwrap(),
+    }
+}
+
+#[test]
+fn test_network_state() {
+    let mut wlan = NetworkDeviceSnapshot::new("wlan0".to_string(), NetworkDeviceType::WiFi);
+    wlan.state = Some(NetworkDeviceState::Activated);
+    wlan.active_access_point = Some(access_point("Molyuu", 5180, 80));
+    wlan.access_points.insert(
+        ("Cafe".to_string(), AccessPointSecurity::None),
+        vec![access_point("Cafe", 2412, 40)],
+    );
+    wlan.access_points.insert(
+        ("Molyuu".to_string(), AccessPointSecurity::WPA),
+        vec![access_point("Molyuu", 5180, 80), access_point("Molyuu", 2437, 60)],
+    );
+    let mut snapshot = NetworkSnapshot {
+        devices: vec![wlan],
+        wireless_enabled: Some(true),
+        ..Default::default()
+    };
+    snapshot.primary_connection.interface = Some("wlan0".to_string());
+    snapshot.primary_connection.device_type = Some(NetworkDeviceType::WiFi);
+    snapshot
+        .saved_profiles
+        .insert(("Molyuu".to_string(), AccessPointSecurity::WPA), true);
+
+    let state = network_state(&snapshot);
+    let device = &state["devices"][0];
+    assert_eq!(device["type"], "WiFi");
+    assert_eq!(device["state"], "Activated");
+    assert_eq!(
+        device["active_access_point"],
+        json!({
+            "ssid": "Molyuu", "bssid": "00:11:22:33:44:55", "frequency": 5180,
+            "band": "5 GHz", "channel": 36, "signal_strength": 80,
+        })
+    );
+    // Strongest networks first.
+    assert_eq!(device["access_points"][0]["ssid"], "Molyuu");
+    assert_eq!(device["access_points"][0]["security"], "WPA");
+    assert_eq!(device["access_points"][0]["access_points"].as_array().unwrap().len(), 2);
+    assert_eq!(device["access_points"][1]["ssid"], "Cafe");
+    assert_eq!(device["wired_link"], Value::Null);
+
+    assert_eq!(state["wireless_enabled"], true);
+    assert_eq!(state["primary_connection"]["type"], "WiFi");
+    assert_eq!(state["metered"]["is_metered"], false);
+    assert_eq!(state["saved_profiles"], json!([{"ssid": "Molyuu", "security": "WPA", "works": true}]));
+}
@@ -0,0 +1,87 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use crate::service::desktop_entry::{DesktopEntries, DesktopEntry, locale_keys};
+
+const NAUTILUS: &str = "[Desktop Entry]
+Name=Files
+Name[de]=Dateien
+Icon=org.gnome.Nautilus
+Type=Application
+
+[Desktop Action new-window]
+Name=New Window
+";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("molyuu-bar-{}-{}", name, uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_locale_keys() {
+    assert_eq!(locale_keys("de_DE.UTF-8@euro"), ["de_DE@euro", "de_DE", "de@euro", "de"]);
+    assert_eq!(locale_keys("sr@latin"), ["sr@latin", "sr"]);
+    assert_eq!(locale_keys("pt_BR.UTF-8"), ["pt_BR", "pt"]);
+    assert!(locale_keys("C.UTF-8").is_empty());
+}
+
+#[test]
+fn test_parse_desktop_entry() {
+    let path = PathBuf::from("org.gnome.Nautilus.desktop");
+    let entry = DesktopEntry::parse("org.gnome.Nautilus", &path, NAUTILUS, &locale_keys("de_AT.UTF-8")).unwrap();
+    assert_eq!(entry.name, "Dateien");
+    assert_eq!(entry.icon.as_deref(), Some("org.gnome.Nautilus"));
+
+    // Values of other groups are ignored.
+    let entry = DesktopEntry::parse("org.gnome.Nautilus", &path, NAUTILUS, &[]).unwrap();
+    assert_eq!(entry.name, "Files");
+
+    let hidden = format!("{}Hidden=true\n", NAUTILUS.replace("\n\n[Desktop Action new-window]\nName=New Window\n", "\n"));
+    assert!(DesktopEntry::parse("org.gnome.Nautilus", &path, &hidden, &[]).is_none());
+}
+
+#[test]
+fn test_resolve_desktop_entries() {
+    let user = temp_dir("user");
+    let system = temp_dir("system");
+    fs::write(system.join("org.gnome.Nautilus.desktop"), NAUTILUS).unwrap();
+    fs::write(
+        system.join("chrome.desktop"),
+        "[Desktop Entry]\nName=System Chrome\nIcon=google-chrome\nStartupWMClass=Google-chrome\n",
+    )
+    .unwrap();
+    fs::create_dir_all(system.join("kde")).unwrap();
+    fs::write(system.join("kde/okular.desktop"), "[Desktop Entry]\nName=Okular\n").unwrap();
+    // The user's entry overrides the system one with the same id.
+    fs::write(user.join("chrome.desktop"), "[Desktop Entry]\nName=Chrome\nStartupWMClass=Google-chrome\n").unwrap();
+
+    let mut entries = DesktopEntries::new(vec![user.clone(), system.clone()], Vec::new());
+    let name = |entries: &mut DesktopEntries, app_id: &str| entries.resolve(app_id).map(|e| e.name.clone());
+    assert_eq!(name(&mut entries, "org.gnome.Nautilus").as_deref(), Some("Files"));
+    assert_eq!(name(&mut entries, "org.gnome.nautilus").as_deref(), Some("Files"));
+    assert_eq!(name(&mut entries, "nautilus").as_deref(), Some("Files"));
+    assert_eq!(name(&mut entries, "Google-chrome").as_deref(), Some("Chrome"));
+    assert_eq!(name(&mut entries, "kde-okular").as_deref(), Some("Okular"));
+    assert_eq!(name(&mut entries, "foot"), None);
+
+    // Explicit invalidation picks up new applications.
+    fs::write(user.join("foot.desktop"), "[Desktop Entry]\nName=Foot\nIcon=foot\n").unwrap();
+    entries.invalidate();
+    assert_eq!(name(&mut entries, "foot").as_deref(), Some("Foot"));
+
+    // So does a changed modification time of an application directory. It is set
+    // explicitly, as the clock may not have advanced since the index was built.
+    fs::write(system.join("alacritty.desktop"), "[Desktop Entry]\nName=Alacritty\n").unwrap();
+    fs::File::open(&system)
+        .and_then(|dir| dir.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(86400)))
+        .unwrap();
+    assert_eq!(name(&mut entries, "alacritty").as_deref(), Some("Alacritty"));
+
+    fs::remove_dir_all(user).unwrap();
+    fs::remove_dir_all(system).unwrap();
+}
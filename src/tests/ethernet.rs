@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use smol_timeout::TimeoutExt;
+use zbus::{interface, zvariant::OwnedObjectPath};
+
+use crate::service::network::ethernet::{WiredLink, watch_wired_link};
+
+use super::private_bus::PrivateBus;
+
+const NETWORK_MANAGER: &str = "org.freedesktop.NetworkManager";
+const DEVICE_PATH: &str = "/org/freedesktop/NetworkManager/Devices/2";
+
+struct FakeWired {
+    carrier: bool,
+    speed: u32,
+}
+
+#[interface(name = "org.freedesktop.NetworkManager.Device.Wired")]
+impl FakeWired {
+    #[zbus(property)]
+    fn carrier(&self) -> bool {
+        self.carrier
+    }
+
+    #[zbus(property)]
+    fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    #[zbus(property)]
+    fn perm_hw_address(&self) -> String {
+        "52:54:00:12:34:56".to_string()
+    }
+}
+
+#[test]
+fn test_wired_link_description() {
+    let mut link = WiredLink {
+        carrier: true,
+        speed: 1000,
+        perm_hw_address: String::new(),
+    };
+    assert_eq!(link.description(), "1 Gb/s, cable connected");
+
+    link.speed = 2500;
+    assert_eq!(link.speed_label().as_deref(), Some("2.5 Gb/s"));
+    link.speed = 100;
+    assert_eq!(link.speed_label().as_deref(), Some("100 Mb/s"));
+
+    // Some drivers don't report a speed.
+    link.speed = 0;
+    assert_eq!(link.description(), "Cable connected");
+
+    link.carrier = false;
+    assert_eq!(link.description(), "Cable unplugged");
+}
+
+#[test]
+fn test_wired_link_from_fake_device() {
+    let bus = PrivateBus::start().expect("This test needs dbus-daemon to run a private bus");
+
+    smol::block_on(async {
+        let server = bus
+            .builder()
+            .name(NETWORK_MANAGER)
+            .unwrap()
+            .serve_at(DEVICE_PATH, FakeWired { carrier: false, speed: 0 })
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = bus.connect().await;
+        let path = OwnedObjectPath::try_from(DEVICE_PATH).unwrap();
+
+        let (tx, rx) = smol::channel::unbounded();
+        let watcher = smol::spawn(async move { watch_wired_link(&client, path, tx).await });
+        let link = rx.recv().await.unwrap();
+        assert!(!link.carrier);
+        assert_eq!(link.perm_hw_address, "52:54:00:12:34:56");
+
+        // Plug in a cable.
+        let wired = server
+            .object_server()
+            .interface::<_, FakeWired>(DEVICE_PATH)
+            .await
+            .unwrap();
+        {
+            let mut fake = wired.get_mut().await;
+            fake.carrier = true;
+            fake.speed = 1000;
+        }
+        let fake = wired.get().await;
+        fake.carrier_changed(wired.signal_context()).await.unwrap();
+        fake.speed_changed(wired.signal_context()).await.unwrap();
+        drop(fake);
+
+        let mut link = link;
+        while link.description() != "1 Gb/s, cable connected" {
+            link = rx
+                .recv()
+                .timeout(Duration::from_secs(5))
+                .await
+                .expect("Timed out waiting for a link update")
+                .unwrap();
+        }
+
+        drop(rx);
+        drop(watcher);
+    });
+}
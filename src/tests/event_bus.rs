@@ -0,0 +1,138 @@
+use std::time::{Duration, Instant};
+
+use crate::service::event::{EventBus, OverflowPolicy, SubscribeOptions};
+
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+enum TestTopic {
+    Volume,
+    Brightness,
+}
+
+#[test]
+fn test_event_bus_routes_by_topic() {
+    smol::block_on(async {
+        let bus = EventBus::<TestTopic, u32>::new();
+        let volume = bus.subscribe(vec![TestTopic::Volume], SubscribeOptions::new());
+        let both = bus.subscribe(vec![TestTopic::Volume, TestTopic::Brightness], SubscribeOptions::new());
+
+        bus.publish(TestTopic::Volume, 1);
+        bus.publish(TestTopic::Brightness, 2);
+
+        assert_eq!(volume.recv().await, Some(1));
+        assert_eq!(volume.try_recv(), None);
+        assert_eq!(both.recv().await, Some(1));
+        assert_eq!(both.recv().await, Some(2));
+    });
+}
+
+#[test]
+fn test_event_bus_unsubscribes_on_drop() {
+    smol::block_on(async {
+        let bus = EventBus::<TestTopic, u32>::new();
+        let subscription = bus.subscribe(vec![TestTopic::Volume], SubscribeOptions::new());
+        assert_eq!(bus.subscriber_count(&TestTopic::Volume), 1);
+        drop(subscription);
+        assert_eq!(bus.subscriber_count(&TestTopic::Volume), 0);
+
+        // Subscriptions end once the bus is gone, after draining what was queued.
+        let subscription = bus.subscribe(vec![TestTopic::Volume], SubscribeOptions::new());
+        bus.publish(TestTopic::Volume, 1);
+        drop(bus);
+        assert_eq!(subscription.recv().await, Some(1));
+        assert_eq!(subscription.recv().await, None);
+    });
+}
+
+#[test]
+fn test_event_bus_replay_and_initial_events() {
+    smol::block_on(async {
+        let bus = EventBus::<TestTopic, u32>::new();
+        bus.publish(TestTopic::Brightness, 10);
+        bus.publish(TestTopic::Volume, 20);
+        bus.publish(TestTopic::Volume, 30);
+
+        let late = bus.subscribe(vec![TestTopic::Volume], SubscribeOptions::new());
+        assert_eq!(late.try_recv(), None);
+
+        // Initial events come first, replayed events follow in publishing order.
+        let replayed = bus.subscribe_with_initial(
+            vec![TestTopic::Volume, TestTopic::Brightness],
+            SubscribeOptions::new().replay(),
+            vec![0],
+        );
+        assert_eq!(replayed.recv().await, Some(0));
+        assert_eq!(replayed.recv().await, Some(10));
+        assert_eq!(replayed.recv().await, Some(30));
+        assert_eq!(replayed.try_recv(), None);
+        assert_eq!(bus.last(&TestTopic::Volume), Some(30));
+    });
+}
+
+#[test]
+fn test_event_bus_without_replay() {
+    smol::block_on(async {
+        let bus = EventBus::<TestTopic, u32>::new().without_replay([TestTopic::Brightness]);
+        bus.publish(TestTopic::Brightness, 10);
+        bus.publish(TestTopic::Volume, 20);
+
+        let replayed = bus.subscribe(
+            vec![TestTopic::Volume, TestTopic::Brightness],
+            SubscribeOptions::new().replay(),
+        );
+        assert_eq!(replayed.recv().await, Some(20));
+        assert_eq!(replayed.try_recv(), None);
+        assert_eq!(bus.last(&TestTopic::Brightness), None);
+
+        // Live events on the topic are still delivered.
+        bus.publish(TestTopic::Brightness, 11);
+        assert_eq!(replayed.recv().await, Some(11));
+    });
+}
+
+#[test]
+fn test_event_bus_overflow_policies() {
+    let bus = EventBus::<TestTopic, u32>::new();
+    let coalescing = bus.subscribe(
+        vec![TestTopic::Volume, TestTopic::Brightness],
+        SubscribeOptions::new().bounded(2, OverflowPolicy::Coalesce),
+    );
+    let dropping_oldest = bus.subscribe(
+        vec![TestTopic::Volume, TestTopic::Brightness],
+        SubscribeOptions::new().bounded(2, OverflowPolicy::DropOldest),
+    );
+    let dropping_newest = bus.subscribe(
+        vec![TestTopic::Volume, TestTopic::Brightness],
+        SubscribeOptions::new().bounded(2, OverflowPolicy::DropNewest),
+    );
+
+    bus.publish(TestTopic::Volume, 1);
+    bus.publish(TestTopic::Brightness, 2);
+    bus.publish(TestTopic::Volume, 3);
+
+    let drain = |subscription: &crate::service::event::Subscription<TestTopic, u32>| {
+        std::iter::from_fn(|| subscription.try_recv()).collect::<Vec<_>>()
+    };
+    // The queued volume is replaced by the latest one.
+    assert_eq!(drain(&coalescing), [3, 2]);
+    assert_eq!(drain(&dropping_oldest), [2, 3]);
+    assert_eq!(drain(&dropping_newest), [1, 2]);
+}
+
+#[test]
+fn test_event_bus_recv_until() {
+    smol::block_on(async {
+        let bus = EventBus::<TestTopic, u32>::new();
+        let subscription = bus.subscribe(vec![TestTopic::Volume], SubscribeOptions::new());
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        assert_eq!(subscription.recv_until(deadline).await, None);
+        assert!(Instant::now() >= deadline);
+
+        // Queued events are returned right away, one at a time.
+        bus.publish(TestTopic::Volume, 1);
+        bus.publish(TestTopic::Volume, 2);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(subscription.recv_until(deadline).await, Some(1));
+        assert_eq!(subscription.recv().await, Some(2));
+    });
+}
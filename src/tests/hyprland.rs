@@ -0,0 +1,238 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use smol::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::unix::{UnixListener, UnixStream},
+};
+
+use crate::service::{
+    compositor::{
+        Compositor, CompositorCommand, CompositorError, StatePublisher,
+        hyprland::{HyprlandCompositor, parse_address},
+    },
+    event::{EventBus, SubscribeOptions, UIUpdateEvent, UIUpdateEventType},
+};
+
+#[derive(Default)]
+struct HyprState {
+    /// Id of the active workspace.
+    active: i64,
+    commands: Vec<String>,
+    event_streams: Vec<UnixStream>,
+}
+
+/// A fake Hyprland instance directory with a command and an event socket.
+struct FakeHyprland {
+    dir: PathBuf,
+    state: Arc<Mutex<HyprState>>,
+    _server: smol::Task<()>,
+    _events: smol::Task<()>,
+}
+
+impl FakeHyprland {
+    fn start() -> Self {
+        let dir = std::env::temp_dir().join(format!("molyuu-bar-hypr-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let commands = UnixListener::bind(dir.join(".socket.sock")).unwrap();
+        let events = UnixListener::bind(dir.join(".socket2.sock")).unwrap();
+        let state = Arc::new(Mutex::new(HyprState {
+            active: 1,
+            ..Default::default()
+        }));
+
+        let server_state = state.clone();
+        let server = smol::spawn(async move {
+            while let Ok((stream, _)) = commands.accept().await {
+                Self::serve(stream, &server_state).await;
+            }
+        });
+        let events_state = state.clone();
+        let events = smol::spawn(async move {
+            while let Ok((stream, _)) = events.accept().await {
+                events_state.lock().unwrap().event_streams.push(stream);
+            }
+        });
+        Self {
+            dir,
+            state,
+            _server: server,
+            _events: events,
+        }
+    }
+
+    async fn serve(mut stream: UnixStream, state: &Mutex<HyprState>) {
+        // Like Hyprland, read a single request and close the connection after replying.
+        let mut buffer = [0u8; 1024];
+        let length = stream.read(&mut buffer).await.unwrap();
+        let request = String::from_utf8_lossy(&buffer[..length]).to_string();
+
+        let mut events = None;
+        let reply = match request.as_str() {
+            "j/workspaces" => json!([
+                {"id": 1, "name": "1", "monitor": "DP-1", "windows": 2},
+                {"id": 3, "name": "3", "monitor": "DP-1", "windows": 0},
+                {"id": 2, "name": "2", "monitor": "HDMI-A-1", "windows": 1},
+                {"id": -98, "name": "special:scratch", "monitor": "DP-1", "windows": 1},
+            ])
+            .to_string(),
+            "j/monitors" => json!([
+                {"name": "DP-1", "focused": true, "activeWorkspace": {"id": state.lock().unwrap().active, "name": "1"}},
+                {"name": "HDMI-A-1", "focused": false, "activeWorkspace": {"id": 2, "name": "2"}},
+            ])
+            .to_string(),
+            "j/clients" => json!([
+                {"address": "0x1a", "title": "~", "class": "foot", "workspace": {"id": 1, "name": "1"}},
+                {"address": "0x2b", "title": "Untitled", "class": "", "workspace": {"id": 1, "name": "1"},
+                 "fullscreen": false},
+                {"address": "0x3c", "title": "Mozilla Firefox", "class": "firefox", "workspace": {"id": 2, "name": "2"},
+                 "fullscreen": 2},
+                {"address": "0x4d", "title": "Passwords", "class": "keepassxc",
+                 "workspace": {"id": -98, "name": "special:scratch"}},
+            ])
+            .to_string(),
+            "j/activewindow" => json!({"address": "0x1a", "title": "~"}).to_string(),
+            "j/devices" => json!({
+                "mice": [],
+                "keyboards": [
+                    {"name": "power-button", "layout": "us", "active_keymap": "English (US)", "main": false},
+                    {"name": "keyboard", "layout": "us,de", "active_keymap": "German", "main": true,
+                     "active_layout_index": 1},
+                ],
+            })
+            .to_string(),
+            "dispatch closewindow address:0x63" => "Window not found".to_string(),
+            command => {
+                let mut state = state.lock().unwrap();
+                state.commands.push(command.to_string());
+                if let Some(id) = command.strip_prefix("dispatch workspace ") {
+                    state.active = id.parse().unwrap_or(state.active);
+                    events = Some(state.event_streams.clone());
+                }
+                "ok".to_string()
+            }
+        };
+        stream.write_all(reply.as_bytes()).await.unwrap();
+        drop(stream);
+
+        for mut stream in events.unwrap_or_default() {
+            let event = format!("workspace>>{0}\nworkspacev2>>{0},{0}\n", state.lock().unwrap().active);
+            let _ = stream.write_all(event.as_bytes()).await;
+        }
+    }
+
+    fn commands(&self) -> Vec<String> {
+        self.state.lock().unwrap().commands.clone()
+    }
+}
+
+#[test]
+fn test_hyprland_parse_address() {
+    assert_eq!(parse_address("0x55d0c8b2a5e0"), Some(0x55d0c8b2a5e0));
+    assert_eq!(parse_address("1a"), Some(0x1a));
+    assert_eq!(parse_address("0xzz"), None);
+}
+
+#[test]
+fn test_hyprland_state() {
+    smol::block_on(async {
+        let fake = FakeHyprland::start();
+        let hyprland = HyprlandCompositor::new(&fake.dir);
+
+        let workspaces = hyprland.workspaces().await.unwrap();
+        let summary: Vec<(u64, u8, String, bool, bool, usize)> = workspaces
+            .iter()
+            .map(|w| (w.id, w.idx, w.label(), w.is_active, w.is_focused, w.windows))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, 1, "1".to_string(), true, true, 2),
+                (3, 2, "3".to_string(), false, false, 0),
+                (2, 1, "2".to_string(), true, false, 1),
+            ]
+        );
+
+        let windows = hyprland.windows().await.unwrap();
+        let summary: Vec<(u64, Option<&str>, Option<u64>, bool, bool)> = windows
+            .iter()
+            .map(|w| (w.id, w.app_id.as_deref(), w.workspace_id, w.is_focused, w.is_fullscreen))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0x1a, Some("foot"), Some(1), true, false),
+                (0x2b, None, Some(1), false, false),
+                (0x3c, Some("firefox"), Some(2), false, true),
+                (0x4d, Some("keepassxc"), None, false, false),
+            ]
+        );
+
+        let layouts = hyprland.keyboard_layouts().await.unwrap();
+        assert_eq!(layouts.names, vec!["us", "de"]);
+        assert_eq!(layouts.current, 1);
+    });
+}
+
+#[test]
+fn test_hyprland_commands() {
+    smol::block_on(async {
+        let fake = FakeHyprland::start();
+        let hyprland = HyprlandCompositor::new(&fake.dir);
+
+        hyprland.execute(CompositorCommand::FocusWindow { id: 0x3c }).await.unwrap();
+        hyprland
+            .execute(CompositorCommand::MoveWindowToWorkspace { window: 0x1a, workspace: 3 })
+            .await
+            .unwrap();
+        hyprland.execute(CompositorCommand::FocusWorkspaceUp).await.unwrap();
+        hyprland.execute(CompositorCommand::SwitchLayoutNext).await.unwrap();
+        assert_eq!(
+            fake.commands(),
+            vec![
+                "dispatch focuswindow address:0x3c",
+                "dispatch movetoworkspacesilent 3,address:0x1a",
+                "dispatch workspace m-1",
+                "switchxkblayout all next",
+            ]
+        );
+
+        assert!(matches!(
+            hyprland.execute(CompositorCommand::CloseWindow { id: 99 }).await,
+            Err(CompositorError::Rejected(message)) if message == "Window not found"
+        ));
+    });
+}
+
+#[test]
+fn test_hyprland_listen() {
+    smol::block_on(async {
+        let fake = FakeHyprland::start();
+        let hyprland = Arc::new(HyprlandCompositor::new(&fake.dir));
+        let events = EventBus::new();
+        let subscription = events.subscribe(vec![UIUpdateEventType::WorkspaceChanged], SubscribeOptions::new());
+
+        let listener = hyprland.clone();
+        let _listen = smol::spawn(async move {
+            let mut publisher = StatePublisher::new("Hyprland", events);
+            listener.listen(&mut publisher).await
+        });
+
+        let focused = |event: Option<UIUpdateEvent>| match event {
+            Some(UIUpdateEvent::WorkspaceChanged { workspaces }) => {
+                workspaces.into_iter().find(|w| w.is_focused).map(|w| w.id)
+            }
+            event => panic!("Unexpected event {:?}", event),
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(focused(subscription.recv_until(deadline).await), Some(1));
+
+        hyprland.execute(CompositorCommand::FocusWorkspace { id: 3 }).await.unwrap();
+        assert_eq!(focused(subscription.recv_until(deadline).await), Some(3));
+        assert_eq!(fake.commands(), vec!["dispatch workspace 3"]);
+    });
+}
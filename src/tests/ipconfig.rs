@@ -0,0 +1,64 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use zbus::zvariant::Value;
+
+use crate::service::network::ipconfig::{IpAddressEntry, IpConfig, IpConfigError, IpMethod};
+
+#[test]
+fn test_parse_address_entry() {
+    let entry: IpAddressEntry<Ipv4Addr> = "192.168.1.10/24".parse().unwrap();
+    assert_eq!(entry.address, Ipv4Addr::new(192, 168, 1, 10));
+    assert_eq!(entry.prefix, 24);
+
+    assert!(matches!("192.168.1.10".parse::<IpAddressEntry<Ipv4Addr>>(), Err(IpConfigError::InvalidAddress(_))));
+    assert!(matches!("192.168.1.10/33".parse::<IpAddressEntry<Ipv4Addr>>(), Err(IpConfigError::InvalidPrefix(_))));
+    assert!("fd00::10/64".parse::<IpAddressEntry<Ipv6Addr>>().is_ok());
+    assert!("fd00::10/24".parse::<IpAddressEntry<Ipv4Addr>>().is_err());
+}
+
+#[test]
+fn test_manual_ipv4_settings() {
+    let config = IpConfig::<Ipv4Addr> {
+        method: IpMethod::Manual,
+        addresses: vec!["10.0.0.2/8".parse().unwrap()],
+        gateway: Some(Ipv4Addr::new(10, 0, 0, 1)),
+        dns: vec![Ipv4Addr::new(1, 1, 1, 1)],
+        ignore_auto_dns: false,
+        route_metric: Some(600),
+    };
+    assert!(config.validate().is_ok());
+
+    let section = config.to_settings();
+    assert_eq!(section.get("method"), Some(&Value::from("manual")));
+    assert_eq!(section.get("gateway"), Some(&Value::from("10.0.0.1")));
+    assert_eq!(section.get("route-metric"), Some(&Value::from(600i64)));
+    assert_eq!(section["address-data"].value_signature().as_str(), "aa{sv}");
+    // IPv4 DNS servers are sent as `au` in network byte order.
+    assert_eq!(section.get("dns"), Some(&Value::from(vec![u32::from_ne_bytes([1, 1, 1, 1])])));
+    assert!(!section.contains_key("ignore-auto-dns"));
+}
+
+#[test]
+fn test_auto_ipv6_settings() {
+    let config = IpConfig::<Ipv6Addr> {
+        dns: vec!["2606:4700:4700::1111".parse().unwrap()],
+        ignore_auto_dns: true,
+        ..Default::default()
+    };
+
+    let section = config.to_settings();
+    assert_eq!(section.get("method"), Some(&Value::from("auto")));
+    assert_eq!(section.get("ignore-auto-dns"), Some(&Value::from(true)));
+    assert_eq!(section["dns"].value_signature().as_str(), "aay");
+    assert!(!section.contains_key("address-data"));
+    assert!(!section.contains_key("gateway"));
+}
+
+#[test]
+fn test_manual_requires_address() {
+    let config = IpConfig::<Ipv4Addr> {
+        method: IpMethod::Manual,
+        ..Default::default()
+    };
+    assert_eq!(config.validate(), Err(IpConfigError::MissingAddress));
+}
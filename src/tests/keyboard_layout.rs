@@ -0,0 +1,18 @@
+use crate::{service::event::KeyboardLayouts, utils::keyboard_layout::short_names};
+
+#[test]
+fn test_keyboard_layout_short_names() {
+    let layouts = KeyboardLayouts {
+        names: vec![
+            "English (US)".to_string(),
+            "German".to_string(),
+            "English (UK)".to_string(),
+            "Esperanto".to_string(),
+            "Spanish".to_string(),
+        ],
+        current: 1,
+    };
+    assert_eq!(short_names(&layouts.names), ["EN1", "DE", "EN2", "EO", "ES"]);
+    assert_eq!(layouts.current_name(), Some("German"));
+    assert_eq!(KeyboardLayouts::default().current_name(), None);
+}
@@ -0,0 +1,30 @@
+use crate::service::network::metered::{MeteredState, NetworkMetered};
+
+#[test]
+fn test_network_metered_from_nm_values() {
+    assert_eq!(NetworkMetered::try_from(0), Ok(NetworkMetered::Unknown));
+    assert_eq!(NetworkMetered::try_from(3), Ok(NetworkMetered::GuessYes));
+    assert!(NetworkMetered::try_from(5).is_err());
+
+    assert!(NetworkMetered::Yes.is_metered());
+    assert!(NetworkMetered::GuessYes.is_metered());
+    assert!(!NetworkMetered::No.is_metered());
+    assert!(!NetworkMetered::GuessNo.is_metered());
+    assert!(!NetworkMetered::Unknown.is_metered());
+}
+
+#[test]
+fn test_metered_state_follows_device() {
+    // A profile left on automatic still reports what NetworkManager guessed for the device.
+    let guessed = MeteredState {
+        device: NetworkMetered::GuessYes,
+        profile: NetworkMetered::Unknown,
+    };
+    assert!(guessed.is_metered());
+
+    let overridden = MeteredState {
+        device: NetworkMetered::No,
+        profile: NetworkMetered::No,
+    };
+    assert!(!overridden.is_metered());
+}
@@ -1 +1,24 @@
-mod wifi;
\ No newline at end of file
+mod wifi;
+mod ipconfig;
+mod wifi_uri;
+mod wireless_band;
+mod rfkill;
+mod private_bus;
+mod modem;
+mod metered;
+mod ethernet;
+mod snapshot;
+mod event_bus;
+mod niri_workspaces;
+mod niri_windows;
+mod desktop_entry;
+mod keyboard_layout;
+mod scroll;
+mod niri_client;
+mod compositor;
+mod sway;
+mod hyprland;
+mod visibility;
+mod control;
+mod config;
+mod bar_dbus;
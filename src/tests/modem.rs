@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use smol_timeout::TimeoutExt;
+use zbus::{interface, zvariant::OwnedObjectPath};
+
+use crate::service::network::modem::{
+    AccessTechnology, ModemState, ModemStatus, read_modem_status, watch_modem_status,
+};
+
+use super::private_bus::PrivateBus;
+
+const MODEM_MANAGER: &str = "org.freedesktop.ModemManager1";
+const MODEM_PATH: &str = "/org/freedesktop/ModemManager1/Modem/0";
+
+struct FakeModem {
+    state: i32,
+    signal_quality: u32,
+    access_technologies: u32,
+}
+
+#[interface(name = "org.freedesktop.ModemManager1.Modem")]
+impl FakeModem {
+    #[zbus(property)]
+    fn state(&self) -> i32 {
+        self.state
+    }
+
+    #[zbus(property)]
+    fn signal_quality(&self) -> (u32, bool) {
+        (self.signal_quality, true)
+    }
+
+    #[zbus(property)]
+    fn access_technologies(&self) -> u32 {
+        self.access_technologies
+    }
+}
+
+struct FakeModem3gpp {
+    operator_name: String,
+}
+
+#[interface(name = "org.freedesktop.ModemManager1.Modem.Modem3gpp")]
+impl FakeModem3gpp {
+    #[zbus(property)]
+    fn operator_name(&self) -> String {
+        self.operator_name.clone()
+    }
+}
+
+#[test]
+fn test_access_technology_from_flags() {
+    assert_eq!(AccessTechnology::from_flags(0), AccessTechnology::Unknown);
+    // EDGE
+    assert_eq!(AccessTechnology::from_flags(1 << 4), AccessTechnology::Gen2G);
+    // HSPA+
+    assert_eq!(AccessTechnology::from_flags(1 << 9), AccessTechnology::Gen3G);
+    // LTE
+    assert_eq!(AccessTechnology::from_flags(1 << 14), AccessTechnology::Lte);
+    // 5G NSA reports LTE and NR together.
+    assert_eq!(AccessTechnology::from_flags(1 << 14 | 1 << 15), AccessTechnology::Gen5G);
+}
+
+#[test]
+fn test_modem_status_from_fake_modem_manager() {
+    let bus = PrivateBus::start().expect("This test needs dbus-daemon to run a private bus");
+
+    smol::block_on(async {
+        let server = bus
+            .builder()
+            .name(MODEM_MANAGER)
+            .unwrap()
+            .serve_at(
+                MODEM_PATH,
+                FakeModem {
+                    state: 8,
+                    signal_quality: 42,
+                    access_technologies: 1 << 14,
+                },
+            )
+            .unwrap()
+            .serve_at(
+                MODEM_PATH,
+                FakeModem3gpp {
+                    operator_name: "Molyuu Mobile".to_string(),
+                },
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = bus.connect().await;
+        let path = OwnedObjectPath::try_from(MODEM_PATH).unwrap();
+
+        let expected = ModemStatus {
+            state: ModemState::Registered,
+            signal_quality: 42,
+            access_technology: AccessTechnology::Lte,
+            operator_name: Some("Molyuu Mobile".to_string()),
+        };
+        assert_eq!(read_modem_status(&client, path.clone()).await.unwrap(), expected);
+
+        let (tx, rx) = smol::channel::unbounded();
+        let watcher = smol::spawn(async move { watch_modem_status(&client, path, tx).await });
+        assert_eq!(rx.recv().await.unwrap(), expected);
+
+        let modem = server
+            .object_server()
+            .interface::<_, FakeModem>(MODEM_PATH)
+            .await
+            .unwrap();
+        {
+            let mut fake = modem.get_mut().await;
+            fake.state = 11;
+            fake.access_technologies = 1 << 14 | 1 << 15;
+        }
+        let fake = modem.get().await;
+        fake.state_changed(modem.signal_context()).await.unwrap();
+        fake.access_technologies_changed(modem.signal_context()).await.unwrap();
+        drop(fake);
+
+        let mut status = expected.clone();
+        while status.state != ModemState::Connected || status.access_technology != AccessTechnology::Gen5G {
+            status = rx
+                .recv()
+                .timeout(Duration::from_secs(5))
+                .await
+                .expect("Timed out waiting for a status update")
+                .unwrap();
+        }
+        assert_eq!(status.signal_quality, 42);
+        assert_eq!(status.operator_name.as_deref(), Some("Molyuu Mobile"));
+
+        drop(rx);
+        drop(watcher);
+    });
+}
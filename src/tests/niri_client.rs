@@ -0,0 +1,110 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use niri_ipc::{Action, Reply, Request, Response};
+use smol::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::unix::{UnixListener, UnixStream},
+};
+
+use crate::service::compositor::niri::client::{NiriClient, NiriError};
+
+/// A fake niri command socket that, like niri, answers one request per connection
+/// and then hangs up.
+struct FakeNiri {
+    path: PathBuf,
+    connections: Arc<AtomicUsize>,
+    _server: smol::Task<()>,
+}
+
+impl FakeNiri {
+    fn start() -> Self {
+        let dir = std::env::temp_dir().join(format!("molyuu-bar-niri-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("niri.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let counter = connections.clone();
+        let server = smol::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                // Counted before the reply is sent, so clients see every connection.
+                counter.fetch_add(1, Ordering::SeqCst);
+                smol::spawn(Self::serve(stream)).detach();
+            }
+        });
+        Self {
+            path,
+            connections,
+            _server: server,
+        }
+    }
+
+    async fn serve(stream: UnixStream) {
+        let mut reader = BufReader::new(stream.clone());
+        let mut writer = stream;
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+            return;
+        }
+        let reply: Reply = match serde_json::from_str::<Request>(&line).unwrap() {
+            Request::Workspaces => Ok(Response::Workspaces(Vec::new())),
+            Request::FocusedWindow => Ok(Response::FocusedWindow(None)),
+            Request::Outputs => Ok(Response::Handled),
+            Request::Action(_) => Err("Workspace not found".to_string()),
+            _ => Err("Unsupported".to_string()),
+        };
+        let mut reply = serde_json::to_string(&reply).unwrap();
+        reply.push('\n');
+        writer.write_all(reply.as_bytes()).await.unwrap();
+    }
+}
+
+#[test]
+fn test_niri_client_typed_replies() {
+    smol::block_on(async {
+        let niri = FakeNiri::start();
+        let client = NiriClient::new(&niri.path);
+
+        assert!(client.workspaces().await.unwrap().is_empty());
+        assert!(matches!(
+            client.action(Action::FocusWorkspaceDown {}).await,
+            Err(NiriError::Niri(message)) if message == "Workspace not found"
+        ));
+        assert!(matches!(
+            client.outputs().await,
+            Err(NiriError::UnexpectedResponse(response)) if matches!(*response, Response::Handled)
+        ));
+
+        // Concurrent requests get their own replies.
+        let (workspaces, focused) = smol::future::zip(client.workspaces(), client.focused_window()).await;
+        assert!(workspaces.unwrap().is_empty());
+        assert!(focused.unwrap().is_none());
+    });
+}
+
+#[test]
+fn test_niri_client_connection_per_request() {
+    smol::block_on(async {
+        let niri = FakeNiri::start();
+        let client = NiriClient::new(&niri.path);
+
+        for _ in 0..5 {
+            assert!(client.workspaces().await.unwrap().is_empty());
+        }
+        assert_eq!(niri.connections.load(Ordering::SeqCst), 5);
+    });
+}
+
+#[test]
+fn test_niri_client_without_socket() {
+    smol::block_on(async {
+        let client = NiriClient::new("/nonexistent/niri.sock");
+        assert!(matches!(client.workspaces().await, Err(NiriError::Io(_))));
+    });
+}
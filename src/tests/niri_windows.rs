@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use niri_ipc::Window;
+
+use crate::service::{
+    compositor::{diff_windows, niri::NiriWindows},
+    event::{UIUpdateEvent, UIUpdateEventType, WindowInfo},
+};
+
+fn window(id: u64, title: &str, workspace_id: u64) -> WindowInfo {
+    WindowInfo {
+        id,
+        title: Some(title.to_string()),
+        app_id: Some("foot".to_string()),
+        workspace_id: Some(workspace_id),
+        is_focused: false,
+        is_fullscreen: false,
+    }
+}
+
+fn windows(list: &[WindowInfo]) -> HashMap<u64, WindowInfo> {
+    list.iter().map(|w| (w.id, w.clone())).collect()
+}
+
+#[test]
+fn test_diff_windows() {
+    let before = windows(&[window(1, "a", 1), window(2, "b", 1), window(3, "c", 2)]);
+    let mut focused = window(3, "c", 2);
+    focused.is_focused = true;
+    let after = windows(&[window(4, "d", 1), window(2, "b", 2), focused, window(1, "a", 1)]);
+
+    let changes: Vec<(UIUpdateEventType, u64)> = diff_windows(&before, &after)
+        .into_iter()
+        .map(|(event_type, event)| match event {
+            UIUpdateEvent::WindowOpened { window } | UIUpdateEvent::WindowChanged { window } => {
+                (event_type, window.id)
+            }
+            UIUpdateEvent::WindowClosed { id } => (event_type, id),
+            event => panic!("Unexpected event {:?}", event),
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (UIUpdateEventType::WindowChanged, 2),
+            (UIUpdateEventType::WindowChanged, 3),
+            (UIUpdateEventType::WindowOpened, 4),
+        ]
+    );
+
+    let changes = diff_windows(&after, &windows(&[window(4, "d", 1)]));
+    let closed: Vec<u64> = changes
+        .iter()
+        .map(|(_, event)| match event {
+            UIUpdateEvent::WindowClosed { id } => *id,
+            event => panic!("Unexpected event {:?}", event),
+        })
+        .collect();
+    assert_eq!(closed, [1, 2, 3]);
+    assert!(diff_windows(&before, &before).is_empty());
+}
+
+#[test]
+fn test_niri_does_not_report_fullscreen() {
+    let mut niri = NiriWindows::new();
+    niri.update_all(vec![Window {
+        id: 1,
+        title: Some("mpv".to_string()),
+        app_id: Some("mpv".to_string()),
+        pid: None,
+        workspace_id: Some(1),
+        is_focused: true,
+        is_floating: false,
+    }]);
+    // The bar relies on the layer order instead, see `AutoHideMode::Fullscreen`.
+    assert!(!niri.infos()[&1].is_fullscreen);
+}
@@ -0,0 +1,81 @@
+use niri_ipc::{Window, Workspace};
+
+use crate::service::compositor::niri::{NiriWindows, NiriWorkspaces};
+
+fn window(id: u64, workspace_id: Option<u64>) -> Window {
+    Window {
+        id,
+        title: None,
+        app_id: None,
+        pid: None,
+        workspace_id,
+        is_focused: false,
+        is_floating: false,
+    }
+}
+
+fn workspace(id: u64, idx: u8, output: &str, is_focused: bool) -> Workspace {
+    Workspace {
+        id,
+        idx,
+        name: None,
+        output: Some(output.to_string()),
+        is_active: is_focused,
+        is_focused,
+        active_window_id: None,
+    }
+}
+
+#[test]
+fn test_workspaces_listed_by_output_and_index() {
+    let windows = NiriWindows::new();
+    let mut workspaces = NiriWorkspaces::new();
+    workspaces.update_all(vec![
+        workspace(7, 2, "DP-1", false),
+        workspace(3, 1, "HDMI-A-1", false),
+        workspace(5, 1, "DP-1", true),
+    ]);
+
+    let listed: Vec<(u64, bool)> = workspaces.list(&windows).iter().map(|w| (w.id, w.is_focused)).collect();
+    assert_eq!(listed, [(5, true), (7, false), (3, false)]);
+
+    // Workspaces on different outputs share indices, so focus follows the id.
+    workspaces.set_focused(3);
+    let focused: Vec<u64> = workspaces.list(&windows).iter().filter(|w| w.is_focused).map(|w| w.id).collect();
+    assert_eq!(focused, [3]);
+    assert_eq!(workspaces.get_focused().map(|w| w.id), Some(3));
+}
+
+#[test]
+fn test_workspace_window_counts_and_labels() {
+    let mut windows = NiriWindows::new();
+    windows.update_all(vec![window(1, Some(5)), window(2, Some(5)), window(3, None)]);
+    let mut workspaces = NiriWorkspaces::new();
+    let mut named = workspace(7, 2, "DP-1", false);
+    named.name = Some("chat".to_string());
+    workspaces.update_all(vec![workspace(5, 1, "DP-1", true), named]);
+
+    let listed: Vec<(String, usize)> = workspaces.list(&windows).iter().map(|w| (w.label(), w.windows)).collect();
+    assert_eq!(listed, [("1".to_string(), 2), ("chat".to_string(), 0)]);
+
+    // A complete window list replaces the previous one.
+    windows.update_all(vec![window(4, Some(7))]);
+    let counts: Vec<usize> = workspaces.list(&windows).iter().map(|w| w.windows).collect();
+    assert_eq!(counts, [0, 1]);
+}
+
+#[test]
+fn test_workspace_activation_per_output() {
+    let windows = NiriWindows::new();
+    let mut workspaces = NiriWorkspaces::new();
+    workspaces.update_all(vec![
+        workspace(1, 1, "DP-1", true),
+        workspace(2, 2, "DP-1", false),
+        workspace(3, 1, "HDMI-A-1", false),
+    ]);
+    workspaces.set_active(3);
+    workspaces.set_active(2);
+
+    let active: Vec<(u64, bool)> = workspaces.list(&windows).iter().map(|w| (w.id, w.is_active)).collect();
+    assert_eq!(active, [(1, false), (2, true), (3, true)]);
+}
@@ -0,0 +1,49 @@
+use std::{
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+};
+
+use zbus::{Connection, connection::Builder};
+
+/// A private `dbus-daemon --session` for tests, killed when dropped.
+pub struct PrivateBus {
+    daemon: Child,
+    address: String,
+}
+
+impl PrivateBus {
+    /// Starts the daemon. Returns `None` if `dbus-daemon` is not installed.
+    pub fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+
+    /// Returns a builder for a new connection to the bus.
+    pub fn builder(&self) -> Builder<'_> {
+        Builder::address(self.address.as_str()).expect("Invalid bus address")
+    }
+
+    /// Opens a new connection to the bus.
+    pub async fn connect(&self) -> Connection {
+        self.builder().build().await.expect("Failed to connect to the private bus")
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
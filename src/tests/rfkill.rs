@@ -0,0 +1,130 @@
+use std::{io::Write, os::unix::net::UnixStream};
+
+use futures_lite::future;
+use smol::Async;
+
+use crate::service::{
+    event::{EventListener, SubscribeOptions},
+    rfkill::{
+        RfkillService,
+        device::{AirplaneModeState, RadioState, RadioType, RfkillEvent, RfkillOperation, RfkillReader, RfkillState},
+        event::{RfkillServiceEvent, RfkillServiceEventType, RfkillServiceRequest},
+    },
+};
+
+fn event(idx: u32, radio: RadioType, op: RfkillOperation, soft: bool, hard: bool) -> RfkillEvent {
+    RfkillEvent { idx, radio, op, soft, hard }
+}
+
+#[test]
+fn test_rfkill_event_encoding() {
+    let wlan = event(3, RadioType::Wlan, RfkillOperation::Change, true, false);
+    let bytes = wlan.to_bytes();
+    assert_eq!(&bytes[4..], &[1, 2, 1, 0]);
+    assert_eq!(RfkillEvent::from_bytes(&bytes), Some(wlan));
+
+    // Unknown radio types are rejected.
+    let mut unknown = bytes;
+    unknown[4] = 42;
+    assert_eq!(RfkillEvent::from_bytes(&unknown), None);
+
+    assert_eq!(
+        RfkillEvent::change_all(RadioType::Bluetooth, false).to_bytes(),
+        [0, 0, 0, 0, 2, 3, 0, 0]
+    );
+}
+
+#[test]
+fn test_rfkill_state() {
+    let mut state = RfkillState::new();
+    assert_eq!(state.airplane_mode(), AirplaneModeState::default());
+
+    assert_eq!(state.apply(&event(0, RadioType::Wlan, RfkillOperation::Add, false, false)), Some(RadioType::Wlan));
+    assert_eq!(state.apply(&event(1, RadioType::Bluetooth, RfkillOperation::Add, true, false)), Some(RadioType::Bluetooth));
+    // Repeated state is not a change.
+    assert_eq!(state.apply(&event(1, RadioType::Bluetooth, RfkillOperation::Change, true, false)), None);
+    assert!(!state.airplane_mode().enabled);
+
+    state.apply(&event(0, RadioType::Wlan, RfkillOperation::Change, false, true));
+    assert_eq!(
+        state.radio_state(RadioType::Wlan),
+        RadioState { present: true, blocked: true, hard_blocked: true }
+    );
+    assert_eq!(state.airplane_mode(), AirplaneModeState { enabled: true, hard_blocked: true });
+
+    assert_eq!(state.apply(&event(0, RadioType::Wlan, RfkillOperation::Del, false, false)), Some(RadioType::Wlan));
+    assert_eq!(state.radio_state(RadioType::Wlan), RadioState::default());
+    assert_eq!(state.airplane_mode(), AirplaneModeState { enabled: true, hard_blocked: false });
+}
+
+#[test]
+fn test_rfkill_service_with_fake_device() {
+    smol::block_on(async {
+        let (mut kernel, ours) = UnixStream::pair().unwrap();
+        let reader = RfkillReader::new(Async::new(ours).unwrap());
+        let mut written: Vec<u8> = Vec::new();
+
+        let mut service = RfkillService::new();
+        let rx = service.subscribe(
+            vec![RfkillServiceEventType::RadioStateChanged, RfkillServiceEventType::AirplaneModeChanged],
+            SubscribeOptions::new(),
+        );
+        let Some(RfkillServiceEvent::HandlerRegistered { command_sender }) = rx.recv().await else {
+            panic!("Expected HandlerRegistered");
+        };
+
+        let device = async {
+            // Devices reported on open, the second one split across two reads.
+            kernel.write_all(&event(0, RadioType::Wlan, RfkillOperation::Add, false, false).to_bytes()).unwrap();
+            let bluetooth = event(1, RadioType::Bluetooth, RfkillOperation::Add, false, false).to_bytes();
+            kernel.write_all(&bluetooth[..3]).unwrap();
+            smol::Timer::after(std::time::Duration::from_millis(10)).await;
+            kernel.write_all(&bluetooth[3..]).unwrap();
+
+            for radio in [RadioType::Wlan, RadioType::Bluetooth] {
+                match rx.recv().await.unwrap() {
+                    RfkillServiceEvent::RadioStateChanged { radio: changed, state } => {
+                        assert_eq!(changed, radio);
+                        assert!(state.present && !state.blocked);
+                    }
+                    other => panic!("Unexpected event: {:?}", other),
+                }
+                if radio == RadioType::Wlan {
+                    assert!(matches!(
+                        rx.recv().await.unwrap(),
+                        RfkillServiceEvent::AirplaneModeChanged { state } if state == AirplaneModeState::default()
+                    ));
+                }
+            }
+
+            command_sender
+                .send(RfkillServiceRequest::SetAirplaneMode { enabled: true })
+                .await
+                .unwrap();
+            while !command_sender.is_empty() {
+                future::yield_now().await;
+            }
+
+            // The kernel answers with per-device changes, the hardware switch is on as well.
+            kernel.write_all(&event(0, RadioType::Wlan, RfkillOperation::Change, true, true).to_bytes()).unwrap();
+            kernel.write_all(&event(1, RadioType::Bluetooth, RfkillOperation::Change, true, false).to_bytes()).unwrap();
+            let mut airplane_mode = AirplaneModeState::default();
+            while !airplane_mode.enabled {
+                if let RfkillServiceEvent::AirplaneModeChanged { state } = rx.recv().await.unwrap() {
+                    airplane_mode = state;
+                }
+            }
+            assert!(airplane_mode.hard_blocked);
+
+            drop(kernel);
+        };
+
+        future::zip(service.run(reader, Some(&mut written)), device).await;
+
+        let expected: Vec<u8> = RadioType::AIRPLANE_MODE
+            .iter()
+            .flat_map(|radio| RfkillEvent::change_all(*radio, true).to_bytes())
+            .collect();
+        assert_eq!(written, expected);
+    });
+}
@@ -0,0 +1,79 @@
+use crate::utils::scroll::{ScrollAccumulator, ScrollOptions};
+
+#[test]
+fn test_scroll_wheel_steps() {
+    let mut scroll = ScrollAccumulator::new(ScrollOptions::default());
+    assert_eq!(scroll.push(1.0, false), 1);
+    assert_eq!(scroll.push(-1.0, false), -1);
+    assert_eq!(scroll.push(2.0, false), 2);
+    assert_eq!(scroll.push(0.0, false), 0);
+
+    // High-resolution wheels report fractions of a notch.
+    assert_eq!(scroll.push(0.5, false), 0);
+    assert_eq!(scroll.push(0.25, false), 0);
+    assert_eq!(scroll.push(0.5, false), 1);
+    assert_eq!(scroll.push(0.75, false), 1);
+    assert_eq!(scroll.push(-0.5, false), 0);
+    // Touchpad pixels do not add to partial notches.
+    assert_eq!(scroll.push(-30.0, true), 0);
+    assert_eq!(scroll.push(-0.5, false), 0);
+
+    let mut inverted = ScrollAccumulator::new(ScrollOptions {
+        invert: true,
+        ..ScrollOptions::default()
+    });
+    assert_eq!(inverted.push(1.0, false), -1);
+}
+
+#[test]
+fn test_scroll_touchpad_threshold() {
+    let mut scroll = ScrollAccumulator::new(ScrollOptions {
+        invert: false,
+        threshold: 10.0,
+    });
+    assert_eq!(scroll.push(4.0, true), 0);
+    assert_eq!(scroll.push(4.0, true), 0);
+    assert_eq!(scroll.push(4.0, true), 1);
+    // 2 px are left over and count towards the next step.
+    assert_eq!(scroll.push(8.0, true), 1);
+    assert_eq!(scroll.push(25.0, true), 2);
+
+    // Turning around starts from zero.
+    assert_eq!(scroll.push(-6.0, true), 0);
+    assert_eq!(scroll.push(-6.0, true), -1);
+
+    scroll.push(-8.0, true);
+    scroll.reset();
+    assert_eq!(scroll.push(-4.0, true), 0);
+}
+
+#[test]
+fn test_scroll_options_from_str() {
+    assert_eq!("normal".parse(), Ok(ScrollOptions::default()));
+    assert_eq!(
+        " Inverted ".parse(),
+        Ok(ScrollOptions {
+            invert: true,
+            ..ScrollOptions::default()
+        })
+    );
+    assert!("sideways".parse::<ScrollOptions>().is_err());
+}
+
+#[test]
+fn test_scroll_set_options() {
+    let mut scroll = ScrollAccumulator::new(ScrollOptions::default());
+    assert_eq!(scroll.push(30.0, true), 0);
+    // The same options keep the partial step.
+    scroll.set_options(ScrollOptions::default());
+    assert_eq!(scroll.push(10.0, true), 1);
+
+    // New options, e.g. after a reload, start from zero.
+    assert_eq!(scroll.push(30.0, true), 0);
+    scroll.set_options(ScrollOptions {
+        invert: false,
+        threshold: 50.0,
+    });
+    assert_eq!(scroll.push(30.0, true), 0);
+    assert_eq!(scroll.push(20.0, true), 1);
+}
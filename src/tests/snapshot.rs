@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+
+use rusty_network_manager::dbus_interface_types::NMDeviceStateReason;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::service::network::{
+    NetworkServiceStorage,
+    endpoints::event::{NetworkDeviceState, NetworkDeviceType, NetworkServiceEvent},
+    wireless::ap::AccessPoint,
+};
+
+fn access_point(path: &str, signal_strength: u8) -> AccessPoint {
+    AccessPoint {
+        ssid: "Molyuu".to_string(),
+        flags: 0,
+        wpa_flags: 0,
+        rsn_flags: 0,
+        mode: 2,
+        bssid: "00:11:22:33:44:55".to_string(),
+        frequency: 2412,
+        signal_strength,
+        last_seen: 0,
+        dbus_path: OwnedObjectPath::try_from(path).unwrap(),
+    }
+}
+
+#[test]
+fn test_snapshot_from_storage() {
+    let mut storage = NetworkServiceStorage::new();
+    storage.register_interface(
+        "wlan0".to_string(),
+        "/org/freedesktop/NetworkManager/Devices/3".to_string(),
+        NetworkDeviceType::WiFi,
+        smol::spawn(async {}),
+    );
+    storage.register_interface(
+        "eth0".to_string(),
+        "/org/freedesktop/NetworkManager/Devices/2".to_string(),
+        NetworkDeviceType::Ethernet,
+        smol::spawn(async {}),
+    );
+
+    let ap = access_point("/org/freedesktop/NetworkManager/AccessPoint/1", 40);
+    let key = (ap.ssid.clone(), ap.key_management());
+    storage.refresh_access_points("wlan0".to_string(), HashMap::from([(key.clone(), vec![ap.clone()])]));
+    storage.refresh_ap_connections(HashSet::from([key.clone()]));
+
+    for event in [
+        NetworkServiceEvent::DeviceStateChanged {
+            interface: "wlan0".to_string(),
+            state: NetworkDeviceState::Activated,
+            reason: NMDeviceStateReason::UNKNOWN,
+        },
+        NetworkServiceEvent::ActiveAccessPointChanged {
+            interface: "wlan0".to_string(),
+            ap: ap.clone(),
+        },
+        NetworkServiceEvent::AccessPointUpdated {
+            interface: "wlan0".to_string(),
+            ap: access_point("/org/freedesktop/NetworkManager/AccessPoint/1", 80),
+        },
+        NetworkServiceEvent::GlobalWirelessEnabledStateChanged { enabled: true },
+        NetworkServiceEvent::PrimaryConnectionChanged {
+            interface: Some("wlan0".to_string()),
+            device_type: Some(NetworkDeviceType::WiFi),
+            connection_id: Some("Molyuu".to_string()),
+        },
+        // Unknown interfaces are not invented.
+        NetworkServiceEvent::DeviceStateChanged {
+            interface: "wlan1".to_string(),
+            state: NetworkDeviceState::Disconnected,
+            reason: NMDeviceStateReason::UNKNOWN,
+        },
+    ] {
+        storage.observe_event(&event);
+    }
+
+    let snapshot = storage.snapshot();
+    let interfaces: Vec<&str> = snapshot.devices.iter().map(|d| d.interface.as_str()).collect();
+    assert_eq!(interfaces, ["eth0", "wlan0"]);
+
+    let eth0 = &snapshot.devices[0];
+    assert_eq!(eth0.device_type, NetworkDeviceType::Ethernet);
+    assert_eq!(eth0.state, None);
+
+    let wlan0 = &snapshot.devices[1];
+    assert_eq!(wlan0.state, Some(NetworkDeviceState::Activated));
+    assert_eq!(wlan0.active_access_point.as_ref().map(|ap| ap.signal_strength), Some(80));
+    assert_eq!(wlan0.access_points.get(&key).map(Vec::len), Some(1));
+
+    assert_eq!(snapshot.wireless_enabled, Some(true));
+    assert_eq!(snapshot.primary_connection.connection_id.as_deref(), Some("Molyuu"));
+    assert_eq!(snapshot.saved_profiles.get(&key), Some(&true));
+
+    // Removing an interface drops everything recorded for it.
+    storage.unregister_interface_by_dbus_path("/org/freedesktop/NetworkManager/Devices/3");
+    assert_eq!(storage.snapshot().devices.len(), 1);
+}
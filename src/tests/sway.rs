@@ -0,0 +1,290 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+use smol::net::unix::{UnixListener, UnixStream};
+
+use crate::service::{
+    compositor::{
+        Compositor, CompositorCommand, CompositorError, StatePublisher,
+        sway::{self, SwayCompositor},
+    },
+    event::{EventBus, SubscribeOptions, UIUpdateEvent, UIUpdateEventType},
+};
+
+#[derive(Default)]
+struct SwayState {
+    /// Id of the focused workspace.
+    focused: u64,
+    commands: Vec<String>,
+    /// How often the tree was requested.
+    trees: usize,
+    subscribers: Vec<UnixStream>,
+}
+
+/// A fake sway IPC socket with three workspaces on two outputs.
+struct FakeSway {
+    path: PathBuf,
+    state: Arc<Mutex<SwayState>>,
+    _server: smol::Task<()>,
+}
+
+impl FakeSway {
+    fn start() -> Self {
+        let dir = std::env::temp_dir().join(format!("molyuu-bar-sway-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sway.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let state = Arc::new(Mutex::new(SwayState {
+            focused: 10,
+            ..Default::default()
+        }));
+
+        let server_state = state.clone();
+        let server = smol::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                smol::spawn(Self::serve(stream, server_state.clone())).detach();
+            }
+        });
+        Self {
+            path,
+            state,
+            _server: server,
+        }
+    }
+
+    fn workspaces(focused: u64) -> Value {
+        json!([
+            {"id": 10, "num": 1, "name": "1", "output": "DP-1", "visible": focused != 11, "focused": focused == 10},
+            {"id": 11, "num": 2, "name": "2", "output": "DP-1", "visible": focused == 11, "focused": focused == 11},
+            {"id": 12, "num": -1, "name": "web", "output": "HDMI-A-1", "visible": true, "focused": focused == 12},
+        ])
+    }
+
+    fn tree() -> Value {
+        json!({
+            "id": 1, "type": "root", "name": "root",
+            "nodes": [
+                {"id": 2, "type": "output", "name": "__i3", "nodes": [
+                    {"id": 3, "type": "workspace", "name": "__i3_scratch", "nodes": [], "floating_nodes": [
+                        {"id": 40, "type": "floating_con", "name": "Passwords", "app_id": "keepassxc", "nodes": []},
+                    ]},
+                ]},
+                {"id": 4, "type": "output", "name": "DP-1", "nodes": [
+                    {"id": 10, "type": "workspace", "name": "1", "nodes": [
+                        {"id": 20, "type": "con", "name": "~", "app_id": "foot", "focused": true, "nodes": []},
+                    ], "floating_nodes": [
+                        {"id": 21, "type": "floating_con", "name": "Steam", "app_id": null,
+                         "window_properties": {"class": "steam"}, "nodes": []},
+                    ]},
+                    {"id": 11, "type": "workspace", "name": "2", "nodes": []},
+                ]},
+                {"id": 5, "type": "output", "name": "HDMI-A-1", "nodes": [
+                    {"id": 12, "type": "workspace", "name": "web", "nodes": [
+                        {"id": 30, "type": "con", "name": null, "nodes": [
+                            {"id": 31, "type": "con", "name": "Mozilla Firefox", "app_id": "firefox",
+                             "fullscreen_mode": 1, "nodes": []},
+                        ]},
+                    ]},
+                ]},
+            ],
+        })
+    }
+
+    async fn serve(mut stream: UnixStream, state: Arc<Mutex<SwayState>>) {
+        while let Ok((kind, payload)) = sway::read_message(&mut stream).await {
+            let payload = String::from_utf8(payload).unwrap();
+            let mut notify = false;
+            let reply = match kind {
+                sway::RUN_COMMAND => {
+                    let mut state = state.lock().unwrap();
+                    state.commands.push(payload.clone());
+                    if payload.starts_with("[con_id=99]") {
+                        json!([{"success": false, "error": "No matching node."}])
+                    } else {
+                        if let Some(name) = payload.strip_prefix("workspace ") {
+                            state.focused = match name {
+                                "\"1\"" => 10,
+                                "\"2\"" => 11,
+                                _ => 12,
+                            };
+                            notify = true;
+                        }
+                        json!([{"success": true}])
+                    }
+                }
+                sway::GET_WORKSPACES => Self::workspaces(state.lock().unwrap().focused),
+                sway::SUBSCRIBE => {
+                    state.lock().unwrap().subscribers.push(stream.clone());
+                    json!({"success": true})
+                }
+                sway::GET_TREE => {
+                    state.lock().unwrap().trees += 1;
+                    Self::tree()
+                }
+                sway::GET_INPUTS => json!([
+                    {"type": "pointer", "identifier": "mouse"},
+                    {"type": "keyboard", "identifier": "kbd",
+                     "xkb_layout_names": ["English (US)", "German"], "xkb_active_layout_index": 1},
+                ]),
+                _ => json!({"success": false}),
+            };
+            let reply = serde_json::to_vec(&reply).unwrap();
+            sway::write_message(&mut stream, kind, &reply).await.unwrap();
+
+            if notify {
+                let subscribers = state.lock().unwrap().subscribers.clone();
+                for mut subscriber in subscribers {
+                    let event = br#"{"change": "focus"}"#;
+                    let _ = sway::write_message(&mut subscriber, sway::EVENT, event).await;
+                }
+            }
+        }
+    }
+
+    fn commands(&self) -> Vec<String> {
+        self.state.lock().unwrap().commands.clone()
+    }
+
+    fn trees(&self) -> usize {
+        self.state.lock().unwrap().trees
+    }
+}
+
+#[test]
+fn test_sway_state() {
+    smol::block_on(async {
+        let fake = FakeSway::start();
+        let sway = SwayCompositor::new(&fake.path);
+
+        let workspaces = sway.workspaces().await.unwrap();
+        let summary: Vec<(u64, u8, &str, bool, bool, usize)> = workspaces
+            .iter()
+            .map(|w| (w.id, w.idx, w.output.as_deref().unwrap(), w.is_active, w.is_focused, w.windows))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (10, 1, "DP-1", true, true, 2),
+                (11, 2, "DP-1", false, false, 0),
+                (12, 1, "HDMI-A-1", true, false, 1),
+            ]
+        );
+        assert_eq!(workspaces[2].label(), "web");
+
+        let windows = sway.windows().await.unwrap();
+        let summary: Vec<(u64, Option<&str>, Option<u64>, bool, bool)> = windows
+            .iter()
+            .map(|w| (w.id, w.app_id.as_deref(), w.workspace_id, w.is_focused, w.is_fullscreen))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (40, Some("keepassxc"), None, false, false),
+                (20, Some("foot"), Some(10), true, false),
+                (21, Some("steam"), Some(10), false, false),
+                (31, Some("firefox"), Some(12), false, true),
+            ]
+        );
+
+        let layouts = sway.keyboard_layouts().await.unwrap();
+        assert_eq!(layouts.names, vec!["English (US)", "German"]);
+        assert_eq!(layouts.current, 1);
+    });
+}
+
+#[test]
+fn test_sway_commands() {
+    smol::block_on(async {
+        let fake = FakeSway::start();
+        let sway = SwayCompositor::new(&fake.path);
+
+        sway.execute(CompositorCommand::FocusWorkspace { id: 12 }).await.unwrap();
+        sway.execute(CompositorCommand::MoveWindowToWorkspace { window: 20, workspace: 11 })
+            .await
+            .unwrap();
+        sway.execute(CompositorCommand::SwitchLayout { index: 1 }).await.unwrap();
+        sway.execute(CompositorCommand::FocusWorkspaceDown).await.unwrap();
+        assert_eq!(
+            fake.commands(),
+            vec![
+                "workspace \"web\"",
+                "[con_id=20] move container to workspace \"2\"",
+                "input type:keyboard xkb_switch_layout 1",
+                "workspace next_on_output",
+            ]
+        );
+
+        assert!(matches!(
+            sway.execute(CompositorCommand::CloseWindow { id: 99 }).await,
+            Err(CompositorError::Rejected(message)) if message == "No matching node."
+        ));
+        assert!(matches!(
+            sway.execute(CompositorCommand::FocusWorkspace { id: 999 }).await,
+            Err(CompositorError::Rejected(_))
+        ));
+    });
+}
+
+#[test]
+fn test_sway_listen() {
+    smol::block_on(async {
+        let fake = FakeSway::start();
+        let sway = Arc::new(SwayCompositor::new(&fake.path));
+        let events = EventBus::new();
+        let subscription = events.subscribe(vec![UIUpdateEventType::WorkspaceChanged], SubscribeOptions::new());
+
+        let listener = sway.clone();
+        let _listen = smol::spawn(async move {
+            let mut publisher = StatePublisher::new("Sway", events);
+            listener.listen(&mut publisher).await
+        });
+
+        let focused = |event: Option<UIUpdateEvent>| match event {
+            Some(UIUpdateEvent::WorkspaceChanged { workspaces }) => {
+                workspaces.into_iter().find(|w| w.is_focused).map(|w| w.id)
+            }
+            event => panic!("Unexpected event {:?}", event),
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(focused(subscription.recv_until(deadline).await), Some(10));
+
+        // Focusing a workspace makes sway send an event, which triggers a refresh.
+        sway.execute(CompositorCommand::FocusWorkspace { id: 11 }).await.unwrap();
+        assert_eq!(focused(subscription.recv_until(deadline).await), Some(11));
+    });
+}
+
+#[test]
+fn test_sway_refresh_fetches_tree_once() {
+    smol::block_on(async {
+        let fake = FakeSway::start();
+        let sway = SwayCompositor::new(&fake.path);
+        let events = EventBus::new();
+        let subscription = events.subscribe(
+            vec![UIUpdateEventType::WorkspaceChanged, UIUpdateEventType::WindowOpened],
+            SubscribeOptions::new(),
+        );
+        let mut publisher = StatePublisher::new("Sway", events);
+
+        sway.refresh(&mut publisher).await.unwrap();
+        assert_eq!(fake.trees(), 1);
+
+        // The window counts and the windows come from the same tree.
+        let mut opened = 0;
+        while let Some(event) = subscription.try_recv() {
+            match event {
+                UIUpdateEvent::WorkspaceChanged { workspaces } => {
+                    let counts: Vec<usize> = workspaces.iter().map(|w| w.windows).collect();
+                    assert_eq!(counts, vec![2, 0, 1]);
+                }
+                UIUpdateEvent::WindowOpened { .. } => opened += 1,
+                event => panic!("Unexpected event {:?}", event),
+            }
+        }
+        assert_eq!(opened, 4);
+    });
+}
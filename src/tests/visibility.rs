@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use crate::service::event::{WindowInfo, WorkspaceInfo};
+use crate::utils::visibility::{AutoHideMode, OutputFocus, Visibility};
+
+#[test]
+fn test_auto_hide_mode_parsing() {
+    assert_eq!("off".parse(), Ok(AutoHideMode::Off));
+    assert_eq!("Fullscreen".parse(), Ok(AutoHideMode::Fullscreen));
+    assert_eq!(" hover ".parse(), Ok(AutoHideMode::Always));
+    assert!("sometimes".parse::<AutoHideMode>().is_err());
+}
+
+#[test]
+fn test_visibility_modes() {
+    let mut off = Visibility::new(AutoHideMode::Off);
+    off.set_fullscreen(true);
+    assert!(off.is_visible());
+    assert!(off.reserves_space());
+    assert!(!off.uses_hot_zone());
+
+    let mut fullscreen = Visibility::new(AutoHideMode::Fullscreen);
+    assert!(fullscreen.is_visible());
+    fullscreen.set_fullscreen(true);
+    assert!(!fullscreen.is_visible());
+    // Touching the screen edge reveals the bar over the fullscreen window.
+    fullscreen.set_hovered(true);
+    assert!(fullscreen.is_visible());
+    fullscreen.set_hovered(false);
+    assert!(!fullscreen.is_visible());
+
+    let mut always = Visibility::new(AutoHideMode::Always);
+    assert!(!always.is_visible());
+    assert!(!always.reserves_space());
+    always.set_hovered(true);
+    assert!(always.is_visible());
+}
+
+#[test]
+fn test_visibility_toggle() {
+    let mut visibility = Visibility::new(AutoHideMode::Fullscreen);
+    assert!(!visibility.toggle());
+    assert!(!visibility.is_visible());
+    // Hidden by hand, hovering does not bring it back.
+    visibility.set_hovered(true);
+    assert!(!visibility.is_visible());
+    visibility.set_hovered(false);
+    assert!(visibility.toggle());
+
+    // Shown during fullscreen, the override ends once fullscreen does.
+    visibility.set_fullscreen(true);
+    assert!(visibility.toggle());
+    visibility.set_fullscreen(false);
+    assert!(visibility.is_visible());
+    visibility.set_fullscreen(true);
+    assert!(!visibility.is_visible());
+}
+
+#[test]
+fn test_visibility_set_mode() {
+    let mut visibility = Visibility::new(AutoHideMode::Off);
+    visibility.set_fullscreen(true);
+    assert!(!visibility.toggle());
+
+    // A reload starts over from the automatic state of the new mode.
+    visibility.set_mode(AutoHideMode::Fullscreen);
+    assert!(!visibility.is_visible());
+    assert!(visibility.uses_hot_zone());
+    visibility.set_fullscreen(false);
+    assert!(visibility.is_visible());
+
+    visibility.set_mode(AutoHideMode::Off);
+    visibility.set_fullscreen(true);
+    assert!(visibility.is_visible());
+    assert!(visibility.reserves_space());
+}
+
+fn workspace(id: u64, output: &str, is_active: bool, is_focused: bool) -> WorkspaceInfo {
+    WorkspaceInfo {
+        id,
+        idx: 1,
+        name: None,
+        output: Some(output.to_string()),
+        is_active,
+        is_focused,
+        windows: 1,
+    }
+}
+
+fn window(id: u64, workspace_id: u64, is_fullscreen: bool) -> WindowInfo {
+    WindowInfo {
+        id,
+        title: None,
+        app_id: None,
+        workspace_id: Some(workspace_id),
+        is_focused: false,
+        is_fullscreen,
+    }
+}
+
+#[test]
+fn test_fullscreen_on_output() {
+    let workspaces = [
+        workspace(1, "DP-1", true, true),
+        workspace(2, "DP-1", false, false),
+        workspace(3, "HDMI-A-1", true, false),
+    ];
+    let windows = |list: &[WindowInfo]| -> HashMap<u64, WindowInfo> {
+        list.iter().map(|window| (window.id, window.clone())).collect()
+    };
+    let focused = |mut window: WindowInfo| {
+        window.is_focused = true;
+        window
+    };
+
+    // A fullscreen window on a hidden workspace does not count.
+    let mut dp = OutputFocus::new();
+    let hidden = windows(&[focused(window(10, 1, false)), window(11, 2, true)]);
+    assert!(!dp.fullscreen_on_output(&workspaces, &hidden, Some("DP-1")));
+
+    // Neither does one that lost focus to another window on its workspace.
+    let behind = windows(&[window(10, 1, true), focused(window(13, 1, false))]);
+    assert!(!dp.fullscreen_on_output(&workspaces, &behind, Some("DP-1")));
+    let front = windows(&[focused(window(10, 1, true)), window(13, 1, false)]);
+    assert!(dp.fullscreen_on_output(&workspaces, &front, Some("DP-1")));
+
+    // Focusing the other output keeps the window focused on this one.
+    let mut hdmi = OutputFocus::new();
+    let elsewhere = windows(&[window(10, 1, true), focused(window(12, 3, false))]);
+    assert!(dp.fullscreen_on_output(&workspaces, &elsewhere, Some("DP-1")));
+    assert!(!hdmi.fullscreen_on_output(&workspaces, &elsewhere, Some("HDMI-A-1")));
+    // Nothing was ever focused on HDMI-A-1, so its fullscreen window does not count yet.
+    let unfocused = windows(&[focused(window(10, 1, false)), window(14, 3, true)]);
+    assert!(!OutputFocus::new().fullscreen_on_output(&workspaces, &unfocused, Some("HDMI-A-1")));
+
+    // Without a known output, the focused workspace counts.
+    let mut any = OutputFocus::new();
+    assert!(!any.fullscreen_on_output(&workspaces, &hidden, None));
+    assert!(any.fullscreen_on_output(&workspaces, &front, None));
+}
@@ -2,12 +2,11 @@ use std::time::Duration;
 
 use smol::Timer;
 
-use crate::service::{event::EventListener, network::{endpoints::event::{NetworkServiceEvent, NetworkServiceEventType, NetworkServiceRequest, WiFiConnServiceMessage, WiFiConnServiceRequest, WiFiConnServiceResponse}, wireless::ap::AccessPointSecurity, NetworkService}};
+use crate::service::{event::{EventListener, SubscribeOptions}, network::{endpoints::event::{NetworkServiceEvent, NetworkServiceEventType, NetworkServiceRequest, WiFiConnServiceMessage, WiFiConnServiceRequest, WiFiConnServiceResponse}, wireless::ap::AccessPointSecurity, NetworkService}};
 
 async fn test_wifi_async() {
-    let (sender, receiver) = smol::channel::unbounded::<NetworkServiceEvent>();
     let mut network_service = NetworkService::new();
-    network_service.register_event_handler(NetworkServiceEventType::AccessPointScanReport, sender.clone());
+    let subscription = network_service.subscribe(vec![NetworkServiceEventType::AccessPointScanReport], SubscribeOptions::new());
 
     smol::spawn(async move {
         network_service.listen().await;
@@ -16,7 +15,7 @@ async fn test_wifi_async() {
     // Wait for the network service to start
     Timer::after(Duration::from_secs(1)).await;
 
-    let command_sender = receiver.recv().await.unwrap();
+    let command_sender = subscription.recv().await.unwrap();
     match command_sender {
         NetworkServiceEvent::HandlerRegistered { command_sender } => {
             let (event_sender, event_receiver) = smol::channel::unbounded();
@@ -25,10 +24,10 @@ async fn test_wifi_async() {
             println!("Accept: {:?}", accept);
             assert!(matches!(accept, WiFiConnServiceMessage::Response(WiFiConnServiceResponse::ServerAcceptedConnection(_))));
             if let Some(WiFiConnServiceResponse::ServerAcceptedConnection(event_sender)) = accept.into_response() {
-                event_sender.send(WiFiConnServiceRequest::WiFiConnect { ssid: "Test".to_string(), key_mgmt: AccessPointSecurity::WPA }.into_message()).await.unwrap();
+                event_sender.send(WiFiConnServiceRequest::WiFiConnect { ssid: "Test".to_string(), key_mgmt: AccessPointSecurity::WPA, bssid: None, ip_settings: None }.into_message()).await.unwrap();
                 let event = event_receiver.recv().await.unwrap();
                 assert!(matches!(event, WiFiConnServiceMessage::Response(WiFiConnServiceResponse::AuthentiationRequired)));
-                event_sender.send(WiFiConnServiceRequest::ProvideAuthenticationInfo { psk: "test_wifi".to_string() }.into_message()).await.unwrap();
+                event_sender.send(WiFiConnServiceRequest::ProvideAuthenticationInfo { psk: "test_wifi".to_string(), ip_settings: None }.into_message()).await.unwrap();
                 
                 let event = event_receiver.recv().await.unwrap();
                 assert!(matches!(event, WiFiConnServiceMessage::Response(WiFiConnServiceResponse::RequestAcknowledged)));
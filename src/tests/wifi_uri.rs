@@ -0,0 +1,52 @@
+use crate::service::network::wireless::{
+    ap::AccessPointSecurity,
+    uri::{WiFiUri, WiFiUriError},
+};
+
+#[test]
+fn test_parse_wifi_uri() {
+    let uri: WiFiUri = "WIFI:T:WPA;S:office;P:hunter2;;".parse().unwrap();
+    assert_eq!(uri.ssid, "office");
+    assert_eq!(uri.security, AccessPointSecurity::WPA);
+    assert_eq!(uri.password.as_deref(), Some("hunter2"));
+    assert!(!uri.hidden);
+
+    // Field order does not matter and escapes are resolved.
+    let uri: WiFiUri = r#"WIFI:S:lab\;5G;H:true;P:a\:b\\c;T:SAE;;"#.parse().unwrap();
+    assert_eq!(uri.ssid, "lab;5G");
+    assert_eq!(uri.security, AccessPointSecurity::WPA3);
+    assert_eq!(uri.password.as_deref(), Some(r"a:b\c"));
+    assert!(uri.hidden);
+
+    let uri: WiFiUri = "WIFI:T:nopass;S:guest;;".parse().unwrap();
+    assert_eq!(uri.security, AccessPointSecurity::None);
+    assert_eq!(uri.password, None);
+
+    let uri: WiFiUri = r#"WIFI:T:WPA;S:"0123";P:"secret";;"#.parse().unwrap();
+    assert_eq!(uri.ssid, "0123");
+    assert_eq!(uri.password.as_deref(), Some("secret"));
+}
+
+#[test]
+fn test_parse_wifi_uri_errors() {
+    assert_eq!("T:WPA;S:x;;".parse::<WiFiUri>(), Err(WiFiUriError::MissingScheme));
+    assert_eq!("WIFI:T:WPA;P:x;;".parse::<WiFiUri>(), Err(WiFiUriError::MissingSsid));
+    assert_eq!("WIFI:T:WPA;S:x;;".parse::<WiFiUri>(), Err(WiFiUriError::MissingPassword));
+    assert!(matches!(
+        "WIFI:T:WEP;S:x;P:y;;".parse::<WiFiUri>(),
+        Err(WiFiUriError::UnsupportedSecurity(_))
+    ));
+}
+
+#[test]
+fn test_wifi_uri_round_trip() {
+    let uri = WiFiUri {
+        ssid: r#"caf"e;net"#.to_string(),
+        security: AccessPointSecurity::WPA,
+        password: Some("p,a:s;s".to_string()),
+        hidden: true,
+    };
+    let serialized = uri.to_string();
+    assert_eq!(serialized, r#"WIFI:T:WPA;S:caf\"e\;net;P:p\,a\:s\;s;H:true;;"#);
+    assert_eq!(serialized.parse::<WiFiUri>().unwrap(), uri);
+}
@@ -0,0 +1,19 @@
+use crate::service::network::wireless::ap::WirelessBand;
+
+#[test]
+fn test_wireless_channel() {
+    assert_eq!(WirelessBand::channel(2412), Some(1));
+    assert_eq!(WirelessBand::channel(2472), Some(13));
+    assert_eq!(WirelessBand::channel(2484), Some(14));
+    assert_eq!(WirelessBand::channel(5180), Some(36));
+    assert_eq!(WirelessBand::channel(5935), Some(2));
+    assert_eq!(WirelessBand::channel(5955), Some(1));
+    assert_eq!(WirelessBand::channel(7115), Some(233));
+
+    // Band edges below the first channel must not underflow.
+    for frequency in [2401, 2406, 5925, 5940, 5950] {
+        assert!(WirelessBand::from_frequency(frequency).is_some());
+        assert_eq!(WirelessBand::channel(frequency), None);
+    }
+    assert_eq!(WirelessBand::channel(3000), None);
+}
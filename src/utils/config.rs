@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use tracing::warn;
+
+use crate::utils::{scroll::ScrollOptions, visibility::AutoHideMode};
+use crate::widgets::window_list::WindowListScope;
+
+/// Selects the `AutoHideMode`: `off`, `fullscreen` or `always`.
+pub const AUTO_HIDE_VAR: &str = "MOLYUU_BAR_AUTO_HIDE";
+
+/// Selects the `WindowListScope`: `current-workspace` or `all-workspaces`.
+pub const WINDOW_LIST_VAR: &str = "MOLYUU_BAR_WINDOW_LIST";
+
+/// Selects the direction of scrolling over workspaces and windows: `normal` or `inverted`.
+pub const SCROLL_VAR: &str = "MOLYUU_BAR_SCROLL";
+
+/// Sets the options as `MOLYUU_BAR_*=value` lines, in the config directory.
+const CONFIG_FILE: &str = "config";
+
+/// Styles the bar on top of the built-in stylesheet, in the config directory.
+const STYLESHEET_FILE: &str = "style.css";
+
+/// The options of the bar.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BarOptions {
+    pub auto_hide: AutoHideMode,
+    pub window_list_scope: WindowListScope,
+    pub scroll: ScrollOptions,
+}
+
+impl BarOptions {
+    /// Reads the `MOLYUU_BAR_*` options from `var`, falling back to the default of
+    /// every option that is unset or invalid.
+    pub fn read(var: impl Fn(&str) -> Option<String>) -> Self {
+        Self {
+            auto_hide: option(&var, AUTO_HIDE_VAR),
+            window_list_scope: option(&var, WINDOW_LIST_VAR),
+            scroll: option(&var, SCROLL_VAR),
+        }
+    }
+}
+
+fn option<T: FromStr<Err = String> + Default>(var: &impl Fn(&str) -> Option<String>, name: &str) -> T {
+    let Some(value) = var(name) else {
+        return T::default();
+    };
+    value.parse().unwrap_or_else(|e| {
+        warn!("{}: {}", name, e);
+        T::default()
+    })
+}
+
+/// Parses the `KEY=value` lines of the config file, skipping blank lines and
+/// `#` comments.
+pub fn parse_config(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match line.split_once('=') {
+            Some((key, value)) => Some((key.trim().to_string(), value.trim().to_string())),
+            None => {
+                warn!("Ignoring config line without '=': {}", line);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Where the config file and the stylesheet live: `molyuu-bar` in
+/// `XDG_CONFIG_HOME`, falling back to `~/.config`.
+pub fn config_dir(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    let base = var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("molyuu-bar"))
+}
+
+/// Reads `name` in `dir`, `None` if there is no such file.
+fn read_file(dir: &Path, name: &str) -> io::Result<Option<String>> {
+    let path = dir.join(name);
+    match fs::read_to_string(&path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(io::Error::new(e.kind(), format!("{:?}: {}", path, e))),
+    }
+}
+
+/// The configuration of the bar, as read at startup and on reload.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub options: BarOptions,
+    /// The user stylesheet, empty without one.
+    pub stylesheet: String,
+}
+
+impl Config {
+    /// Reads the options and the stylesheet from `dir`. Options set in the
+    /// environment, looked up with `env`, take precedence over the config file.
+    pub fn load(dir: Option<&Path>, env: impl Fn(&str) -> Option<String>) -> io::Result<Self> {
+        let (file, stylesheet) = match dir {
+            Some(dir) => (read_file(dir, CONFIG_FILE)?, read_file(dir, STYLESHEET_FILE)?),
+            None => (None, None),
+        };
+        let file = parse_config(file.as_deref().unwrap_or_default());
+        Ok(Self {
+            options: BarOptions::read(|name| env(name).or_else(|| file.get(name).cloned())),
+            stylesheet: stylesheet.unwrap_or_default(),
+        })
+    }
+}
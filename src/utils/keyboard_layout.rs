@@ -0,0 +1,60 @@
+/// ISO 639-1 codes of the languages xkb names its layouts after.
+const LANGUAGE_CODES: &[(&str, &str)] = &[
+    ("Arabic", "AR"),
+    ("Chinese", "ZH"),
+    ("Czech", "CS"),
+    ("Danish", "DA"),
+    ("Dutch", "NL"),
+    ("English", "EN"),
+    ("Esperanto", "EO"),
+    ("Finnish", "FI"),
+    ("French", "FR"),
+    ("German", "DE"),
+    ("Greek", "EL"),
+    ("Hebrew", "HE"),
+    ("Hungarian", "HU"),
+    ("Italian", "IT"),
+    ("Japanese", "JA"),
+    ("Korean", "KO"),
+    ("Norwegian", "NO"),
+    ("Persian", "FA"),
+    ("Polish", "PL"),
+    ("Portuguese", "PT"),
+    ("Romanian", "RO"),
+    ("Russian", "RU"),
+    ("Spanish", "ES"),
+    ("Swedish", "SV"),
+    ("Turkish", "TR"),
+    ("Ukrainian", "UK"),
+];
+
+/// Short names of the layouts `names`, e.g. `EN` for `English (US)` and `DE` for `German`.
+///
+/// Layouts of the same language are numbered, e.g. `EN1` and `EN2` for
+/// `English (US)` and `English (UK)`.
+pub fn short_names(names: &[String]) -> Vec<String> {
+    let short: Vec<String> = names.iter().map(|name| short_name(name)).collect();
+    short
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            if short.iter().filter(|other| *other == name).count() > 1 {
+                let nth = short[..idx].iter().filter(|other| *other == name).count() + 1;
+                format!("{}{}", name, nth)
+            } else {
+                name.clone()
+            }
+        })
+        .collect()
+}
+
+/// The ISO 639-1 code of the language a layout is named after, or the first two
+/// letters of its name.
+fn short_name(name: &str) -> String {
+    let language = name.split([' ', '(']).next().unwrap_or(name);
+    LANGUAGE_CODES
+        .iter()
+        .find(|(lang, _)| *lang == language)
+        .map(|(_, code)| code.to_string())
+        .unwrap_or_else(|| language.chars().take(2).collect::<String>().to_uppercase())
+}
@@ -1 +1,5 @@
-pub mod strings;
\ No newline at end of file
+pub mod config;
+pub mod keyboard_layout;
+pub mod scroll;
+pub mod strings;
+pub mod visibility;
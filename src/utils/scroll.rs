@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+/// How scrolling over a widget translates into navigation steps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollOptions {
+    /// Swap the direction, e.g. for natural scrolling.
+    pub invert: bool,
+    /// Distance in pixels a touchpad has to scroll for one step.
+    pub threshold: f64,
+}
+
+impl Default for ScrollOptions {
+    fn default() -> Self {
+        Self {
+            invert: false,
+            threshold: 40.0,
+        }
+    }
+}
+
+impl FromStr for ScrollOptions {
+    type Err = String;
+
+    /// Parses the scroll direction, `normal` or `inverted`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invert = match s.trim().to_lowercase().as_str() {
+            "normal" => false,
+            "inverted" | "natural" => true,
+            other => return Err(format!("Unknown scroll direction {:?}, expected normal or inverted", other)),
+        };
+        Ok(Self {
+            invert,
+            ..Self::default()
+        })
+    }
+}
+
+/// Turns scroll deltas into whole navigation steps.
+///
+/// Mouse wheels scroll in notches and step once per notch, high-resolution wheels
+/// report fractions of a notch that add up to one. Touchpads scroll smoothly in
+/// pixels, which add up until they pass the threshold.
+#[derive(Clone, Debug)]
+pub struct ScrollAccumulator {
+    options: ScrollOptions,
+    pending: f64,
+    /// Whether `pending` is in pixels rather than notches.
+    pending_smooth: bool,
+}
+
+impl ScrollAccumulator {
+    pub fn new(options: ScrollOptions) -> Self {
+        Self {
+            options,
+            pending: 0.0,
+            pending_smooth: false,
+        }
+    }
+
+    /// Adds a scroll delta and returns the number of steps to take, positive
+    /// for down / right and negative for up / left.
+    ///
+    /// `smooth` is set for pixel deltas from a touchpad, otherwise `delta` counts
+    /// wheel notches.
+    pub fn push(&mut self, delta: f64, smooth: bool) -> i32 {
+        let delta = if self.options.invert { -delta } else { delta };
+        if delta == 0.0 {
+            return 0;
+        }
+
+        // Reversing direction drops what was scrolled the other way, switching
+        // devices what was scrolled with the other one.
+        if self.pending_smooth != smooth || (self.pending != 0.0 && self.pending.signum() != delta.signum()) {
+            self.pending = 0.0;
+            self.pending_smooth = smooth;
+        }
+        self.pending += delta;
+        let threshold = if smooth {
+            self.options.threshold.max(f64::EPSILON)
+        } else {
+            1.0
+        };
+        let steps = (self.pending / threshold).trunc();
+        self.pending -= steps * threshold;
+        steps as i32
+    }
+
+    /// Forgets partial scrolling, e.g. when the touchpad gesture ends.
+    pub fn reset(&mut self) {
+        self.pending = 0.0;
+    }
+
+    /// Switches to `options`, forgetting partial scrolling if they changed.
+    pub fn set_options(&mut self, options: ScrollOptions) {
+        if self.options != options {
+            self.options = options;
+            self.reset();
+        }
+    }
+}
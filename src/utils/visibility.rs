@@ -0,0 +1,152 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::service::event::{WindowInfo, WorkspaceInfo};
+
+/// When the bar gets out of the way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AutoHideMode {
+    /// Always shown.
+    #[default]
+    Off,
+    /// Hidden while the window focused on the bar's output is fullscreen. The bar
+    /// sits below fullscreen windows, so they cover it where they are not reported.
+    Fullscreen,
+    /// Hidden until the pointer touches the screen edge. The bar then overlaps
+    /// windows instead of reserving space.
+    Always,
+}
+
+impl FromStr for AutoHideMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "never" => Ok(AutoHideMode::Off),
+            "fullscreen" => Ok(AutoHideMode::Fullscreen),
+            "always" | "hover" => Ok(AutoHideMode::Always),
+            other => Err(format!("Unknown auto-hide mode {:?}, expected off, fullscreen or always", other)),
+        }
+    }
+}
+
+/// Decides whether the bar is shown.
+///
+/// The mode, the fullscreen state and hovering the screen edge give the automatic
+/// visibility. A manual toggle overrides it until the two agree again, e.g. a bar
+/// shown during a fullscreen video hides again after the next fullscreen window.
+#[derive(Clone, Debug)]
+pub struct Visibility {
+    mode: AutoHideMode,
+    fullscreen: bool,
+    hovered: bool,
+    forced: Option<bool>,
+}
+
+impl Visibility {
+    pub fn new(mode: AutoHideMode) -> Self {
+        Self {
+            mode,
+            fullscreen: false,
+            hovered: false,
+            forced: None,
+        }
+    }
+
+    pub fn mode(&self) -> AutoHideMode {
+        self.mode
+    }
+
+    /// Switches to `mode`, dropping a manual override.
+    pub fn set_mode(&mut self, mode: AutoHideMode) {
+        self.mode = mode;
+        self.forced = None;
+    }
+
+    fn automatic(&self) -> bool {
+        self.hovered
+            || match self.mode {
+                AutoHideMode::Off => true,
+                AutoHideMode::Fullscreen => !self.fullscreen,
+                AutoHideMode::Always => false,
+            }
+    }
+
+    /// Drops the manual override once it matches the automatic visibility.
+    fn settle(&mut self) {
+        if self.forced == Some(self.automatic()) {
+            self.forced = None;
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.forced.unwrap_or_else(|| self.automatic())
+    }
+
+    /// Whether the bar should reserve space at the screen edge.
+    pub fn reserves_space(&self) -> bool {
+        self.mode != AutoHideMode::Always
+    }
+
+    /// Whether the hot zone at the screen edge is needed to reveal the bar.
+    pub fn uses_hot_zone(&self) -> bool {
+        self.mode != AutoHideMode::Off
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+        self.settle();
+    }
+
+    /// Set while the pointer is over the hot zone or the bar itself.
+    pub fn set_hovered(&mut self, hovered: bool) {
+        self.hovered = hovered;
+        self.settle();
+    }
+
+    /// Shows a hidden bar or hides a shown one. Returns the new visibility.
+    pub fn toggle(&mut self) -> bool {
+        self.forced = Some(!self.is_visible());
+        self.settle();
+        self.is_visible()
+    }
+}
+
+/// Follows which window is focused on one output: the window that last had focus
+/// while on a workspace active there. Focusing another output leaves it alone, as
+/// the window stays in front on its own output.
+#[derive(Debug, Default)]
+pub struct OutputFocus {
+    focused: Option<u64>,
+}
+
+impl OutputFocus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the focused window and returns whether it is fullscreen. Without an
+    /// output, the focused workspace counts as the shown one.
+    pub fn fullscreen_on_output(
+        &mut self,
+        workspaces: &[WorkspaceInfo],
+        windows: &HashMap<u64, WindowInfo>,
+        output: Option<&str>,
+    ) -> bool {
+        let shown: Vec<u64> = workspaces
+            .iter()
+            .filter(|workspace| match output {
+                Some(output) => workspace.is_active && workspace.output.as_deref() == Some(output),
+                None => workspace.is_focused,
+            })
+            .map(|workspace| workspace.id)
+            .collect();
+        let is_shown = |window: &WindowInfo| window.workspace_id.is_some_and(|id| shown.contains(&id));
+
+        if let Some(window) = windows.values().find(|window| window.is_focused && is_shown(window)) {
+            self.focused = Some(window.id);
+        }
+        self.focused
+            .and_then(|id| windows.get(&id))
+            .is_some_and(|window| window.is_fullscreen && is_shown(window))
+    }
+}
@@ -0,0 +1,21 @@
+use gtk4::{IconTheme, Image, gdk::Display};
+
+use crate::service::desktop_entry::DesktopEntry;
+
+/// Icon shown for windows without a usable icon.
+const FALLBACK_ICON: &str = "application-x-executable";
+
+/// Shows the icon from the desktop entry of an application, falling back to an
+/// icon named after its app id.
+pub fn set_app_icon(image: &Image, entry: Option<&DesktopEntry>, app_id: Option<&str>) {
+    let icon = entry.and_then(|entry| entry.icon.as_deref()).or(app_id);
+    match icon {
+        Some(path) if path.starts_with('/') => image.set_from_file(Some(path)),
+        Some(name) if has_icon(name) => image.set_icon_name(Some(name)),
+        _ => image.set_icon_name(Some(FALLBACK_ICON)),
+    }
+}
+
+fn has_icon(name: &str) -> bool {
+    Display::default().is_some_and(|display| IconTheme::for_display(&display).has_icon(name))
+}
@@ -1,27 +1,49 @@
-use std::time::Duration;
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use gtk4::{
-    Box, Label, Revealer,
-    prelude::{BoxExt, WidgetExt},
-};
-use smol::{
-    Timer,
-    channel::{Receiver, Sender},
+    Box, EventControllerKey, Image, Label, Revealer, accessible, gdk, glib,
+    prelude::{AccessibleExt, BoxExt, WidgetExt},
 };
 use tracing::Event;
 
-use crate::service::event::{EventHandler, EventHandlerMutExt, EventListener, UIUpdateEvent, UIUpdateEventType};
+use crate::service::{
+    compositor::{self, CompositorCommand},
+    desktop_entry,
+    event::{EventHandler, EventHandlerMutExt, EventListener, OverflowPolicy, SubscribeOptions, Subscription, UIUpdateEvent, UIUpdateEventType},
+};
+use crate::utils::scroll::ScrollOptions;
+use crate::widgets::app_icon::set_app_icon;
+use crate::widgets::navigation::{connect_scroll_steps, send_steps};
+
+/// Duration of the crossfade between two windows.
+const FADE_DURATION: Duration = Duration::from_millis(300);
+
+/// Shown while no window is focused.
+fn compositor_name() -> &'static str {
+    compositor::current().map(|compositor| compositor.name()).unwrap_or("")
+}
 
 pub struct CurrentWindow {
+    container: Box,
+    icon: Image,
     app_id: Label,
     app_title: Label,
-    channel: (Sender<UIUpdateEvent>, Receiver<UIUpdateEvent>),
+    subscription: Option<Subscription<UIUpdateEventType, UIUpdateEvent>>,
     box_revealer: Revealer,
 }
 
 impl CurrentWindow {
-    pub fn new() -> Self {
-        let app_id = Label::new(Some("Niri"));
+    /// Scrolling over the widget, or pressing the arrow keys while it has keyboard
+    /// focus, moves the column focus; `scroll` configures the former.
+    pub fn new(scroll: Rc<Cell<ScrollOptions>>) -> Self {
+        let icon = Image::new();
+        icon.add_css_class("app-icon");
+        icon.set_visible(false);
+        let app_id = Label::new(Some(compositor_name()));
         let app_title = Label::new(Some(""));
         let container = Box::new(gtk4::Orientation::Vertical, 0);
         let outer_container = Box::new(gtk4::Orientation::Horizontal, 0);
@@ -38,19 +60,40 @@ impl CurrentWindow {
         container.set_halign(gtk4::Align::Start);
         container.set_valign(gtk4::Align::Center);
 
+        outer_container.append(&icon);
         outer_container.append(&container);
+        outer_container.set_focusable(true);
+        connect_scroll_steps(&outer_container, scroll, |steps| {
+            send_steps(steps, CompositorCommand::FocusColumnRight, CompositorCommand::FocusColumnLeft)
+        });
+
+        let keys = EventControllerKey::new();
+        keys.connect_key_pressed(|_, key, _, _| match key {
+            gdk::Key::Left => {
+                send_steps(-1, CompositorCommand::FocusColumnRight, CompositorCommand::FocusColumnLeft);
+                glib::Propagation::Stop
+            }
+            gdk::Key::Right => {
+                send_steps(1, CompositorCommand::FocusColumnRight, CompositorCommand::FocusColumnLeft);
+                glib::Propagation::Stop
+            }
+            _ => glib::Propagation::Proceed,
+        });
+        outer_container.add_controller(keys);
 
         let box_revealer = Revealer::builder()
             .transition_type(gtk4::RevealerTransitionType::Crossfade)
-            .transition_duration(300)
+            .transition_duration(FADE_DURATION.as_millis() as u32)
             .reveal_child(false)
             .child(&outer_container)
             .build();
 
         Self {
+            container: outer_container,
+            icon,
             app_id,
             app_title,
-            channel: smol::channel::unbounded(),
+            subscription: None,
             box_revealer,
         }
     }
@@ -62,23 +105,52 @@ impl CurrentWindow {
 
 impl EventHandler<UIUpdateEventType, UIUpdateEvent> for CurrentWindow {
     fn register_to_listener(&mut self, listener: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) {
-        listener.register_event_handler(
-            UIUpdateEventType::WindowFocusChanged,
-            self.channel.0.clone(),
-        );
+        self.subscription = Some(listener.subscribe(
+            vec![UIUpdateEventType::WindowFocusChanged],
+            // Only the latest focused window is worth showing.
+            SubscribeOptions::new()
+                .replay()
+                .bounded(1, OverflowPolicy::Coalesce),
+        ));
     }
 }
 
 impl EventHandlerMutExt<UIUpdateEventType, UIUpdateEvent> for CurrentWindow {
     async fn listen_mut(&mut self) {
-        while let Ok(event) = self.channel.1.recv().await {
+        let Some(subscription) = self.subscription.take() else {
+            return;
+        };
+        while let Some(event) = subscription.recv().await {
             match event {
                 UIUpdateEvent::WindowFocusChanged { app_id, title } => {
                     self.box_revealer.set_reveal_child(false);
-                    Timer::after(Duration::from_millis(300)).await;
-                    self.app_id.set_text(app_id.as_deref().unwrap_or("Niri"));
-                    self.app_title
-                        .set_text(truncate_text(title.as_deref().unwrap_or("Niri"), 40).as_str());
+                    // Focus changes arriving during the fade-out replace this one, without
+                    // restarting the fade, so the bar catches up within one animation.
+                    let mut latest = (app_id, title);
+                    let faded_out = Instant::now() + FADE_DURATION;
+                    while let Some(event) = subscription.recv_until(faded_out).await {
+                        if let UIUpdateEvent::WindowFocusChanged { app_id, title } = event {
+                            latest = (app_id, title);
+                        }
+                    }
+
+                    let (app_id, title) = latest;
+                    // Show "Files" rather than "org.gnome.Nautilus" where a desktop entry exists.
+                    let entry = match app_id.clone() {
+                        Some(app_id) => desktop_entry::lookup(app_id).await,
+                        None => None,
+                    };
+                    match &entry {
+                        Some(entry) => self.app_id.set_text(&entry.name),
+                        None => self.app_id.set_text(app_id.as_deref().unwrap_or(compositor_name())),
+                    }
+                    set_app_icon(&self.icon, entry.as_deref(), app_id.as_deref());
+                    self.icon.set_visible(app_id.is_some());
+                    let title = title.as_deref().unwrap_or(compositor_name());
+                    self.app_title.set_text(truncate_text(title, 40).as_str());
+                    // Screen readers get the whole title.
+                    self.container
+                        .update_property(&[accessible::Property::Label(&format!("Focused window: {}", title))]);
                     self.box_revealer.set_reveal_child(true);
                 }
                 _ => {}
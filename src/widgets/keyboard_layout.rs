@@ -0,0 +1,149 @@
+use gtk4::{
+    Box, Button, EventControllerKey, EventControllerScroll, EventControllerScrollFlags, GestureClick, Label, ListBox,
+    Popover, gdk, glib,
+    prelude::{BoxExt, ButtonExt, GestureSingleExt, PopoverExt, StaticType, WidgetExt},
+};
+
+use crate::service::{
+    compositor::{self, CompositorCommand},
+    event::{EventHandler, EventHandlerMutExt, EventListener, KeyboardLayouts, SubscribeOptions, Subscription, UIUpdateEvent, UIUpdateEventType},
+};
+use crate::utils::keyboard_layout::short_names;
+
+/// Shows the short name of the active keyboard layout.
+///
+/// Clicking cycles to the next layout, scrolling moves to the next or previous one
+/// and a secondary click or the Menu key lists all layouts.
+pub struct KeyboardLayout {
+    container: Box,
+    button: Button,
+    label: Label,
+    list: ListBox,
+    subscription: Option<Subscription<UIUpdateEventType, UIUpdateEvent>>,
+}
+
+impl KeyboardLayout {
+    pub fn new() -> Self {
+        let label = Label::new(None);
+        label.add_css_class("layout-name");
+
+        let button = Button::builder().child(&label).build();
+        button.add_css_class("keyboard-layout-button");
+        button.connect_clicked(|_| compositor::dispatch(CompositorCommand::SwitchLayoutNext));
+
+        let scroll = EventControllerScroll::new(
+            EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
+        );
+        scroll.connect_scroll(|_, _, dy| {
+            if dy > 0.0 {
+                compositor::dispatch(CompositorCommand::SwitchLayoutNext);
+            } else if dy < 0.0 {
+                compositor::dispatch(CompositorCommand::SwitchLayoutPrevious);
+            }
+            glib::Propagation::Stop
+        });
+        button.add_controller(scroll);
+
+        let list = ListBox::new();
+        list.set_selection_mode(gtk4::SelectionMode::None);
+        list.connect_row_activated(|list, row| {
+            compositor::dispatch(CompositorCommand::SwitchLayout {
+                index: row.index() as usize,
+            });
+            if let Some(popover) = list.ancestor(Popover::static_type()) {
+                popover.set_visible(false);
+            }
+        });
+        let popover = Popover::new();
+        popover.add_css_class("popup");
+        popover.set_child(Some(&list));
+        popover.set_parent(&button);
+        popover.connect_hide(|popover| popover.remove_css_class("visible"));
+
+        let keys = EventControllerKey::new();
+        let popover_clone = popover.clone();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            if key != gdk::Key::Menu {
+                return glib::Propagation::Proceed;
+            }
+            popover_clone.popup();
+            popover_clone.add_css_class("visible");
+            glib::Propagation::Stop
+        });
+        button.add_controller(keys);
+
+        let click = GestureClick::new();
+        click.set_button(gdk::BUTTON_SECONDARY);
+        click.connect_released(move |_, _, _, _| {
+            popover.popup();
+            popover.add_css_class("visible");
+        });
+        button.add_controller(click);
+
+        let container = Box::new(gtk4::Orientation::Horizontal, 0);
+        container.add_css_class("keyboard-layout");
+        container.append(&button);
+        // Hidden until the compositor reports more than one layout.
+        container.set_visible(false);
+
+        Self {
+            container,
+            button,
+            label,
+            list,
+            subscription: None,
+        }
+    }
+
+    pub fn export_widget(&self) -> &Box {
+        &self.container
+    }
+
+    fn update(&self, layouts: &KeyboardLayouts) {
+        let short_names = short_names(&layouts.names);
+        self.label
+            .set_text(short_names.get(layouts.current).map(String::as_str).unwrap_or("??"));
+        self.button.set_tooltip_text(layouts.current_name());
+        self.container.set_visible(layouts.names.len() > 1);
+
+        self.list.remove_all();
+        for (idx, (name, short_name)) in layouts.names.iter().zip(&short_names).enumerate() {
+            let row = Box::new(gtk4::Orientation::Horizontal, 8);
+            row.add_css_class("layout-row");
+            let short = Label::new(Some(short_name));
+            short.add_css_class("layout-short-name");
+            let full = Label::new(Some(name));
+            full.set_hexpand(true);
+            full.set_halign(gtk4::Align::Start);
+            row.append(&short);
+            row.append(&full);
+            if idx == layouts.current {
+                row.add_css_class("active");
+                row.append(&gtk4::Image::from_icon_name("object-select-symbolic"));
+            }
+            self.list.append(&row);
+        }
+    }
+}
+
+impl EventHandler<UIUpdateEventType, UIUpdateEvent> for KeyboardLayout {
+    fn register_to_listener(&mut self, listener: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) {
+        self.subscription = Some(listener.subscribe(
+            vec![UIUpdateEventType::KeyboardLayoutChanged],
+            SubscribeOptions::new().replay(),
+        ));
+    }
+}
+
+impl EventHandlerMutExt<UIUpdateEventType, UIUpdateEvent> for KeyboardLayout {
+    async fn listen_mut(&mut self) {
+        let Some(subscription) = self.subscription.take() else {
+            return;
+        };
+        while let Some(event) = subscription.recv().await {
+            if let UIUpdateEvent::KeyboardLayoutChanged { layouts } = event {
+                self.update(&layouts);
+            }
+        }
+    }
+}
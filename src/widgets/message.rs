@@ -0,0 +1,61 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use gtk4::{
+    Label, Stack, StackTransitionType, Widget, glib,
+    prelude::{IsA, WidgetExt},
+};
+
+/// Duration of the crossfade between the content and a message.
+const FADE_DURATION: Duration = Duration::from_millis(300);
+
+/// Shows short messages in place of `content` for a while. Cheap to clone.
+#[derive(Clone)]
+pub struct Message {
+    stack: Stack,
+    label: Label,
+    /// Counts the messages shown, so only the latest one hides itself.
+    shown: Rc<Cell<u64>>,
+}
+
+impl Message {
+    pub fn new(content: &impl IsA<Widget>) -> Self {
+        let label = Label::new(None);
+        label.add_css_class("bar-message");
+        label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        label.set_max_width_chars(60);
+
+        let stack = Stack::new();
+        stack.set_transition_type(StackTransitionType::Crossfade);
+        stack.set_transition_duration(FADE_DURATION.as_millis() as u32);
+        stack.set_hhomogeneous(false);
+        stack.add_named(content, Some("content"));
+        stack.add_named(&label, Some("message"));
+        stack.set_visible_child_name("content");
+
+        Self {
+            stack,
+            label,
+            shown: Rc::new(Cell::new(0)),
+        }
+    }
+
+    pub fn export_widget(&self) -> &Stack {
+        &self.stack
+    }
+
+    /// Shows `text` for `timeout`, replacing the message shown before.
+    pub fn show(&self, text: &str, timeout: Duration) {
+        let id = self.shown.get() + 1;
+        self.shown.set(id);
+        self.label.set_text(text);
+        self.stack.set_visible_child_name("message");
+
+        let stack = self.stack.clone();
+        let shown = self.shown.clone();
+        glib::timeout_add_local_once(timeout, move || {
+            if shown.get() == id {
+                stack.set_visible_child_name("content");
+            }
+        });
+    }
+}
@@ -1,3 +1,8 @@
+pub mod app_icon;
 pub mod current_window;
+pub mod keyboard_layout;
+pub mod message;
+pub mod navigation;
 pub mod panel;
+pub mod window_list;
 pub mod workspace;
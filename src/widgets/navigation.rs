@@ -0,0 +1,62 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use gtk4::{
+    EventControllerScroll, EventControllerScrollFlags, Widget, gdk, glib,
+    prelude::{IsA, WidgetExt},
+};
+use tracing::warn;
+
+use crate::{
+    service::compositor::{self, CompositorCommand},
+    utils::scroll::{ScrollAccumulator, ScrollOptions},
+};
+
+/// Calls `on_steps` with the number of steps scrolled over `widget`, positive for
+/// down / right. Horizontal scrolling counts when it is the larger of the two axes.
+/// `options` is read on every scroll, so changing it takes effect right away.
+pub fn connect_scroll_steps(
+    widget: &impl IsA<Widget>,
+    options: Rc<Cell<ScrollOptions>>,
+    on_steps: impl Fn(i32) + 'static,
+) {
+    let controller = EventControllerScroll::new(EventControllerScrollFlags::BOTH_AXES);
+    let accumulator = Rc::new(RefCell::new(ScrollAccumulator::new(options.get())));
+
+    let scrolled = accumulator.clone();
+    controller.connect_scroll(move |controller, dx, dy| {
+        let smooth = controller.unit() == gdk::ScrollUnit::Surface;
+        let delta = if dy.abs() >= dx.abs() { dy } else { dx };
+        let steps = {
+            let mut accumulator = scrolled.borrow_mut();
+            accumulator.set_options(options.get());
+            accumulator.push(delta, smooth)
+        };
+        if steps != 0 {
+            on_steps(steps);
+        }
+        glib::Propagation::Stop
+    });
+    controller.connect_scroll_end(move |_| accumulator.borrow_mut().reset());
+    widget.add_controller(controller);
+}
+
+/// Sends `forward` for every positive step and `backward` for every negative one,
+/// one after another so the compositor applies them in order.
+pub fn send_steps(steps: i32, forward: CompositorCommand, backward: CompositorCommand) {
+    let Some(compositor) = compositor::current() else {
+        return;
+    };
+    let command = if steps > 0 { forward } else { backward };
+    smol::spawn(async move {
+        for _ in 0..steps.unsigned_abs() {
+            if let Err(e) = compositor.execute(command.clone()).await {
+                warn!("Failed to execute compositor command: {}", e);
+                break;
+            }
+        }
+    })
+    .detach();
+}
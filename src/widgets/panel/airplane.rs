@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use adw::prelude::{ActionRowExt, PreferencesRowExt};
+use gtk4::{glib::{object::ObjectExt, SignalHandlerId}, prelude::WidgetExt};
+use tracing::{instrument, warn};
+
+use crate::service::{
+    event::{EventHandler, EventHandlerMutExt, EventListener, SubscribeOptions, Subscription},
+    rfkill::{
+        device::{AirplaneModeState, RadioState, RadioType},
+        event::{RfkillServiceEvent, RfkillServiceEventType, RfkillServiceRequest},
+    },
+};
+
+const AIRPLANE_MODE: &str = "/io/github/bigsaltyfishes/molyuubar/icons/flight_24.svg";
+
+/// An "Airplane Mode" switch backed by the rfkill service.
+///
+/// The row is hidden on machines without any radio and becomes insensitive while a
+/// hardware switch keeps a radio off.
+pub struct AirplaneMode {
+    row: adw::SwitchRow,
+    toggled_handler: Option<SignalHandlerId>,
+    radios: HashMap<RadioType, RadioState>,
+    airplane_mode: AirplaneModeState,
+    subscription: Option<Subscription<RfkillServiceEventType, RfkillServiceEvent>>,
+}
+
+impl AirplaneMode {
+    pub fn new() -> Self {
+        let row = adw::SwitchRow::new();
+        row.add_css_class("airplane-mode");
+        row.set_title("Airplane Mode");
+        row.set_subtitle("Off");
+        row.set_visible(false);
+
+        let icon = gtk4::Image::from_resource(AIRPLANE_MODE);
+        icon.add_css_class("icon");
+        icon.set_valign(gtk4::Align::Center);
+        icon.set_halign(gtk4::Align::Center);
+        row.add_prefix(&icon);
+
+        Self {
+            row,
+            toggled_handler: None,
+            radios: HashMap::new(),
+            airplane_mode: AirplaneModeState::default(),
+            subscription: None,
+        }
+    }
+
+    pub fn export_widget(&self) -> &adw::SwitchRow {
+        &self.row
+    }
+
+    fn radio_name(radio: RadioType) -> &'static str {
+        match radio {
+            RadioType::Wlan => "Wi-Fi",
+            RadioType::Bluetooth => "Bluetooth",
+            RadioType::Wwan => "Mobile broadband",
+            _ => "Radio",
+        }
+    }
+
+    /// Syncs the switch with the last reported state without triggering a request.
+    fn update(&self) {
+        if let Some(handler) = &self.toggled_handler {
+            self.row.block_signal(handler);
+        }
+        self.row.set_active(self.airplane_mode.enabled);
+        if let Some(handler) = &self.toggled_handler {
+            self.row.unblock_signal(handler);
+        }
+
+        let hard_blocked: Vec<&str> = RadioType::AIRPLANE_MODE
+            .iter()
+            .filter(|radio| self.radios.get(radio).is_some_and(|state| state.hard_blocked))
+            .map(|radio| Self::radio_name(*radio))
+            .collect();
+        let subtitle = match (self.airplane_mode.enabled, hard_blocked.is_empty()) {
+            (_, false) => format!("{} turned off by hardware switch", hard_blocked.join(", ")),
+            (true, true) => "On".to_string(),
+            (false, true) => "Off".to_string(),
+        };
+        self.row.set_subtitle(&subtitle);
+        // Unblocking would not bring a hard-blocked radio back, so don't pretend it could.
+        self.row
+            .set_sensitive(!(self.airplane_mode.enabled && self.airplane_mode.hard_blocked));
+        self.row
+            .set_visible(self.radios.values().any(|state| state.present));
+    }
+}
+
+impl EventHandler<RfkillServiceEventType, RfkillServiceEvent> for AirplaneMode {
+    fn register_to_listener(&mut self, listener: &mut impl EventListener<RfkillServiceEventType, RfkillServiceEvent>) {
+        let subscription = listener.subscribe(vec![
+            RfkillServiceEventType::RadioStateChanged,
+            RfkillServiceEventType::AirplaneModeChanged,
+        ], SubscribeOptions::new());
+
+        match subscription.try_recv().expect("Unable to register event handler.") {
+            RfkillServiceEvent::HandlerRegistered { command_sender } => {
+                self.toggled_handler = Some(self.row.connect_active_notify(move |row| {
+                    let request = RfkillServiceRequest::SetAirplaneMode { enabled: row.is_active() };
+                    if command_sender.try_send(request).is_err() {
+                        warn!("rfkill service is not available");
+                    }
+                }));
+            }
+            _ => {
+                panic!("Unexpected event received during handler registration.");
+            }
+        }
+        self.subscription = Some(subscription);
+    }
+}
+
+impl EventHandlerMutExt<RfkillServiceEventType, RfkillServiceEvent> for AirplaneMode {
+    #[instrument(skip_all)]
+    async fn listen_mut(&mut self) {
+        let Some(subscription) = self.subscription.take() else {
+            return;
+        };
+        while let Some(event) = subscription.recv().await {
+            match event {
+                RfkillServiceEvent::RadioStateChanged { radio, state } => {
+                    if RadioType::AIRPLANE_MODE.contains(&radio) {
+                        self.radios.insert(radio, state);
+                    }
+                }
+                RfkillServiceEvent::AirplaneModeChanged { state } => {
+                    self.airplane_mode = state;
+                }
+                _ => continue,
+            }
+            self.update();
+        }
+    }
+}
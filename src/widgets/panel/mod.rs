@@ -1,18 +1,34 @@
+mod airplane;
 mod power;
 mod datetime;
 mod network;
+mod wifi_auth;
+mod wifi_share;
 
 use std::os::unix::net;
 
 use gtk4::{
-    Box,
+    Box, Popover,
     prelude::{BoxExt, WidgetExt},
 };
 use power::Power;
+use smol::channel::Sender;
 
-use crate::service::{event::{EventHandler, EventHandlerMutExt}, network::NetworkService};
+use crate::service::{
+    event::{EventBus, EventHandler, EventHandlerMutExt, EventListener, SubscribeOptions},
+    network::{
+        NetworkService,
+        endpoints::event::{NetworkServiceEvent, NetworkServiceEventType, NetworkServiceRequest},
+    },
+    rfkill::RfkillService,
+};
 
-pub struct Panel(Box);
+pub struct Panel {
+    container: Box,
+    network_popover: Popover,
+    network_commands: Option<Sender<NetworkServiceRequest>>,
+    network_events: EventBus<NetworkServiceEventType, NetworkServiceEvent>,
+}
 
 impl Panel {
     pub fn new() -> Self {
@@ -22,6 +38,17 @@ impl Panel {
         let power = Power::new();
         let mut network_service = NetworkService::new();
         network.register_to_listener(&mut network_service);
+        // Only for the handshake, which is queued whatever the topics.
+        let network_commands = match network_service.subscribe(Vec::new(), SubscribeOptions::new()).try_recv() {
+            Some(NetworkServiceEvent::HandlerRegistered { command_sender }) => Some(command_sender),
+            _ => None,
+        };
+        let network_popover = network.menu().export_widget().clone();
+        let network_events = network_service.event_bus().clone();
+        let mut airplane_mode = airplane::AirplaneMode::new();
+        let mut rfkill_service = RfkillService::new();
+        airplane_mode.register_to_listener(&mut rfkill_service);
+        network.menu().prepend_row(airplane_mode.export_widget());
 
         panel.set_css_classes(&["panel"]);
         panel.append(network.export_widget());
@@ -34,11 +61,36 @@ impl Panel {
         smol::spawn(gtk4::glib::spawn_future_local(async move {
             network.listen_mut().await;
         })).detach();
+        smol::spawn(async move {
+            rfkill_service.listen().await;
+        }).detach();
+        smol::spawn(gtk4::glib::spawn_future_local(async move {
+            airplane_mode.listen_mut().await;
+        })).detach();
         
-        Panel(panel)
+        Panel {
+            container: panel,
+            network_popover,
+            network_commands,
+            network_events,
+        }
     }
 
     pub fn export_widget(&self) -> &Box {
-        &self.0
+        &self.container
+    }
+
+    pub fn network_popover(&self) -> &Popover {
+        &self.network_popover
+    }
+
+    /// Sends requests to the network service behind the panel.
+    pub fn network_commands(&self) -> Option<&Sender<NetworkServiceRequest>> {
+        self.network_commands.as_ref()
+    }
+
+    /// The events of the network service behind the panel.
+    pub fn network_events(&self) -> &EventBus<NetworkServiceEventType, NetworkServiceEvent> {
+        &self.network_events
     }
 }
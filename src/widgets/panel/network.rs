@@ -1,52 +1,155 @@
-use std::collections::{HashMap, HashSet};
+use std::{cell::{Cell, RefCell}, collections::{HashMap, HashSet}, rc::Rc};
 
-use adw::{gio::NetworkService, glib::object::IsA, prelude::{ActionRowExt, PreferencesRowExt}};
-use gtk4::{prelude::{BoxExt, ButtonExt, PopoverExt, WidgetExt}, Button, Popover, Widget};
-use rusty_network_manager::AccessPointProxy;
-use smol::channel::{Receiver, Sender};
-use tracing::{instrument, warn};
+use adw::{glib::{object::{IsA, ObjectExt}, SignalHandlerId}, prelude::{ActionRowExt, ExpanderRowExt, PreferencesRowExt}};
+use gtk4::{prelude::{BoxExt, ButtonExt, PopoverExt, WidgetExt}, Button, Popover, Stack, Widget};
+use smol::channel::Sender;
+use tracing::{info, instrument, warn};
 
-use crate::{service::{event::{EventHandler, EventHandlerExt, EventHandlerMutExt, EventListener}, network::{endpoints::event::{NetworkDeviceState, NetworkDeviceType, NetworkServiceEvent, NetworkServiceEventType, NetworkServiceRequest}, wireless::{self, ap::{AccessPoint, AccessPointSecurity}}}}, utils::strings};
+use crate::service::{event::{EventHandler, EventHandlerMutExt, EventListener, SubscribeOptions, Subscription}, network::{endpoints::event::{NetworkDeviceState, NetworkDeviceType, NetworkServiceEvent, NetworkServiceEventType, NetworkServiceRequest, WiFiConnServiceRequest, WiFiConnServiceResponse}, ethernet::WiredLink, metered::{MeteredState, NetworkMetered}, modem::{ModemState, ModemStatus}, wireless::{ap::{AccessPoint, AccessPointSecurity, HwAddress}, uri::WiFiUri}}};
+
+use super::{wifi_auth::WiFiAuthPrompt, wifi_share::{WiFiJoinPage, WiFiSharePage}};
 
 const WIFI_OFF: &str = "/io/github/bigsaltyfishes/molyuubar/icons/signal_wifi_off_24.svg";
 const WIFI_NOT_CONNECTED_BUT_AVAILABLE: &str = "/io/github/bigsaltyfishes/molyuubar/icons/signal_wifi_statusbar_not_connected_24.svg";
 const NETWORK_NOT_CONNECTED: &str = "/io/github/bigsaltyfishes/molyuubar/icons/signal_wifi_bad_24.svg";
 const ETHERNET_CONNECTED: &str = "/io/github/bigsaltyfishes/molyuubar/icons/settings_ethernet_24.svg";
+const WIFI_CONNECTED_UNKNOWN_STRENGTH: &str = "/io/github/bigsaltyfishes/molyuubar/icons/signal_wifi_0_24.svg";
+const CELLULAR_NO_SIGNAL: &str = "/io/github/bigsaltyfishes/molyuubar/icons/signal_cellular_0_bar_24.svg";
+const METERED: &str = "/io/github/bigsaltyfishes/molyuubar/icons/data_usage_24.svg";
 
 pub struct NetworkMenu {
     popover: Popover,
     wireless_menu: WirelessMenu,
+    auth_prompt: WiFiAuthPrompt,
+    share_page: WiFiSharePage,
+    // Connector of the interface from the latest scan report, used by "Join from WIFI: URI".
+    connector: Rc<RefCell<Option<WirelessConnector>>>,
+    wired: gtk4::ListBox,
+    ethernet_rows: HashMap<String, EthernetMenu>,
+    modems: gtk4::ListBox,
+    modem_rows: HashMap<String, ModemMenu>,
+    metered: MeteredMenu,
 }
 
 impl NetworkMenu {
     pub fn new(parent: &impl IsA<Widget>) -> Self {
         let popover = Popover::new();
+        let stack = Stack::new();
         let wireless_menu = WirelessMenu::new();
         popover.add_css_class("popup");
         popover.set_parent(parent);
-        popover.set_child(Some(wireless_menu.export_widget()));
+        stack.set_vhomogeneous(false);
+        stack.add_named(wireless_menu.export_widget(), Some("main"));
+        let auth_prompt = WiFiAuthPrompt::new(&stack);
+        let share_page = WiFiSharePage::new(&stack);
+        let join_page = WiFiJoinPage::new(&stack);
+        stack.set_visible_child_name("main");
+        popover.set_child(Some(&stack));
+
+        let connector: Rc<RefCell<Option<WirelessConnector>>> = Rc::new(RefCell::new(None));
+        let join_button = Button::with_label("Join from WIFI: URI");
+        join_button.add_css_class("join-uri");
+        {
+            let connector = connector.clone();
+            join_button.connect_clicked(move |_| {
+                let Some(connector) = connector.borrow().clone() else {
+                    warn!("No wireless interface to join a network with");
+                    return;
+                };
+                let join_page = join_page.clone();
+                gtk4::glib::spawn_future_local(async move {
+                    if let Some(uri) = join_page.ask().await {
+                        connector.join(uri);
+                    }
+                });
+            });
+        }
+        wireless_menu.export_widget().append(&join_button);
+
+        let modems = gtk4::ListBox::new();
+        modems.add_css_class("modems");
+        modems.add_css_class("boxed-list");
+        modems.set_selection_mode(gtk4::SelectionMode::None);
+        modems.set_visible(false);
+        wireless_menu.export_widget().prepend(&modems);
+
+        let wired = gtk4::ListBox::new();
+        wired.add_css_class("wired");
+        wired.add_css_class("boxed-list");
+        wired.set_selection_mode(gtk4::SelectionMode::None);
+        wired.set_visible(false);
+        wireless_menu.export_widget().prepend(&wired);
+
+        let metered = MeteredMenu::new();
+        wireless_menu.export_widget().prepend(metered.export_widget());
 
         Self {
             popover,
             wireless_menu,
+            auth_prompt,
+            share_page,
+            connector,
+            wired,
+            ethernet_rows: HashMap::new(),
+            modems,
+            modem_rows: HashMap::new(),
+            metered,
         }
     }
 
     pub fn export_widget(&self) -> &Popover {
         &self.popover
     }
+
+    /// Adds a row for a newly discovered Ethernet device.
+    fn add_ethernet(&mut self, interface: String, cmd_sender: Sender<NetworkServiceRequest>) -> &EthernetMenu {
+        let menu = EthernetMenu::new(interface.clone(), cmd_sender);
+        self.wired.append(menu.export_widget());
+        self.wired.set_visible(true);
+        self.ethernet_rows.entry(interface).or_insert(menu)
+    }
+
+    fn remove_ethernet(&mut self, interface: &str) {
+        if let Some(menu) = self.ethernet_rows.remove(interface) {
+            self.wired.remove(menu.export_widget());
+        }
+        self.wired.set_visible(!self.ethernet_rows.is_empty());
+    }
+
+    /// Adds a row for a newly discovered modem.
+    fn add_modem(&mut self, interface: String, cmd_sender: Sender<NetworkServiceRequest>) -> &ModemMenu {
+        let menu = ModemMenu::new(interface.clone(), cmd_sender);
+        self.modems.append(menu.export_widget());
+        self.modems.set_visible(true);
+        self.modem_rows.entry(interface).or_insert(menu)
+    }
+
+    fn remove_modem(&mut self, interface: &str) {
+        if let Some(menu) = self.modem_rows.remove(interface) {
+            self.modems.remove(menu.export_widget());
+        }
+        self.modems.set_visible(!self.modem_rows.is_empty());
+    }
+
+    /// Adds a row above the wireless controls, e.g. the airplane mode switch.
+    pub fn prepend_row(&self, row: &impl IsA<Widget>) {
+        self.wireless_menu.export_widget().prepend(row);
+    }
 }
 
+/// A popover row for an Ethernet device with a connect / disconnect button.
 pub struct EthernetMenu {
     ethernet_row: adw::ActionRow,
-    icon: gtk4::Image,
+    button: Button,
+    connected: Rc<Cell<bool>>,
+    carrier: Cell<bool>,
 }
 
 impl EthernetMenu {
-    pub fn new() -> Self {
+    pub fn new(interface: String, cmd_sender: Sender<NetworkServiceRequest>) -> Self {
         let ethernet_row = adw::ActionRow::new();
         ethernet_row.set_title("Ethernet");
-        ethernet_row.set_subtitle("Not connected");
+        ethernet_row.set_subtitle("Cable unplugged");
         ethernet_row.add_css_class("ethernet");
 
         let icon = gtk4::Image::from_resource(ETHERNET_CONNECTED);
@@ -55,22 +158,331 @@ impl EthernetMenu {
         icon.set_halign(gtk4::Align::Center);
         ethernet_row.add_prefix(&icon);
 
+        let button = Button::with_label("Connect");
+        button.set_valign(gtk4::Align::Center);
+        button.set_sensitive(false);
+        ethernet_row.add_suffix(&button);
+
+        let connected = Rc::new(Cell::new(false));
+        {
+            let connected = connected.clone();
+            button.connect_clicked(move |_| {
+                let interface = interface.clone();
+                let request = if connected.get() {
+                    NetworkServiceRequest::EthernetDisconnect { interface }
+                } else {
+                    NetworkServiceRequest::EthernetConnect { interface }
+                };
+                if cmd_sender.try_send(request).is_err() {
+                    warn!("Network service is not available");
+                }
+            });
+        }
+
         Self {
             ethernet_row,
-            icon,
+            button,
+            connected,
+            carrier: Cell::new(false),
         }
     }
 
     pub fn export_widget(&self) -> &adw::ActionRow {
         &self.ethernet_row
     }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.set(connected);
+        self.button.set_label(if connected { "Disconnect" } else { "Connect" });
+        self.update_sensitivity();
+    }
+
+    /// Shows the link, e.g. `1 Gb/s, cable connected` or `Cable unplugged`.
+    pub fn update_link(&self, link: &WiredLink) {
+        self.ethernet_row.set_subtitle(&link.description());
+        self.ethernet_row.set_tooltip_text(Some(&link.perm_hw_address));
+        self.carrier.set(link.carrier);
+        self.update_sensitivity();
+    }
+
+    fn update_sensitivity(&self) {
+        // Nothing can be activated without a cable.
+        self.button.set_sensitive(self.connected.get() || self.carrier.get());
+    }
+}
+
+/// A popover row for a mobile broadband modem with a connect / disconnect button.
+pub struct ModemMenu {
+    row: adw::ActionRow,
+    icon: gtk4::Image,
+    button: Button,
+    connected: Rc<Cell<bool>>,
+}
+
+impl ModemMenu {
+    pub fn new(interface: String, cmd_sender: Sender<NetworkServiceRequest>) -> Self {
+        let row = adw::ActionRow::new();
+        row.set_title("Mobile Broadband");
+        row.set_subtitle("No service");
+        row.add_css_class("modem");
+
+        let icon = gtk4::Image::from_resource(CELLULAR_NO_SIGNAL);
+        icon.add_css_class("icon");
+        icon.set_valign(gtk4::Align::Center);
+        icon.set_halign(gtk4::Align::Center);
+        row.add_prefix(&icon);
+
+        let button = Button::with_label("Connect");
+        button.set_valign(gtk4::Align::Center);
+        row.add_suffix(&button);
+
+        let connected = Rc::new(Cell::new(false));
+        {
+            let connected = connected.clone();
+            button.connect_clicked(move |_| {
+                let interface = interface.clone();
+                let request = if connected.get() {
+                    NetworkServiceRequest::ModemDisconnect { interface }
+                } else {
+                    NetworkServiceRequest::ModemConnect { interface }
+                };
+                if cmd_sender.try_send(request).is_err() {
+                    warn!("Network service is not available");
+                }
+            });
+        }
+
+        Self {
+            row,
+            icon,
+            button,
+            connected,
+        }
+    }
+
+    pub fn export_widget(&self) -> &adw::ActionRow {
+        &self.row
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.set(connected);
+        self.button.set_label(if connected { "Disconnect" } else { "Connect" });
+        if connected {
+            self.button.set_sensitive(true);
+        }
+    }
+
+    /// Shows the operator, e.g. `Operator` / `LTE · 72%`.
+    pub fn update_status(&self, status: &ModemStatus) {
+        self.row.set_title(status.operator_name.as_deref().unwrap_or("Mobile Broadband"));
+        let subtitle = match status.state {
+            ModemState::Failed => "Modem failed".to_string(),
+            ModemState::Locked => "SIM locked".to_string(),
+            ModemState::Disabled | ModemState::Disabling => "Disabled".to_string(),
+            ModemState::Unknown | ModemState::Initializing | ModemState::Enabling | ModemState::Searching => {
+                "Searching".to_string()
+            }
+            _ => match status.access_technology.label() {
+                "" => format!("{}%", status.signal_quality),
+                label => format!("{} · {}%", label, status.signal_quality),
+            },
+        };
+        self.row.set_subtitle(&subtitle);
+        self.icon.set_resource(Some(Network::match_cellular_icon(status)));
+        // A modem that is not registered cannot bring up a data connection.
+        self.button.set_sensitive(self.connected.get() || matches!(
+            status.state,
+            ModemState::Registered | ModemState::Connecting | ModemState::Connected
+        ));
+    }
+}
+
+/// A "Metered Connection" switch for the profile of the primary connection.
+///
+/// The switch shows what NetworkManager currently assumes; toggling it pins
+/// `connection.metered` on the active profile.
+pub struct MeteredMenu {
+    row: adw::SwitchRow,
+    toggled_handler: Option<SignalHandlerId>,
+}
+
+impl MeteredMenu {
+    pub fn new() -> Self {
+        let row = adw::SwitchRow::new();
+        row.add_css_class("metered");
+        row.set_title("Metered Connection");
+        row.set_subtitle("Detected automatically");
+        row.set_visible(false);
+
+        let icon = gtk4::Image::from_resource(METERED);
+        icon.add_css_class("icon");
+        icon.set_valign(gtk4::Align::Center);
+        icon.set_halign(gtk4::Align::Center);
+        row.add_prefix(&icon);
+
+        Self {
+            row,
+            toggled_handler: None,
+        }
+    }
+
+    pub fn export_widget(&self) -> &adw::SwitchRow {
+        &self.row
+    }
+
+    fn connect_toggled(&mut self, cmd_sender: Sender<NetworkServiceRequest>) {
+        self.toggled_handler = Some(self.row.connect_active_notify(move |row| {
+            let request = NetworkServiceRequest::SetMetered { metered: Some(row.is_active()) };
+            if cmd_sender.try_send(request).is_err() {
+                warn!("Network service is not available");
+            }
+        }));
+    }
+
+    /// Syncs the switch with the reported state without triggering a request.
+    /// The row is hidden while there is no primary connection.
+    pub fn update(&self, state: &MeteredState, connected: bool) {
+        if let Some(handler) = &self.toggled_handler {
+            self.row.block_signal(handler);
+        }
+        self.row.set_active(state.is_metered());
+        if let Some(handler) = &self.toggled_handler {
+            self.row.unblock_signal(handler);
+        }
+        self.row.set_subtitle(match state.profile {
+            NetworkMetered::Unknown => "Detected automatically",
+            _ => "Set for this network",
+        });
+        self.row.set_visible(connected);
+    }
+}
+
+/// Connects to access points on behalf of the menu rows of one interface.
+#[derive(Clone)]
+pub struct WirelessConnector {
+    interface: String,
+    cmd_sender: Sender<NetworkServiceRequest>,
+    auth_prompt: WiFiAuthPrompt,
+    share_page: WiFiSharePage,
+}
+
+impl WirelessConnector {
+    /// Starts connecting to the given network in the background.
+    /// If `bssid` is set, only that access point is used.
+    pub fn connect(&self, ssid: String, key_mgmt: AccessPointSecurity, bssid: Option<HwAddress>) {
+        let connector = self.clone();
+        gtk4::glib::spawn_future_local(async move {
+            connector.run(ssid, key_mgmt, bssid).await;
+        });
+    }
+
+    /// Creates a profile from a `WIFI:` URI and connects to it in the background.
+    pub fn join(&self, uri: WiFiUri) {
+        let connector = self.clone();
+        gtk4::glib::spawn_future_local(async move {
+            let (tx, rx) = smol::channel::bounded(1);
+            let ssid = uri.ssid.clone();
+            let request = NetworkServiceRequest::WiFiConnectUri {
+                interface: connector.interface.clone(),
+                uri,
+                channel: tx,
+            };
+            if connector.cmd_sender.send(request).await.is_err() {
+                warn!("Network service is not available");
+                return;
+            }
+            match rx.recv().await {
+                Ok(true) => info!("Connected to {}", ssid),
+                _ => warn!("Failed to join {}", ssid),
+            }
+        });
+    }
+
+    /// Shows the QR code of a saved network on the share page.
+    pub fn share(&self, ssid: String, key_mgmt: AccessPointSecurity) {
+        let connector = self.clone();
+        gtk4::glib::spawn_future_local(async move {
+            let (tx, rx) = smol::channel::bounded(1);
+            let request = NetworkServiceRequest::GetWiFiShareUri {
+                ssid: ssid.clone(),
+                key_mgmt,
+                channel: tx,
+            };
+            if connector.cmd_sender.send(request).await.is_err() {
+                warn!("Network service is not available");
+                return;
+            }
+            let uri = rx.recv().await.ok().flatten();
+            connector.share_page.show(&ssid, uri).await;
+        });
+    }
+
+    #[instrument(skip(self))]
+    async fn run(&self, ssid: String, key_mgmt: AccessPointSecurity, bssid: Option<HwAddress>) {
+        let (tx, rx) = smol::channel::unbounded();
+        if self
+            .cmd_sender
+            .send(NetworkServiceRequest::WiFiConnect { interface: self.interface.clone(), channel: tx })
+            .await
+            .is_err()
+        {
+            warn!("Network service is not available");
+            return;
+        }
+
+        let session = match rx.recv().await.ok().and_then(|msg| msg.into_response()) {
+            Some(WiFiConnServiceResponse::ServerAcceptedConnection(session)) => session,
+            other => {
+                warn!("Unexpected response to connection request: {:?}", other);
+                return;
+            }
+        };
+
+        if session
+            .send(WiFiConnServiceRequest::WiFiConnect { ssid: ssid.clone(), key_mgmt, bssid, ip_settings: None }.into_message())
+            .await
+            .is_err()
+        {
+            return;
+        }
+
+        // The service drops its end of the channel once the session is over.
+        while let Ok(message) = rx.recv().await {
+            match message.into_response() {
+                Some(WiFiConnServiceResponse::RequestAcknowledged) => {
+                    info!("Connected to {}", ssid);
+                    break;
+                }
+                Some(WiFiConnServiceResponse::AuthentiationRequired) => {
+                    let Some(response) = self.auth_prompt.ask(&ssid).await else {
+                        break;
+                    };
+                    let request = WiFiConnServiceRequest::ProvideAuthenticationInfo {
+                        psk: response.psk,
+                        ip_settings: response.ip_settings,
+                    };
+                    if session
+                        .send(request.into_message())
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                other => warn!("Unexpected message during connection: {:?}", other),
+            }
+        }
+    }
 }
 
 pub struct WirelessMenu {
-    access_points: HashSet<(String, AccessPointSecurity)>,
     controller_icon: gtk4::Image,
     controller: adw::SwitchRow,
-    menus: HashMap<String, adw::ExpanderRow>,
+    scan_spinner: gtk4::Spinner,
+    scanning_interfaces: HashSet<String>,
+    networks: gtk4::ListBox,
+    menus: HashMap<(String, AccessPointSecurity), adw::ExpanderRow>,
+    share_buttons: HashMap<(String, AccessPointSecurity), Button>,
     outer_box: gtk4::Box,
 }
 
@@ -93,13 +505,27 @@ impl WirelessMenu {
         controller.set_title("Wireless Radio");
         controller.set_subtitle("Disabled");
 
+        let scan_spinner = gtk4::Spinner::new();
+        scan_spinner.set_visible(false);
+        scan_spinner.set_tooltip_text(Some("Scanning"));
+        controller.add_suffix(&scan_spinner);
+
+        let networks = gtk4::ListBox::new();
+        networks.add_css_class("networks");
+        networks.add_css_class("boxed-list");
+        networks.set_selection_mode(gtk4::SelectionMode::None);
+
         container.append(&controller);
+        container.append(&networks);
 
         Self {
-            access_points: HashSet::new(),
             controller_icon: controller_icon,
             controller: controller,
+            scan_spinner,
+            scanning_interfaces: HashSet::new(),
+            networks,
             menus: HashMap::new(),
+            share_buttons: HashMap::new(),
             outer_box: container,
         }
     }
@@ -107,9 +533,151 @@ impl WirelessMenu {
     pub fn export_widget(&self) -> &gtk4::Box {
         &self.outer_box
     }
+
+    /// Shows the scan spinner while any interface is scanning.
+    pub fn set_scanning(&mut self, interface: String, scanning: bool) {
+        if scanning {
+            self.scanning_interfaces.insert(interface);
+        } else {
+            self.scanning_interfaces.remove(&interface);
+        }
+        let active = !self.scanning_interfaces.is_empty();
+        self.scan_spinner.set_visible(active);
+        self.scan_spinner.set_spinning(active);
+    }
+
+    /// Rebuilds the network list from a scan report.
+    ///
+    /// Each `(SSID, security)` group gets an expandable row listing every BSSID in it,
+    /// ordered by signal strength. Rows that were expanded stay expanded.
+    pub fn update_access_points(
+        &mut self,
+        access_points: HashMap<(String, AccessPointSecurity), Vec<AccessPoint>>,
+        connector: &WirelessConnector,
+    ) {
+        let expanded: HashSet<(String, AccessPointSecurity)> = self
+            .menus
+            .iter()
+            .filter(|(_, row)| row.is_expanded())
+            .map(|(key, _)| key.clone())
+            .collect();
+        for (_, row) in self.menus.drain() {
+            self.networks.remove(&row);
+        }
+        self.share_buttons.clear();
+
+        let mut groups: Vec<_> = access_points
+            .into_iter()
+            .filter(|((ssid, _), aps)| !ssid.is_empty() && !aps.is_empty())
+            .collect();
+        for (_, aps) in groups.iter_mut() {
+            aps.sort_by(|a, b| b.signal_strength.cmp(&a.signal_strength));
+        }
+        groups.sort_by(|(_, a), (_, b)| b[0].signal_strength.cmp(&a[0].signal_strength));
+
+        let uptime = Self::seconds_since_boot();
+        for (key, aps) in groups {
+            let (row, share) = Self::build_network_row(&key, &aps, uptime, connector);
+            row.set_expanded(expanded.contains(&key));
+            self.networks.append(&row);
+            self.share_buttons.insert(key.clone(), share);
+            self.menus.insert(key, row);
+        }
+    }
+
+    /// Shows the Share button only on the networks in `shareable`, as there is no
+    /// password to share without a saved profile.
+    pub fn update_share_buttons(&self, shareable: &HashSet<(String, AccessPointSecurity)>) {
+        for (key, share) in &self.share_buttons {
+            share.set_visible(shareable.contains(key));
+        }
+    }
 }
 
 impl WirelessMenu {
+    fn build_network_row(
+        (ssid, key_mgmt): &(String, AccessPointSecurity),
+        aps: &[AccessPoint],
+        uptime: Option<i64>,
+        connector: &WirelessConnector,
+    ) -> (adw::ExpanderRow, Button) {
+        let row = adw::ExpanderRow::new();
+        row.set_use_markup(false);
+        row.set_title(ssid);
+        row.set_subtitle(&match aps.len() {
+            1 => format!("{:?}", key_mgmt),
+            n => format!("{:?} · {} access points", key_mgmt, n),
+        });
+
+        let icon = gtk4::Image::from_resource(Network::match_icon(&aps[0]));
+        icon.add_css_class("icon");
+        row.add_prefix(&icon);
+
+        let connect = Button::with_label("Connect");
+        connect.set_valign(gtk4::Align::Center);
+        {
+            let connector = connector.clone();
+            let (ssid, key_mgmt) = (ssid.clone(), *key_mgmt);
+            connect.connect_clicked(move |_| connector.connect(ssid.clone(), key_mgmt, None));
+        }
+        row.add_suffix(&connect);
+
+        let share = Button::from_icon_name("emblem-shared-symbolic");
+        share.set_valign(gtk4::Align::Center);
+        share.set_tooltip_text(Some("Share"));
+        share.set_visible(false);
+        {
+            let connector = connector.clone();
+            let (ssid, key_mgmt) = (ssid.clone(), *key_mgmt);
+            share.connect_clicked(move |_| connector.share(ssid.clone(), key_mgmt));
+        }
+        row.add_suffix(&share);
+
+        for ap in aps {
+            let bssid_row = adw::ActionRow::new();
+            bssid_row.add_css_class("bssid");
+            bssid_row.set_title(&ap.bssid);
+            bssid_row.set_subtitle(&Self::describe_access_point(ap, uptime));
+
+            let pin = Button::with_label("Connect");
+            pin.set_valign(gtk4::Align::Center);
+            pin.set_tooltip_text(Some("Connect to this access point only"));
+            {
+                let connector = connector.clone();
+                let (ssid, key_mgmt, bssid) = (ssid.clone(), *key_mgmt, ap.bssid.clone());
+                pin.connect_clicked(move |_| connector.connect(ssid.clone(), key_mgmt, Some(bssid.clone())));
+            }
+            bssid_row.add_suffix(&pin);
+            row.add_row(&bssid_row);
+        }
+
+        (row, share)
+    }
+
+    /// Formats band, channel, signal strength and last seen time, e.g. `5 GHz · Ch 36 · 72% · 3s ago`.
+    fn describe_access_point(ap: &AccessPoint, uptime: Option<i64>) -> String {
+        let band = ap
+            .band()
+            .map(|band| band.to_string())
+            .unwrap_or_else(|| format!("{} MHz", ap.frequency));
+        let channel = ap
+            .channel()
+            .map(|channel| format!(" · Ch {}", channel))
+            .unwrap_or_default();
+        // NetworkManager reports `LastSeen` in CLOCK_BOOTTIME seconds, -1 if never seen.
+        let seen = match uptime {
+            Some(now) if ap.last_seen >= 0 => format!(" · {}s ago", (now - ap.last_seen as i64).max(0)),
+            _ => String::new(),
+        };
+        format!("{}{} · {}%{}", band, channel, ap.signal_strength, seen)
+    }
+
+    fn seconds_since_boot() -> Option<i64> {
+        let uptime = std::fs::read_to_string("/proc/uptime").ok()?;
+        let seconds: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+        Some(seconds as i64)
+    }
+
     fn match_controller_icon(enabled: bool, available: bool, connected: bool) -> &'static gtk4::gdk::Texture {
         lazy_static::lazy_static! {
             static ref WIFI_OFF_TEXTRUE: gtk4::gdk::Texture = gtk4::gdk::Texture::for_pixbuf(
@@ -140,13 +708,21 @@ impl WirelessMenu {
 pub struct NetworkStateStorage {
     wifi_enabled: bool,
     interfaces: HashMap<String, (NetworkDeviceType, bool)>,
-    default_routing_interface: Option<(NetworkDeviceType, String)>,
+    active_access_points: HashMap<String, AccessPoint>,
+    saved_profiles: HashSet<(String, AccessPointSecurity)>,
+    modem_status: HashMap<String, ModemStatus>,
+    wired_links: HashMap<String, WiredLink>,
+    primary_interface: Option<(NetworkDeviceType, String)>,
+    primary_connection_id: Option<String>,
+    metered: MeteredState,
 }
 
 pub struct Network {
     button: Button,
     icon: gtk4::Image,
-    event_channel: (Sender<NetworkServiceEvent>, Receiver<NetworkServiceEvent>),
+    metered_badge: gtk4::Image,
+    menu: NetworkMenu,
+    subscription: Option<Subscription<NetworkServiceEventType, NetworkServiceEvent>>,
     cmd_sender: Option<Sender<NetworkServiceRequest>>,
     storage: NetworkStateStorage,
 }
@@ -163,19 +739,32 @@ impl Network {
         icon.add_css_class("icon");
         icon.set_valign(gtk4::Align::Center);
         icon.set_halign(gtk4::Align::Center);
-        button.set_child(Some(&icon));
+
+        let metered_badge = gtk4::Image::from_resource(METERED);
+        metered_badge.add_css_class("metered-badge");
+        metered_badge.set_valign(gtk4::Align::Center);
+        metered_badge.set_visible(false);
+
+        let content = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
+        content.append(&icon);
+        content.append(&metered_badge);
+        button.set_child(Some(&content));
         button.set_tooltip_text(Some("Network"));
         let menu = NetworkMenu::new(&button);
 
-        button.connect_clicked(move |_| {
-            let popover = menu.export_widget();
-            popover.popup();
-        });
+        {
+            let popover = menu.export_widget().clone();
+            button.connect_clicked(move |_| {
+                popover.popup();
+            });
+        }
 
         Self {
             button,
             icon,
-            event_channel: smol::channel::unbounded(),
+            metered_badge,
+            menu,
+            subscription: None,
             cmd_sender: None,
             storage: NetworkStateStorage::default(),
         }
@@ -184,10 +773,14 @@ impl Network {
     pub fn export_widget(&self) -> &Button {
         &self.button
     }
+
+    pub fn menu(&self) -> &NetworkMenu {
+        &self.menu
+    }
 }
 
 impl Network {
-    fn match_icon(ap: AccessPoint) -> &'static str {
+    fn match_icon(ap: &AccessPoint) -> &'static str {
         match (ap.signal_strength, ap.key_management() != AccessPointSecurity::None) {
             (0..=20, true)   => "/io/github/bigsaltyfishes/molyuubar/icons/wifi_lock_24.svg",
             (21..=40, true)  => "/io/github/bigsaltyfishes/molyuubar/icons/network_wifi_1_bar_locked_24.svg",
@@ -201,42 +794,174 @@ impl Network {
             (_, false)       => "/io/github/bigsaltyfishes/molyuubar/icons/signal_wifi_4_bar_24.svg",
         }
     }
+
+    fn match_cellular_icon(status: &ModemStatus) -> &'static str {
+        let registered = matches!(
+            status.state,
+            ModemState::Registered | ModemState::Disconnecting | ModemState::Connecting | ModemState::Connected
+        );
+        match (registered, status.signal_quality) {
+            (false, _) | (_, 0..=10) => CELLULAR_NO_SIGNAL,
+            (_, 11..=35) => "/io/github/bigsaltyfishes/molyuubar/icons/signal_cellular_1_bar_24.svg",
+            (_, 36..=60) => "/io/github/bigsaltyfishes/molyuubar/icons/signal_cellular_2_bar_24.svg",
+            (_, 61..=85) => "/io/github/bigsaltyfishes/molyuubar/icons/signal_cellular_3_bar_24.svg",
+            (_, _) => "/io/github/bigsaltyfishes/molyuubar/icons/signal_cellular_4_bar_24.svg",
+        }
+    }
+
+    /// Updates the panel icon from the primary connection reported by the service.
+    fn update_icon(&self) {
+        let resource = match &self.storage.primary_interface {
+            Some((NetworkDeviceType::Ethernet, _)) => ETHERNET_CONNECTED,
+            Some((NetworkDeviceType::WiFi, interface)) => self
+                .storage
+                .active_access_points
+                .get(interface)
+                .map(Self::match_icon)
+                .unwrap_or(WIFI_CONNECTED_UNKNOWN_STRENGTH),
+            Some((NetworkDeviceType::Modem, interface)) => self
+                .storage
+                .modem_status
+                .get(interface)
+                .map(Self::match_cellular_icon)
+                .unwrap_or(CELLULAR_NO_SIGNAL),
+            Some((device_type, _)) => {
+                warn!("Unsupported device type for icon update: {:?}", device_type);
+                NETWORK_NOT_CONNECTED
+            }
+            None => {
+                let has_wifi = self
+                    .storage
+                    .interfaces
+                    .values()
+                    .any(|(dt, _)| *dt == NetworkDeviceType::WiFi);
+                if has_wifi && !self.storage.wifi_enabled {
+                    WIFI_OFF
+                } else {
+                    NETWORK_NOT_CONNECTED
+                }
+            }
+        };
+        self.icon.set_resource(Some(resource));
+    }
+
+    /// Updates the metered badge, the tooltip and the metered switch in the menu.
+    fn update_metered(&self) {
+        let connected = self.storage.primary_interface.is_some();
+        let metered = connected && self.storage.metered.is_metered();
+        self.metered_badge.set_visible(metered);
+
+        let name = self.storage.primary_connection_id.as_deref().unwrap_or("Network");
+        let tooltip = if metered {
+            format!("{} (metered)", name)
+        } else {
+            name.to_string()
+        };
+        self.button.set_tooltip_text(Some(&tooltip));
+        self.menu.metered.update(&self.storage.metered, connected);
+    }
+
+    /// Creates the rows of the known wired devices and modems that have none yet.
+    /// Rows need the command sender, so they are only built once it is known.
+    fn add_device_rows(&mut self) {
+        let Some(cmd_sender) = self.cmd_sender.clone() else {
+            return;
+        };
+        for (interface, (device_type, activated)) in &self.storage.interfaces {
+            match device_type {
+                NetworkDeviceType::Ethernet if !self.menu.ethernet_rows.contains_key(interface) => {
+                    let ethernet = self.menu.add_ethernet(interface.clone(), cmd_sender.clone());
+                    // The watchdog may report the link before the device is announced.
+                    if let Some(link) = self.storage.wired_links.get(interface) {
+                        ethernet.update_link(link);
+                    }
+                    ethernet.set_connected(*activated);
+                }
+                NetworkDeviceType::Modem if !self.menu.modem_rows.contains_key(interface) => {
+                    let modem = self.menu.add_modem(interface.clone(), cmd_sender.clone());
+                    if let Some(status) = self.storage.modem_status.get(interface) {
+                        modem.update_status(status);
+                    }
+                    modem.set_connected(*activated);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Offers sharing for saved networks and the ones currently connected to.
+    fn update_share_buttons(&self) {
+        let mut shareable = self.storage.saved_profiles.clone();
+        shareable.extend(
+            self.storage
+                .active_access_points
+                .values()
+                .map(|ap| (ap.ssid.clone(), ap.key_management())),
+        );
+        self.menu.wireless_menu.update_share_buttons(&shareable);
+    }
 }
 
 impl EventHandler<NetworkServiceEventType, NetworkServiceEvent> for Network {
     fn register_to_listener(&mut self, listener: &mut impl EventListener<NetworkServiceEventType, NetworkServiceEvent>) {
-        listener.register_event_handler_many(vec![
+        let subscription = listener.subscribe(vec![
             NetworkServiceEventType::DeviceAdded,
             NetworkServiceEventType::DeviceRemoved,
             NetworkServiceEventType::DeviceStateChanged,
             NetworkServiceEventType::AccessPointScanReport,
+            NetworkServiceEventType::AccessPointUpdated,
             NetworkServiceEventType::ActiveAccessPointChanged,
             NetworkServiceEventType::GlobalWirelessEnabledStateChanged,
-        ], self.event_channel.0.clone());
+            NetworkServiceEventType::PrimaryConnectionChanged,
+            NetworkServiceEventType::ScanStateChanged,
+            NetworkServiceEventType::ModemStatusChanged,
+            NetworkServiceEventType::MeteredStateChanged,
+            NetworkServiceEventType::WiredLinkChanged,
+            NetworkServiceEventType::SavedProfilesChanged,
+        ], SubscribeOptions::new());
 
-        match smol::block_on(self.event_channel.1.recv()).expect("Unable to register event handler.") {
+        match subscription.try_recv().expect("Unable to register event handler.") {
             NetworkServiceEvent::HandlerRegistered { command_sender } => {
+                // Let the scan scheduler know when the menu is open.
+                let popover = self.menu.export_widget();
+                {
+                    let cmd_sender = command_sender.clone();
+                    popover.connect_show(move |_| {
+                        let _ = cmd_sender.try_send(NetworkServiceRequest::MenuVisibilityChanged { visible: true });
+                    });
+                }
+                {
+                    let cmd_sender = command_sender.clone();
+                    popover.connect_closed(move |_| {
+                        let _ = cmd_sender.try_send(NetworkServiceRequest::MenuVisibilityChanged { visible: false });
+                    });
+                }
+                self.menu.metered.connect_toggled(command_sender.clone());
                 self.cmd_sender = Some(command_sender);
+                self.add_device_rows();
             }
             _ => {
                 panic!("Unexpected event received during handler registration.");
             }
         }
+        self.subscription = Some(subscription);
     }
 }
 
 impl EventHandlerMutExt<NetworkServiceEventType, NetworkServiceEvent> for Network {
     #[instrument(skip_all)]
     async fn listen_mut(&mut self) {
-        while let Ok(event) = self.event_channel.1.recv().await {
+        let Some(subscription) = self.subscription.take() else {
+            return;
+        };
+        while let Some(event) = subscription.recv().await {
             match event {
                 NetworkServiceEvent::DeviceAdded { interface, device_type } => {
                     match device_type {
-                        NetworkDeviceType::WiFi | NetworkDeviceType::Ethernet=> {
-                            self.storage.interfaces.insert(interface.clone(), (device_type, false));
-                            if self.storage.default_routing_interface.is_none() {
-                                self.storage.default_routing_interface = Some((device_type.clone(), interface.clone()));
-                            }
+                        NetworkDeviceType::WiFi | NetworkDeviceType::Ethernet | NetworkDeviceType::Modem => {
+                            // A repeated announcement must not reset the device.
+                            self.storage.interfaces.entry(interface).or_insert((device_type, false));
+                            self.add_device_rows();
                         }
                         _ => {
                             warn!("Unsupported device type: {:?}", device_type);
@@ -244,93 +969,93 @@ impl EventHandlerMutExt<NetworkServiceEventType, NetworkServiceEvent> for Networ
                     }
                 }
                 NetworkServiceEvent::DeviceRemoved { interface } => {
-                    if let Some((device_type, _)) = self.storage.interfaces.remove(&interface) {
-                        if self.storage.default_routing_interface.as_ref().map_or(false, |(dt, _)| *dt == device_type) {
-                            self.storage.default_routing_interface = None;
-                            self.storage.interfaces.iter().find(|(_, (_, state))| {
-                                *state
-                            }).map(|(name, (dt, _))| {
-                                self.storage.default_routing_interface = Some((dt.clone(), name.clone()));
-                            });
-                        }
-
-                        if let Some((dt, _)) = self.storage.default_routing_interface {
-                            if let Some((_, true)) = self.storage.interfaces.get(&interface) {
-                                match dt {
-                                    NetworkDeviceType::WiFi => {
-                                        todo!() // Handle WiFi icon update
-                                    }
-                                    NetworkDeviceType::Ethernet => {
-                                        self.icon.set_resource(Some(ETHERNET_CONNECTED));
-                                    }
-                                    _ => {
-                                        warn!("Unsupported device type for icon update: {:?}", dt);
-                                    }
-                                }
-                            }
-                        } else {
-                            self.icon.set_resource(Some(NETWORK_NOT_CONNECTED));
-                        }
-                    } else {
+                    if self.storage.interfaces.remove(&interface).is_none() {
                         warn!("Attempted to remove non-existent interface: {}", interface);
                     }
+                    self.storage.active_access_points.remove(&interface);
+                    self.storage.modem_status.remove(&interface);
+                    self.storage.wired_links.remove(&interface);
+                    self.menu.remove_modem(&interface);
+                    self.menu.remove_ethernet(&interface);
+                    self.update_share_buttons();
                 }
-                NetworkServiceEvent::DeviceStateChanged { interface, state, reason } => {
-                    if let Some((device_type, is_activated)) = self.storage.interfaces.get_mut(&interface) {
-                        if state == NetworkDeviceState::Activated {
-                            *is_activated = true;
-                        } else {
-                            *is_activated = false;
+                NetworkServiceEvent::DeviceStateChanged { interface, state, reason: _ } => {
+                    if let Some((_, is_activated)) = self.storage.interfaces.get_mut(&interface) {
+                        *is_activated = state == NetworkDeviceState::Activated;
+                        if let Some(modem) = self.menu.modem_rows.get(&interface) {
+                            modem.set_connected(*is_activated);
                         }
-                        
-                        if let Some((_, default_interface)) = self.storage.default_routing_interface.as_mut() {
-                            if default_interface.as_str() == interface.as_str() {
-                                if *is_activated {
-                                    match device_type {
-                                        NetworkDeviceType::WiFi => {
-                                            // TODO: Handle WiFi icon update
-                                        }
-                                        NetworkDeviceType::Ethernet => {
-                                            self.icon.set_resource(Some(ETHERNET_CONNECTED));
-                                        }
-                                        _ => {
-                                            warn!("Unsupported device type for icon update: {:?}", device_type);
-                                        }
-                                    }
-                                } else {
-                                    self.icon.set_resource(Some(NETWORK_NOT_CONNECTED));
-                                    self.storage.default_routing_interface = None;
-                                }
-                            }
-                        } else if *is_activated {
-                            self.storage.default_routing_interface = Some((device_type.clone(), interface.clone()));
-                            match device_type {
-                                NetworkDeviceType::WiFi => {
-                                    // TODO: Handle WiFi icon update
-                                }
-                                NetworkDeviceType::Ethernet => {
-                                    self.icon.set_resource(Some(ETHERNET_CONNECTED));
-                                }
-                                _ => {
-                                    warn!("Unsupported device type for icon update: {:?}", device_type);
-                                }
-                            }
+                        if let Some(ethernet) = self.menu.ethernet_rows.get(&interface) {
+                            ethernet.set_connected(*is_activated);
                         }
                     } else {
                         warn!("Received state change for unknown interface: {}", interface);
                     }
                 }
                 NetworkServiceEvent::AccessPointScanReport { interface, access_points } => {
-                    // TODO: Handle access point scan report
+                    let Some(cmd_sender) = self.cmd_sender.clone() else {
+                        warn!("Received scan report before handler registration");
+                        continue;
+                    };
+                    let connector = WirelessConnector {
+                        interface,
+                        cmd_sender,
+                        auth_prompt: self.menu.auth_prompt.clone(),
+                        share_page: self.menu.share_page.clone(),
+                    };
+                    self.menu.wireless_menu.update_access_points(access_points, &connector);
+                    *self.menu.connector.borrow_mut() = Some(connector);
+                    self.update_share_buttons();
+                }
+                NetworkServiceEvent::AccessPointUpdated { interface, ap } => {
+                    if let Some(active) = self.storage.active_access_points.get_mut(&interface) {
+                        if active.dbus_path == ap.dbus_path {
+                            *active = ap;
+                            self.update_icon();
+                        }
+                    }
                 }
                 NetworkServiceEvent::ActiveAccessPointChanged { interface, ap } => {
-                    // TODO: Handle active access point change
+                    self.storage.active_access_points.insert(interface, ap);
+                    self.update_icon();
+                    self.update_share_buttons();
                 }
                 NetworkServiceEvent::GlobalWirelessEnabledStateChanged { enabled } => {
-                    // TODO: Handle global wireless state change
+                    self.storage.wifi_enabled = enabled;
+                    self.update_icon();
+                }
+                NetworkServiceEvent::ScanStateChanged { interface, scanning } => {
+                    self.menu.wireless_menu.set_scanning(interface, scanning);
+                }
+                NetworkServiceEvent::ModemStatusChanged { interface, status } => {
+                    if let Some(modem) = self.menu.modem_rows.get(&interface) {
+                        modem.update_status(&status);
+                    }
+                    self.storage.modem_status.insert(interface, status);
+                    self.update_icon();
+                }
+                NetworkServiceEvent::WiredLinkChanged { interface, link } => {
+                    if let Some(ethernet) = self.menu.ethernet_rows.get(&interface) {
+                        ethernet.update_link(&link);
+                    }
+                    self.storage.wired_links.insert(interface, link);
+                }
+                NetworkServiceEvent::PrimaryConnectionChanged { interface, device_type, connection_id } => {
+                    self.storage.primary_interface = interface.zip(device_type).map(|(i, dt)| (dt, i));
+                    self.storage.primary_connection_id = connection_id;
+                    self.update_icon();
+                    self.update_metered();
+                }
+                NetworkServiceEvent::MeteredStateChanged { state } => {
+                    self.storage.metered = state;
+                    self.update_metered();
+                }
+                NetworkServiceEvent::SavedProfilesChanged { profiles } => {
+                    self.storage.saved_profiles = profiles;
+                    self.update_share_buttons();
                 }
                 _ => {}
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,317 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use gtk4::{
+    prelude::{BoxExt, ButtonExt, CheckButtonExt, EditableExt, EntryExt, WidgetExt},
+    Button, CheckButton, DropDown, Entry, Expander, Label, PasswordEntry, Stack,
+};
+use smol::channel::{Receiver, Sender};
+
+use crate::service::network::ipconfig::{
+    IpAddressEntry, IpConfig, IpConfigError, IpFamily, IpMethod, IpSettings,
+};
+
+const AUTH_PAGE: &str = "auth";
+
+/// Methods offered in the "Advanced" section, in drop-down order.
+const IP_METHODS: [(&str, IpMethod); 4] = [
+    ("Automatic", IpMethod::Auto),
+    ("Manual", IpMethod::Manual),
+    ("Link-local", IpMethod::LinkLocal),
+    ("Disabled", IpMethod::Disabled),
+];
+
+/// What the user entered in the prompt.
+#[derive(Debug)]
+pub struct WiFiAuthResponse {
+    pub psk: String,
+    /// `None` if the "Advanced" section was left at its defaults.
+    pub ip_settings: Option<IpSettings>,
+}
+
+/// Input widgets for the settings of one IP family.
+#[derive(Clone)]
+struct IpFamilyForm {
+    container: gtk4::Box,
+    method: DropDown,
+    addresses: Entry,
+    gateway: Entry,
+    dns: Entry,
+    ignore_auto_dns: CheckButton,
+    route_metric: Entry,
+}
+
+impl IpFamilyForm {
+    fn new(title: &str, address_example: &str, gateway_example: &str, dns_example: &str) -> Self {
+        let container = gtk4::Box::new(gtk4::Orientation::Vertical, 4);
+        let label = Label::new(Some(title));
+        let names: Vec<&str> = IP_METHODS.iter().map(|(name, _)| *name).collect();
+        let method = DropDown::from_strings(&names);
+        let addresses = Entry::new();
+        let gateway = Entry::new();
+        let dns = Entry::new();
+        let ignore_auto_dns = CheckButton::with_label("Ignore automatic DNS");
+        let route_metric = Entry::new();
+
+        container.add_css_class("ip-family");
+        label.add_css_class("heading");
+        label.set_halign(gtk4::Align::Start);
+        addresses.set_placeholder_text(Some(&format!("Addresses, e.g. {}", address_example)));
+        gateway.set_placeholder_text(Some(&format!("Gateway, e.g. {}", gateway_example)));
+        dns.set_placeholder_text(Some(&format!("DNS servers, e.g. {}", dns_example)));
+        route_metric.set_placeholder_text(Some("Route metric"));
+        route_metric.set_input_purpose(gtk4::InputPurpose::Digits);
+
+        container.append(&label);
+        container.append(&method);
+        container.append(&addresses);
+        container.append(&gateway);
+        container.append(&dns);
+        container.append(&ignore_auto_dns);
+        container.append(&route_metric);
+
+        let form = Self {
+            container,
+            method,
+            addresses,
+            gateway,
+            dns,
+            ignore_auto_dns,
+            route_metric,
+        };
+        form.update_sensitivity();
+        {
+            let form = form.clone();
+            form.method
+                .clone()
+                .connect_selected_notify(move |_| form.update_sensitivity());
+        }
+        form
+    }
+
+    fn selected_method(&self) -> IpMethod {
+        IP_METHODS
+            .get(self.method.selected() as usize)
+            .map(|(_, method)| *method)
+            .unwrap_or_default()
+    }
+
+    /// Addresses and gateway only apply to the manual method, DNS settings are
+    /// meaningless when the family is disabled.
+    fn update_sensitivity(&self) {
+        let method = self.selected_method();
+        self.addresses.set_sensitive(method == IpMethod::Manual);
+        self.gateway.set_sensitive(method == IpMethod::Manual);
+        self.dns.set_sensitive(method != IpMethod::Disabled);
+        self.ignore_auto_dns.set_sensitive(method == IpMethod::Auto);
+        self.route_metric.set_sensitive(method != IpMethod::Disabled);
+    }
+
+    fn reset(&self) {
+        self.method.set_selected(0);
+        self.addresses.set_text("");
+        self.gateway.set_text("");
+        self.dns.set_text("");
+        self.ignore_auto_dns.set_active(false);
+        self.route_metric.set_text("");
+    }
+
+    /// Parses the form. Lists accept commas and whitespace as separators.
+    fn read<A: IpFamily>(&self) -> Result<IpConfig<A>, IpConfigError> {
+        fn split(text: &str) -> impl Iterator<Item = &str> {
+            text.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|item| !item.is_empty())
+        }
+        fn parse_address<A: IpFamily>(text: &str) -> Result<A, IpConfigError> {
+            text.parse()
+                .map_err(|_| IpConfigError::InvalidAddress(text.to_string()))
+        }
+
+        let method = self.selected_method();
+        let manual = method == IpMethod::Manual;
+        let addresses = if manual {
+            split(&self.addresses.text())
+                .map(str::parse::<IpAddressEntry<A>>)
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            Vec::new()
+        };
+        let gateway_text = self.gateway.text();
+        let gateway = match gateway_text.trim() {
+            text if manual && !text.is_empty() => Some(parse_address(text)?),
+            _ => None,
+        };
+        let dns = if method != IpMethod::Disabled {
+            split(&self.dns.text())
+                .map(parse_address)
+                .collect::<Result<Vec<A>, _>>()?
+        } else {
+            Vec::new()
+        };
+        let metric_text = self.route_metric.text();
+        let route_metric = match metric_text.trim() {
+            "" => None,
+            text if method != IpMethod::Disabled => Some(
+                text.parse()
+                    .map_err(|_| IpConfigError::InvalidRouteMetric(text.to_string()))?,
+            ),
+            _ => None,
+        };
+
+        let config = IpConfig {
+            method,
+            addresses,
+            gateway,
+            dns,
+            ignore_auto_dns: method == IpMethod::Auto && self.ignore_auto_dns.is_active(),
+            route_metric,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// An inline password prompt that temporarily replaces the content of a `Stack`.
+///
+/// Dialogs would be clipped by the bar window, so the prompt lives inside the popover
+/// as an extra stack page instead. An "Advanced" section allows entering static
+/// addresses and DNS servers for networks without DHCP.
+#[derive(Clone)]
+pub struct WiFiAuthPrompt {
+    stack: Stack,
+    container: gtk4::Box,
+    title: Label,
+    entry: PasswordEntry,
+    advanced: Expander,
+    ipv4: IpFamilyForm,
+    ipv6: IpFamilyForm,
+    error: Label,
+    responses: (Sender<Option<WiFiAuthResponse>>, Receiver<Option<WiFiAuthResponse>>),
+}
+
+impl WiFiAuthPrompt {
+    /// Creates the prompt and adds it as a page of the given stack.
+    pub fn new(stack: &Stack) -> Self {
+        let container = gtk4::Box::new(gtk4::Orientation::Vertical, 8);
+        let title = Label::new(None);
+        let entry = PasswordEntry::new();
+        let advanced = Expander::new(Some("Advanced"));
+        let advanced_box = gtk4::Box::new(gtk4::Orientation::Vertical, 8);
+        let ipv4 = IpFamilyForm::new("IPv4", "192.168.1.10/24", "192.168.1.1", "1.1.1.1");
+        let ipv6 = IpFamilyForm::new("IPv6", "fd00::10/64", "fd00::1", "2606:4700:4700::1111");
+        let error = Label::new(None);
+        let buttons = gtk4::Box::new(gtk4::Orientation::Horizontal, 8);
+        let cancel = Button::with_label("Cancel");
+        let connect = Button::with_label("Connect");
+
+        container.add_css_class("wifi-auth");
+        title.add_css_class("title");
+        title.set_halign(gtk4::Align::Start);
+        entry.set_show_peek_icon(true);
+        advanced.add_css_class("advanced");
+        error.add_css_class("error");
+        error.set_halign(gtk4::Align::Start);
+        error.set_wrap(true);
+        error.set_visible(false);
+        buttons.set_halign(gtk4::Align::End);
+        connect.add_css_class("suggested-action");
+
+        advanced_box.append(&ipv4.container);
+        advanced_box.append(&ipv6.container);
+        advanced.set_child(Some(&advanced_box));
+        buttons.append(&cancel);
+        buttons.append(&connect);
+        container.append(&title);
+        container.append(&entry);
+        container.append(&advanced);
+        container.append(&error);
+        container.append(&buttons);
+        stack.add_named(&container, Some(AUTH_PAGE));
+
+        let prompt = Self {
+            stack: stack.clone(),
+            container,
+            title,
+            entry,
+            advanced,
+            ipv4,
+            ipv6,
+            error,
+            responses: smol::channel::unbounded(),
+        };
+
+        {
+            let tx = prompt.responses.0.clone();
+            cancel.connect_clicked(move |_| {
+                let _ = tx.try_send(None);
+            });
+        }
+        {
+            let prompt = prompt.clone();
+            connect.connect_clicked(move |_| prompt.submit());
+        }
+        {
+            let prompt = prompt.clone();
+            prompt.entry.clone().connect_activate(move |_| prompt.submit());
+        }
+
+        prompt
+    }
+
+    /// Validates the input and answers the pending `ask`, or shows what is wrong.
+    fn submit(&self) {
+        let psk = self.entry.text().to_string();
+        if psk.is_empty() {
+            self.show_error("Enter a password");
+            return;
+        }
+
+        let ip_settings = match (self.ipv4.read::<Ipv4Addr>(), self.ipv6.read::<Ipv6Addr>()) {
+            (Ok(ipv4), Ok(ipv6)) => Some(IpSettings { ipv4, ipv6 }).filter(|s| *s != IpSettings::default()),
+            (Err(e), _) => {
+                self.show_error(&format!("IPv4: {}", e));
+                return;
+            }
+            (_, Err(e)) => {
+                self.show_error(&format!("IPv6: {}", e));
+                return;
+            }
+        };
+
+        let _ = self
+            .responses
+            .0
+            .try_send(Some(WiFiAuthResponse { psk, ip_settings }));
+    }
+
+    fn show_error(&self, message: &str) {
+        self.error.set_text(message);
+        self.error.set_visible(true);
+    }
+
+    /// Shows the prompt for the given SSID and waits for the user to answer.
+    ///
+    /// Returns `None` if the prompt was cancelled.
+    /// The previously visible page is restored afterwards.
+    pub async fn ask(&self, ssid: &str) -> Option<WiFiAuthResponse> {
+        // Drop answers left over from an earlier prompt.
+        while self.responses.1.try_recv().is_ok() {}
+
+        let previous = self.stack.visible_child();
+        self.title.set_text(&format!("Password for \"{}\"", ssid));
+        self.entry.set_text("");
+        self.error.set_visible(false);
+        self.advanced.set_expanded(false);
+        self.ipv4.reset();
+        self.ipv6.reset();
+        self.stack.set_visible_child(&self.container);
+        self.entry.grab_focus();
+
+        let response = self.responses.1.recv().await.ok().flatten();
+
+        self.entry.set_text("");
+        if let Some(previous) = previous {
+            self.stack.set_visible_child(&previous);
+        }
+        response
+    }
+}
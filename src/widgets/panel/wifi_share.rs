@@ -0,0 +1,236 @@
+use gtk4::{
+    gdk, glib,
+    prelude::{BoxExt, ButtonExt, EditableExt, EntryExt, WidgetExt},
+    Button, Entry, Label, Picture, Stack,
+};
+use qrcode::{Color, QrCode};
+use smol::channel::{Receiver, Sender};
+
+use crate::service::network::wireless::uri::WiFiUri;
+
+const SHARE_PAGE: &str = "share";
+const JOIN_PAGE: &str = "join";
+
+/// Size of one QR module in pixels.
+const QR_MODULE_SIZE: usize = 6;
+/// Light border around the code, in modules, required by scanners.
+const QR_QUIET_ZONE: usize = 4;
+
+/// Renders `data` as a black-on-white QR code texture.
+fn qr_texture(data: &str) -> Option<gdk::MemoryTexture> {
+    let code = QrCode::new(data.as_bytes()).ok()?;
+    let modules = code.width() + 2 * QR_QUIET_ZONE;
+    let size = modules * QR_MODULE_SIZE;
+    let stride = size * 3;
+
+    let mut pixels = vec![0xFFu8; stride * size];
+    for y in 0..code.width() {
+        for x in 0..code.width() {
+            if code[(x, y)] != Color::Dark {
+                continue;
+            }
+            for dy in 0..QR_MODULE_SIZE {
+                let row = (y + QR_QUIET_ZONE) * QR_MODULE_SIZE + dy;
+                let start = row * stride + (x + QR_QUIET_ZONE) * QR_MODULE_SIZE * 3;
+                pixels[start..start + QR_MODULE_SIZE * 3].fill(0);
+            }
+        }
+    }
+
+    Some(gdk::MemoryTexture::new(
+        size as i32,
+        size as i32,
+        gdk::MemoryFormat::R8g8b8,
+        &glib::Bytes::from_owned(pixels),
+        stride,
+    ))
+}
+
+/// A stack page showing the credentials of a saved network as a QR code.
+#[derive(Clone)]
+pub struct WiFiSharePage {
+    stack: Stack,
+    container: gtk4::Box,
+    title: Label,
+    picture: Picture,
+    uri: Label,
+    closed: (Sender<()>, Receiver<()>),
+}
+
+impl WiFiSharePage {
+    /// Creates the page and adds it to the given stack.
+    pub fn new(stack: &Stack) -> Self {
+        let container = gtk4::Box::new(gtk4::Orientation::Vertical, 8);
+        let title = Label::new(None);
+        let picture = Picture::new();
+        let uri = Label::new(None);
+        let back = Button::with_label("Back");
+        let closed = smol::channel::unbounded();
+
+        container.add_css_class("wifi-share");
+        title.add_css_class("title");
+        title.set_halign(gtk4::Align::Start);
+        picture.set_can_shrink(false);
+        picture.set_halign(gtk4::Align::Center);
+        uri.add_css_class("uri");
+        uri.set_selectable(true);
+        uri.set_wrap(true);
+        uri.set_wrap_mode(gtk4::pango::WrapMode::Char);
+        back.set_halign(gtk4::Align::End);
+
+        container.append(&title);
+        container.append(&picture);
+        container.append(&uri);
+        container.append(&back);
+        stack.add_named(&container, Some(SHARE_PAGE));
+
+        {
+            let tx = closed.0.clone();
+            back.connect_clicked(move |_| {
+                let _ = tx.try_send(());
+            });
+        }
+
+        Self {
+            stack: stack.clone(),
+            container,
+            title,
+            picture,
+            uri,
+            closed,
+        }
+    }
+
+    /// Shows the QR code for `uri` until the user goes back.
+    /// If `uri` is `None`, explains that the network has no saved credentials.
+    pub async fn show(&self, ssid: &str, uri: Option<WiFiUri>) {
+        while self.closed.1.try_recv().is_ok() {}
+
+        let previous = self.stack.visible_child();
+        self.title.set_text(&format!("Share \"{}\"", ssid));
+        match uri.as_ref().map(|uri| (uri.to_string(), qr_texture(&uri.to_string()))) {
+            Some((text, Some(texture))) => {
+                self.picture.set_paintable(Some(&texture));
+                self.picture.set_visible(true);
+                self.uri.set_text(&text);
+            }
+            Some((text, None)) => {
+                self.picture.set_visible(false);
+                self.uri.set_text(&text);
+            }
+            None => {
+                self.picture.set_visible(false);
+                self.uri.set_text("This network has no saved password.");
+            }
+        }
+        self.stack.set_visible_child(&self.container);
+
+        let _ = self.closed.1.recv().await;
+
+        // Don't leave the password around in the widget tree.
+        self.picture.set_paintable(None::<&gdk::Paintable>);
+        self.uri.set_text("");
+        if let Some(previous) = previous {
+            self.stack.set_visible_child(&previous);
+        }
+    }
+}
+
+/// A stack page where a `WIFI:` URI can be pasted to join a network.
+#[derive(Clone)]
+pub struct WiFiJoinPage {
+    stack: Stack,
+    container: gtk4::Box,
+    entry: Entry,
+    error: Label,
+    responses: (Sender<Option<WiFiUri>>, Receiver<Option<WiFiUri>>),
+}
+
+impl WiFiJoinPage {
+    /// Creates the page and adds it to the given stack.
+    pub fn new(stack: &Stack) -> Self {
+        let container = gtk4::Box::new(gtk4::Orientation::Vertical, 8);
+        let title = Label::new(Some("Join from WIFI: URI"));
+        let entry = Entry::new();
+        let error = Label::new(None);
+        let buttons = gtk4::Box::new(gtk4::Orientation::Horizontal, 8);
+        let cancel = Button::with_label("Cancel");
+        let join = Button::with_label("Join");
+
+        container.add_css_class("wifi-join");
+        title.add_css_class("title");
+        title.set_halign(gtk4::Align::Start);
+        entry.set_placeholder_text(Some("WIFI:T:WPA;S:network;P:password;;"));
+        error.add_css_class("error");
+        error.set_halign(gtk4::Align::Start);
+        error.set_visible(false);
+        buttons.set_halign(gtk4::Align::End);
+        join.add_css_class("suggested-action");
+
+        buttons.append(&cancel);
+        buttons.append(&join);
+        container.append(&title);
+        container.append(&entry);
+        container.append(&error);
+        container.append(&buttons);
+        stack.add_named(&container, Some(JOIN_PAGE));
+
+        let page = Self {
+            stack: stack.clone(),
+            container,
+            entry,
+            error,
+            responses: smol::channel::unbounded(),
+        };
+
+        {
+            let tx = page.responses.0.clone();
+            cancel.connect_clicked(move |_| {
+                let _ = tx.try_send(None);
+            });
+        }
+        {
+            let page = page.clone();
+            join.connect_clicked(move |_| page.submit());
+        }
+        {
+            let page = page.clone();
+            page.entry.clone().connect_activate(move |_| page.submit());
+        }
+
+        page
+    }
+
+    fn submit(&self) {
+        match self.entry.text().parse::<WiFiUri>() {
+            Ok(uri) => {
+                let _ = self.responses.0.try_send(Some(uri));
+            }
+            Err(e) => {
+                self.error.set_text(&format!("Invalid URI: {}", e));
+                self.error.set_visible(true);
+            }
+        }
+    }
+
+    /// Shows the page and waits for a valid URI.
+    ///
+    /// Returns `None` if the user cancelled.
+    pub async fn ask(&self) -> Option<WiFiUri> {
+        while self.responses.1.try_recv().is_ok() {}
+
+        let previous = self.stack.visible_child();
+        self.entry.set_text("");
+        self.error.set_visible(false);
+        self.stack.set_visible_child(&self.container);
+        self.entry.grab_focus();
+
+        let uri = self.responses.1.recv().await.ok().flatten();
+
+        self.entry.set_text("");
+        if let Some(previous) = previous {
+            self.stack.set_visible_child(&previous);
+        }
+        uri
+    }
+}
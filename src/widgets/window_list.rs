@@ -0,0 +1,304 @@
+use std::{str::FromStr, sync::Arc};
+
+use gtk4::{
+    AccessibleRole, Box, EventControllerKey, GestureClick, Image, Label, PopoverMenu, accessible, gdk,
+    gio::{self, prelude::ActionMapExt},
+    glib::{self, ToVariant},
+    pango::EllipsizeMode,
+    prelude::{AccessibleExt, BoxExt, GestureSingleExt, PopoverExt, WidgetExt},
+};
+use smol::channel::{Receiver, Sender};
+
+use crate::service::{
+    compositor::{self, CompositorCommand},
+    desktop_entry::{self, DesktopEntry},
+    event::{EventHandler, EventHandlerMutExt, EventListener, SubscribeOptions, Subscription, UIUpdateEvent, UIUpdateEventType, WindowInfo, WorkspaceInfo},
+};
+use crate::widgets::app_icon::set_app_icon;
+
+/// Which windows the window list shows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowListScope {
+    /// Windows on the focused workspace.
+    #[default]
+    CurrentWorkspace,
+    AllWorkspaces,
+}
+
+impl FromStr for WindowListScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "current-workspace" | "workspace" => Ok(WindowListScope::CurrentWorkspace),
+            "all-workspaces" | "all" => Ok(WindowListScope::AllWorkspaces),
+            other => Err(format!(
+                "Unknown window list scope {:?}, expected current-workspace or all-workspaces",
+                other
+            )),
+        }
+    }
+}
+
+/// One window in the list: app icon and title.
+///
+/// Primary click focuses the window, middle click closes it and secondary click
+/// opens a menu to move it to another workspace. With keyboard focus, Enter and
+/// Space focus the window, Delete closes it and the Menu key opens the menu.
+struct WindowButton {
+    container: Box,
+    icon: Image,
+    title: Label,
+    menu: gio::Menu,
+    popover: PopoverMenu,
+    window: WindowInfo,
+}
+
+impl WindowButton {
+    fn new(window: WindowInfo, entry: Option<Arc<DesktopEntry>>) -> Self {
+        let icon = Image::new();
+        icon.add_css_class("window-icon");
+        let title = Label::new(None);
+        title.add_css_class("window-title");
+        title.set_ellipsize(EllipsizeMode::End);
+        title.set_max_width_chars(20);
+
+        let container = Box::builder()
+            .orientation(gtk4::Orientation::Horizontal)
+            .spacing(4)
+            .accessible_role(AccessibleRole::Button)
+            .focusable(true)
+            .build();
+        container.add_css_class("window-button");
+        container.append(&icon);
+        container.append(&title);
+
+        let id = window.id;
+        let move_action = gio::SimpleAction::new("move", Some(glib::VariantTy::UINT64));
+        move_action.connect_activate(move |_, workspace_id| {
+            if let Some(workspace_id) = workspace_id.and_then(|v| v.get::<u64>()) {
+                compositor::dispatch(CompositorCommand::MoveWindowToWorkspace {
+                    window: id,
+                    workspace: workspace_id,
+                });
+            }
+        });
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action(&move_action);
+        container.insert_action_group("window", Some(&actions));
+
+        let menu = gio::Menu::new();
+        let popover = PopoverMenu::from_model(Some(&menu));
+        popover.set_has_arrow(false);
+        popover.set_parent(&container);
+
+        let click = GestureClick::new();
+        // Listen to every mouse button, `current_button` tells them apart.
+        click.set_button(0);
+        let popover_clone = popover.clone();
+        click.connect_released(move |gesture, _, _, _| match gesture.current_button() {
+            gtk4::gdk::BUTTON_PRIMARY => compositor::dispatch(CompositorCommand::FocusWindow { id }),
+            gtk4::gdk::BUTTON_MIDDLE => compositor::dispatch(CompositorCommand::CloseWindow { id }),
+            gtk4::gdk::BUTTON_SECONDARY => popover_clone.popup(),
+            _ => {}
+        });
+        container.add_controller(click);
+
+        let keys = EventControllerKey::new();
+        let popover_clone = popover.clone();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            match key {
+                gdk::Key::Return | gdk::Key::KP_Enter | gdk::Key::space => {
+                    compositor::dispatch(CompositorCommand::FocusWindow { id })
+                }
+                gdk::Key::Delete => compositor::dispatch(CompositorCommand::CloseWindow { id }),
+                gdk::Key::Menu => popover_clone.popup(),
+                _ => return glib::Propagation::Proceed,
+            }
+            glib::Propagation::Stop
+        });
+        container.add_controller(keys);
+
+        let mut button = Self {
+            container,
+            icon,
+            title,
+            menu,
+            popover,
+            window: window.clone(),
+        };
+        button.update(window, entry);
+        button
+    }
+
+    fn update(&mut self, window: WindowInfo, entry: Option<Arc<DesktopEntry>>) {
+        set_app_icon(&self.icon, entry.as_deref(), window.app_id.as_deref());
+        let name = entry.as_ref().map(|entry| entry.name.as_str());
+        let title = window
+            .title
+            .as_deref()
+            .or(name)
+            .or(window.app_id.as_deref())
+            .unwrap_or("");
+        self.title.set_text(title);
+        self.container.set_tooltip_text(Some(title));
+        self.container
+            .update_property(&[accessible::Property::Label(title)]);
+
+        if window.is_focused {
+            self.container.add_css_class("focused");
+        } else {
+            self.container.remove_css_class("focused");
+        }
+        self.window = window;
+    }
+
+    /// Lists every other workspace in the move-to menu.
+    fn update_menu(&self, workspaces: &[WorkspaceInfo]) {
+        self.menu.remove_all();
+        let section = gio::Menu::new();
+        for workspace in workspaces.iter().filter(|w| Some(w.id) != self.window.workspace_id) {
+            let item = gio::MenuItem::new(Some(&format!("Move to workspace {}", workspace.label())), None);
+            item.set_action_and_target_value(Some("window.move"), Some(&workspace.id.to_variant()));
+            section.append_item(&item);
+        }
+        self.menu.append_section(None, &section);
+    }
+}
+
+impl Drop for WindowButton {
+    fn drop(&mut self) {
+        self.popover.unparent();
+    }
+}
+
+/// A taskbar listing open windows, in the order they were opened.
+pub struct WindowList {
+    container: Box,
+    scope: WindowListScope,
+    /// Windows in the order they were opened.
+    windows: Vec<WindowButton>,
+    workspaces: Vec<WorkspaceInfo>,
+    subscription: Option<Subscription<UIUpdateEventType, UIUpdateEvent>>,
+    /// Scope changes, e.g. after the configuration was reloaded.
+    scope_channel: (Sender<WindowListScope>, Receiver<WindowListScope>),
+}
+
+/// What the window list reacts to.
+enum WindowListUpdate {
+    Event(UIUpdateEvent),
+    Scope(WindowListScope),
+}
+
+impl WindowList {
+    pub fn new(scope: WindowListScope) -> Self {
+        let container = Box::new(gtk4::Orientation::Horizontal, 4);
+        container.add_css_class("window-list");
+        // Hidden until a window in scope shows up.
+        container.set_visible(false);
+
+        Self {
+            container,
+            scope,
+            windows: Vec::new(),
+            workspaces: Vec::new(),
+            subscription: None,
+            scope_channel: smol::channel::unbounded(),
+        }
+    }
+
+    pub fn export_widget(&self) -> &Box {
+        &self.container
+    }
+
+    /// Changes the scope of the list while it listens.
+    pub fn scope_sender(&self) -> Sender<WindowListScope> {
+        self.scope_channel.0.clone()
+    }
+
+    fn is_shown(&self, window: &WindowInfo) -> bool {
+        match self.scope {
+            WindowListScope::AllWorkspaces => true,
+            WindowListScope::CurrentWorkspace => {
+                let focused = self.workspaces.iter().find(|w| w.is_focused).map(|w| w.id);
+                focused.is_some() && window.workspace_id == focused
+            }
+        }
+    }
+
+    /// Shows the windows in scope and refreshes their move-to menus.
+    fn refresh(&self) {
+        for button in &self.windows {
+            button.container.set_visible(self.is_shown(&button.window));
+            button.update_menu(&self.workspaces);
+        }
+        let any_shown = self.windows.iter().any(|b| b.container.is_visible());
+        self.container.set_visible(any_shown);
+    }
+
+    /// Adds a button for `window`, or updates the existing one.
+    async fn window_opened_or_changed(&mut self, window: WindowInfo) {
+        let entry = match window.app_id.clone() {
+            Some(app_id) => desktop_entry::lookup(app_id).await,
+            None => None,
+        };
+        match self.windows.iter_mut().find(|b| b.window.id == window.id) {
+            Some(button) => button.update(window, entry),
+            None => {
+                let button = WindowButton::new(window, entry);
+                self.container.append(&button.container);
+                self.windows.push(button);
+            }
+        }
+    }
+
+    fn window_closed(&mut self, id: u64) {
+        if let Some(pos) = self.windows.iter().position(|b| b.window.id == id) {
+            let button = self.windows.remove(pos);
+            self.container.remove(&button.container);
+        }
+    }
+}
+
+impl EventHandler<UIUpdateEventType, UIUpdateEvent> for WindowList {
+    fn register_to_listener(&mut self, listener: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) {
+        self.subscription = Some(listener.subscribe(
+            vec![
+                UIUpdateEventType::WorkspaceChanged,
+                UIUpdateEventType::WindowOpened,
+                UIUpdateEventType::WindowChanged,
+                UIUpdateEventType::WindowClosed,
+            ],
+            SubscribeOptions::new(),
+        ));
+    }
+}
+
+impl EventHandlerMutExt<UIUpdateEventType, UIUpdateEvent> for WindowList {
+    async fn listen_mut(&mut self) {
+        let Some(subscription) = self.subscription.take() else {
+            return;
+        };
+        let scopes = self.scope_channel.1.clone();
+        loop {
+            let update = smol::future::or(
+                async { subscription.recv().await.map(WindowListUpdate::Event) },
+                async { scopes.recv().await.ok().map(WindowListUpdate::Scope) },
+            )
+            .await;
+            match update {
+                Some(WindowListUpdate::Event(event)) => match event {
+                    UIUpdateEvent::WorkspaceChanged { workspaces } => self.workspaces = workspaces,
+                    UIUpdateEvent::WindowOpened { window } | UIUpdateEvent::WindowChanged { window } => {
+                        self.window_opened_or_changed(window).await
+                    }
+                    UIUpdateEvent::WindowClosed { id } => self.window_closed(id),
+                    _ => continue,
+                },
+                Some(WindowListUpdate::Scope(scope)) => self.scope = scope,
+                None => return,
+            }
+            self.refresh();
+        }
+    }
+}
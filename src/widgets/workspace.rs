@@ -1,39 +1,106 @@
-use std::time::Duration;
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 use gtk4::{
-    Box, Button, Revealer, RevealerTransitionType,
-    prelude::{BoxExt, ButtonExt, WidgetExt},
-};
-use smol::{
-    Timer,
-    channel::{Receiver, Sender},
+    Box, Button, Label, Revealer, RevealerTransitionType, accessible,
+    prelude::{AccessibleExt, BoxExt, ButtonExt, WidgetExt},
 };
-
 use crate::service::{
-    event::{EventHandler, EventHandlerMutExt, EventListener, UIUpdateEvent, UIUpdateEventType},
-    niri::NiriService,
+    compositor::{self, CompositorCommand},
+    event::{EventHandler, EventHandlerMutExt, EventListener, OverflowPolicy, SubscribeOptions, Subscription, UIUpdateEvent, UIUpdateEventType, WorkspaceInfo},
 };
+use crate::utils::scroll::ScrollOptions;
+use crate::widgets::navigation::{connect_scroll_steps, send_steps};
+
+/// Duration of the crossfade when workspaces are added or removed.
+const FADE_DURATION: Duration = Duration::from_millis(300);
+
+/// The button of one workspace, showing its label and window count.
+struct WorkspaceButton {
+    button: Button,
+    label: Label,
+    windows: Label,
+}
+
+impl WorkspaceButton {
+    fn new(id: u64) -> Self {
+        let label = Label::new(None);
+        label.add_css_class("workspace-label");
+        let windows = Label::new(None);
+        windows.add_css_class("window-count");
+
+        let content = Box::new(gtk4::Orientation::Horizontal, 2);
+        content.set_halign(gtk4::Align::Center);
+        content.append(&label);
+        content.append(&windows);
+
+        let button = Button::builder().child(&content).build();
+        button.add_css_class("workspace-button");
+        button.connect_clicked(move |_| {
+            compositor::dispatch(CompositorCommand::FocusWorkspace { id });
+        });
+
+        Self {
+            button,
+            label,
+            windows,
+        }
+    }
+
+    fn update(&self, workspace: &WorkspaceInfo) {
+        self.label.set_text(&workspace.label());
+        self.windows.set_text(&workspace.windows.to_string());
+        self.windows.set_visible(workspace.windows > 0);
+        let description = match workspace.windows {
+            0 => format!("Workspace {}: no windows", workspace.label()),
+            1 => format!("Workspace {}: 1 window", workspace.label()),
+            n => format!("Workspace {}: {} windows", workspace.label(), n),
+        };
+        self.button.set_tooltip_text(Some(&description));
+        self.button
+            .update_property(&[accessible::Property::Label(&description)]);
+
+        if workspace.is_focused {
+            self.button.add_css_class("active");
+        } else {
+            self.button.remove_css_class("active");
+        }
+        if workspace.windows == 0 {
+            self.button.add_css_class("empty");
+        } else {
+            self.button.remove_css_class("empty");
+        }
+    }
+}
 
 pub struct Workspace {
     revealer: Revealer,
     container: Box,
     outer_container: Box,
-    current_focused: u8,
-    channel: (Sender<UIUpdateEvent>, Receiver<UIUpdateEvent>),
-    buttons: Vec<Button>,
+    subscription: Option<Subscription<UIUpdateEventType, UIUpdateEvent>>,
+    /// Buttons by workspace id, in the order they are shown.
+    buttons: Vec<(u64, WorkspaceButton)>,
 }
 
 impl Workspace {
-    pub fn new() -> Self {
+    /// `scroll` configures how scrolling over the strip switches workspaces.
+    pub fn new(scroll: Rc<Cell<ScrollOptions>>) -> Self {
         let outer_container = Box::new(gtk4::Orientation::Horizontal, 0);
         let workspace = Box::new(gtk4::Orientation::Horizontal, 5);
         workspace.add_css_class("workspace");
         outer_container.add_css_class("workspace-container");
         outer_container.append(&workspace);
+        connect_scroll_steps(&outer_container, scroll, |steps| {
+            send_steps(steps, CompositorCommand::FocusWorkspaceDown, CompositorCommand::FocusWorkspaceUp)
+        });
 
         let revealer = Revealer::builder()
             .transition_type(RevealerTransitionType::Crossfade)
-            .transition_duration(300)
+            .transition_duration(FADE_DURATION.as_millis() as u32)
             .reveal_child(false)
             .child(&outer_container)
             .build();
@@ -42,40 +109,46 @@ impl Workspace {
             revealer,
             container: workspace,
             outer_container,
-            current_focused: 0,
-            channel: smol::channel::unbounded(),
+            subscription: None,
             buttons: Vec::new(),
         }
     }
 
-    pub async fn increase_button(&mut self) {
-        let idx = self.buttons.len() as u8;
-        let button = Button::new();
-        button.add_css_class("workspace-button");
-        button.connect_clicked(move |_| {
-            smol::spawn(NiriService::send_command(niri_ipc::Request::Action(
-                niri_ipc::Action::FocusWorkspace {
-                    reference: niri_ipc::WorkspaceReferenceArg::Index(idx + 1),
-                },
-            )))
-            .detach();
-        });
-
-        // Add button after a delay to allow for animation
-        self.revealer.set_reveal_child(false);
-        Timer::after(Duration::from_millis(300)).await;
-        self.container.append(&button);
-        self.revealer.set_reveal_child(true);
-        self.buttons.push(button);
+    /// Whether `workspaces` adds or removes any button, as opposed to only
+    /// reordering them or moving the focus.
+    fn changes_buttons(&self, workspaces: &[WorkspaceInfo]) -> bool {
+        workspaces.len() != self.buttons.len()
+            || workspaces
+                .iter()
+                .any(|workspace| !self.buttons.iter().any(|(id, _)| *id == workspace.id))
     }
 
-    pub async fn decrease_button(&mut self) {
-        if let Some(button) = self.buttons.pop() {
-            // Remove button after a delay to allow for animation
-            self.revealer.set_reveal_child(false);
-            Timer::after(Duration::from_millis(300)).await;
-            self.container.remove(&button);
-            self.revealer.set_reveal_child(true);
+    /// Reconciles the buttons with `workspaces` by id.
+    ///
+    /// Buttons of workspaces that still exist are kept and only moved, so their
+    /// state survives reorders.
+    fn reconcile(&mut self, workspaces: &[WorkspaceInfo]) {
+        let mut existing: HashMap<u64, WorkspaceButton> = self.buttons.drain(..).collect();
+        let mut previous: Option<Button> = None;
+        for workspace in workspaces {
+            let button = match existing.remove(&workspace.id) {
+                Some(button) => {
+                    self.container.reorder_child_after(&button.button, previous.as_ref());
+                    button
+                }
+                None => {
+                    let button = WorkspaceButton::new(workspace.id);
+                    self.container.insert_child_after(&button.button, previous.as_ref());
+                    button
+                }
+            };
+            button.update(workspace);
+            previous = Some(button.button.clone());
+            self.buttons.push((workspace.id, button));
+        }
+
+        for button in existing.into_values() {
+            self.container.remove(&button.button);
         }
     }
 
@@ -86,38 +159,37 @@ impl Workspace {
 
 impl EventHandler<UIUpdateEventType, UIUpdateEvent> for Workspace {
     fn register_to_listener(&mut self, listener: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) {
-        listener
-            .register_event_handler(UIUpdateEventType::WorkspaceChanged, self.channel.0.clone());
+        self.subscription = Some(listener.subscribe(
+            vec![UIUpdateEventType::WorkspaceChanged],
+            // Every event carries the full state, so only the latest one matters.
+            SubscribeOptions::new()
+                .replay()
+                .bounded(1, OverflowPolicy::Coalesce),
+        ));
     }
 }
 
 impl EventHandlerMutExt<UIUpdateEventType, UIUpdateEvent> for Workspace {
     async fn listen_mut(&mut self) {
-        while let Ok(event) = self.channel.1.recv().await {
+        let Some(subscription) = self.subscription.take() else {
+            return;
+        };
+        while let Some(event) = subscription.recv().await {
             match event {
-                UIUpdateEvent::WorkspaceChanged { num, focused } => {
-                    if num > self.buttons.len() as u8 {
-                        for _ in 0..(num - self.buttons.len() as u8) {
-                            self.increase_button().await;
-                        }
-                    } else if num < self.buttons.len() as u8 {
-                        for _ in 0..(self.buttons.len() as u8 - num) {
-                            self.decrease_button().await;
-                        }
-                    }
-
-                    if focused != self.current_focused {
-                        if let Some(button) = self.buttons.get(focused as usize - 1) {
-                            button.add_css_class("active");
-                        }
-                        if let Some(button) = self
-                            .buttons
-                            .get(self.current_focused.wrapping_sub(1) as usize)
-                        {
-                            button.remove_css_class("active");
+                UIUpdateEvent::WorkspaceChanged { mut workspaces } => {
+                    if self.changes_buttons(&workspaces) {
+                        // Fade out once for all insertions and removals. Updates arriving
+                        // meanwhile replace this one without restarting the fade.
+                        self.revealer.set_reveal_child(false);
+                        let faded_out = Instant::now() + FADE_DURATION;
+                        while let Some(event) = subscription.recv_until(faded_out).await {
+                            if let UIUpdateEvent::WorkspaceChanged { workspaces: latest } = event {
+                                workspaces = latest;
+                            }
                         }
-                        self.current_focused = focused;
                     }
+                    self.reconcile(&workspaces);
+                    self.revealer.set_reveal_child(true);
                 }
                 _ => {}
             }
@@ -0,0 +1,269 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+
+use adw::{Application, ApplicationWindow};
+use gtk4::{
+    Box, EventControllerMotion, glib,
+    prelude::{DisplayExt, GtkWindowExt, MonitorExt, NativeExt, WidgetExt},
+};
+use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use tracing::debug;
+
+use crate::service::event::{EventHandler, EventHandlerMutExt, EventListener, SubscribeOptions, Subscription, UIUpdateEvent, UIUpdateEventType, WindowInfo, WorkspaceInfo};
+use crate::utils::visibility::{AutoHideMode, OutputFocus, Visibility};
+
+/// Height of the strip at the screen edge that reveals a hidden bar.
+const HOT_ZONE_HEIGHT: i32 = 2;
+/// How long the pointer may be away from the bar and the hot zone before the bar
+/// hides again, so moving from one onto the other does not flicker.
+const HIDE_DELAY: Duration = Duration::from_millis(300);
+
+struct Shared {
+    visibility: Visibility,
+    /// Connector of the monitor the bar was last mapped on.
+    output: Option<String>,
+    over_bar: bool,
+    over_hot_zone: bool,
+}
+
+/// Shows and hides the bar window. Cheap to clone, every clone controls the same bar.
+#[derive(Clone)]
+pub struct BarVisibility {
+    window: ApplicationWindow,
+    /// The strip revealing the bar on hover, only shown while auto-hide is on.
+    hot_zone: gtk4::Window,
+    shared: Rc<RefCell<Shared>>,
+}
+
+/// Puts the bar above windows, or below fullscreen ones in `AutoHideMode::Fullscreen`,
+/// and reserves space for it unless it overlaps windows.
+fn place(window: &ApplicationWindow, visibility: &Visibility) {
+    // Compositors draw fullscreen windows above the top layer, so there they cover
+    // the bar even where they are not reported as fullscreen, e.g. on niri.
+    window.set_layer(match visibility.mode() {
+        AutoHideMode::Fullscreen => Layer::Top,
+        AutoHideMode::Off | AutoHideMode::Always => Layer::Overlay,
+    });
+    if visibility.reserves_space() {
+        window.auto_exclusive_zone_enable();
+    } else {
+        window.set_exclusive_zone(0);
+    }
+}
+
+impl BarVisibility {
+    fn new(application: &Application, window: &ApplicationWindow, mode: AutoHideMode) -> Self {
+        let visibility = Visibility::new(mode);
+        place(window, &visibility);
+        let this = Self {
+            window: window.clone(),
+            hot_zone: gtk4::Window::builder().application(application).build(),
+            shared: Rc::new(RefCell::new(Shared {
+                visibility,
+                output: None,
+                over_bar: false,
+                over_hot_zone: false,
+            })),
+        };
+
+        let mapped = this.clone();
+        window.connect_map(move |window| {
+            let output = window
+                .surface()
+                .and_then(|surface| window.display().monitor_at_surface(&surface))
+                .and_then(|monitor| monitor.connector())
+                .map(|connector| connector.to_string());
+            if output.is_some() {
+                mapped.shared.borrow_mut().output = output;
+            }
+        });
+
+        let motion = EventControllerMotion::new();
+        let entered = this.clone();
+        motion.connect_enter(move |_, _, _| entered.set_pointer(|shared| shared.over_bar = true));
+        let left = this.clone();
+        motion.connect_leave(move |_| left.set_pointer(|shared| shared.over_bar = false));
+        window.add_controller(motion);
+
+        set_up_hot_zone(&this);
+        this
+    }
+
+    /// Shows the hot zone, and the bar unless it starts out hidden.
+    pub fn present(&self) {
+        if self.shared.borrow().visibility.uses_hot_zone() {
+            self.hot_zone.present();
+        }
+        if self.is_visible() {
+            self.window.present();
+        }
+    }
+
+    /// Switches to `mode`, e.g. after the configuration was reloaded.
+    pub fn set_mode(&self, mode: AutoHideMode) {
+        let mut shared = self.shared.borrow_mut();
+        if shared.visibility.mode() == mode {
+            return;
+        }
+        shared.visibility.set_mode(mode);
+        place(&self.window, &shared.visibility);
+        let uses_hot_zone = shared.visibility.uses_hot_zone();
+        drop(shared);
+
+        if uses_hot_zone {
+            self.hot_zone.present();
+        } else {
+            self.hot_zone.set_visible(false);
+        }
+        self.apply();
+    }
+
+    fn apply(&self) {
+        let visible = self.shared.borrow().visibility.is_visible();
+        if self.window.is_visible() != visible {
+            debug!("Bar {}", if visible { "shown" } else { "hidden" });
+            self.window.set_visible(visible);
+        }
+    }
+
+    /// Records where the pointer is. Entering reveals the bar right away, leaving
+    /// hides it after `HIDE_DELAY` unless the pointer came back.
+    fn set_pointer(&self, update: impl FnOnce(&mut Shared)) {
+        let mut shared = self.shared.borrow_mut();
+        update(&mut shared);
+        if shared.over_bar || shared.over_hot_zone {
+            shared.visibility.set_hovered(true);
+            drop(shared);
+            self.apply();
+            return;
+        }
+        drop(shared);
+
+        let this = self.clone();
+        glib::timeout_add_local_once(HIDE_DELAY, move || {
+            let mut shared = this.shared.borrow_mut();
+            let hovered = shared.over_bar || shared.over_hot_zone;
+            shared.visibility.set_hovered(hovered);
+            drop(shared);
+            this.apply();
+        });
+    }
+
+    fn set_fullscreen(&self, fullscreen: bool) {
+        self.shared.borrow_mut().visibility.set_fullscreen(fullscreen);
+        self.apply();
+    }
+
+    fn output(&self) -> Option<String> {
+        self.shared.borrow().output.clone()
+    }
+
+    /// Shows a hidden bar or hides a shown one, see `Visibility::toggle`.
+    pub fn toggle(&self) {
+        self.shared.borrow_mut().visibility.toggle();
+        self.apply();
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.shared.borrow().visibility.is_visible()
+    }
+
+    /// Calls `f` with the new visibility whenever the bar is shown or hidden.
+    pub fn connect_changed(&self, f: impl Fn(bool) + 'static) {
+        self.window.connect_visible_notify(move |window| f(window.is_visible()));
+    }
+}
+
+/// Turns the hot zone into an invisible strip along the top edge that reveals the
+/// bar on hover.
+fn set_up_hot_zone(visibility: &BarVisibility) {
+    let zone = &visibility.hot_zone;
+    zone.init_layer_shell();
+    zone.set_namespace("molyuu-bar-hot-zone");
+    zone.set_layer(Layer::Overlay);
+    // Stay at the very edge, above the space other panels reserve.
+    zone.set_exclusive_zone(-1);
+    for edge in [Edge::Left, Edge::Right, Edge::Top] {
+        zone.set_anchor(edge, true);
+    }
+    zone.set_css_classes(&["hot-zone"]);
+
+    let strip = Box::new(gtk4::Orientation::Horizontal, 0);
+    strip.set_size_request(-1, HOT_ZONE_HEIGHT);
+    zone.set_child(Some(&strip));
+
+    let motion = EventControllerMotion::new();
+    let entered = visibility.clone();
+    motion.connect_enter(move |_, _, _| entered.set_pointer(|shared| shared.over_hot_zone = true));
+    let left = visibility.clone();
+    motion.connect_leave(move |_| left.set_pointer(|shared| shared.over_hot_zone = false));
+    zone.add_controller(motion);
+}
+
+/// Hides the bar according to its `AutoHideMode`, following the workspaces and
+/// windows the compositor reports.
+pub struct AutoHide {
+    visibility: BarVisibility,
+    subscription: Option<Subscription<UIUpdateEventType, UIUpdateEvent>>,
+    workspaces: Vec<WorkspaceInfo>,
+    windows: HashMap<u64, WindowInfo>,
+    focus: OutputFocus,
+}
+
+impl AutoHide {
+    pub fn new(application: &Application, window: &ApplicationWindow, mode: AutoHideMode) -> Self {
+        Self {
+            visibility: BarVisibility::new(application, window, mode),
+            subscription: None,
+            workspaces: Vec::new(),
+            windows: HashMap::new(),
+            focus: OutputFocus::new(),
+        }
+    }
+
+    pub fn visibility(&self) -> &BarVisibility {
+        &self.visibility
+    }
+
+    fn update(&mut self, event: UIUpdateEvent) {
+        match event {
+            UIUpdateEvent::WorkspaceChanged { workspaces } => self.workspaces = workspaces,
+            UIUpdateEvent::WindowOpened { window } | UIUpdateEvent::WindowChanged { window } => {
+                self.windows.insert(window.id, window);
+            }
+            UIUpdateEvent::WindowClosed { id } => {
+                self.windows.remove(&id);
+            }
+            _ => return,
+        }
+        let output = self.visibility.output();
+        let fullscreen = self.focus.fullscreen_on_output(&self.workspaces, &self.windows, output.as_deref());
+        self.visibility.set_fullscreen(fullscreen);
+    }
+}
+
+impl EventHandler<UIUpdateEventType, UIUpdateEvent> for AutoHide {
+    /// Follows the windows in every mode, as a reload may switch to
+    /// `AutoHideMode::Fullscreen`.
+    fn register_to_listener(&mut self, listener: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) {
+        self.subscription = Some(listener.subscribe(
+            vec![
+                UIUpdateEventType::WorkspaceChanged,
+                UIUpdateEventType::WindowOpened,
+                UIUpdateEventType::WindowChanged,
+                UIUpdateEventType::WindowClosed,
+            ],
+            SubscribeOptions::new().replay(),
+        ));
+    }
+}
+
+impl EventHandlerMutExt<UIUpdateEventType, UIUpdateEvent> for AutoHide {
+    async fn listen_mut(&mut self) {
+        let Some(subscription) = self.subscription.take() else {
+            return;
+        };
+        while let Some(event) = subscription.recv().await {
+            self.update(event);
+        }
+    }
+}
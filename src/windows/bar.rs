@@ -1,25 +1,42 @@
+use std::{cell::Cell, rc::Rc};
+
 use adw::prelude::AdwApplicationWindowExt;
 use adw::{Application, ApplicationWindow, prelude::*};
-use gtk4::CenterBox;
-use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use gtk4::{Box, CenterBox};
+use gtk4_layer_shell::{Edge, KeyboardMode, LayerShell};
 
 use crate::service::event::{EventHandler, EventHandlerMutExt, EventListener, UIUpdateEvent, UIUpdateEventType};
+use crate::utils::config::Config;
 use crate::widgets::current_window::CurrentWindow;
+use crate::widgets::keyboard_layout::KeyboardLayout;
+use crate::widgets::message::Message;
 use crate::widgets::panel::Panel;
+use crate::widgets::window_list::WindowList;
 use crate::widgets::workspace::Workspace;
+use crate::windows::auto_hide::{AutoHide, BarVisibility};
+use crate::windows::config::BarConfig;
+use crate::windows::control::BarControl;
 
 pub struct Taskbar {
     window: ApplicationWindow,
     container: CenterBox,
+    visibility: BarVisibility,
+    panel: Panel,
+    config: BarConfig,
+    message: Message,
 }
 
 impl Taskbar {
-    pub fn new(application: &Application, service: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) -> Self {
+    pub fn new(
+        application: &Application,
+        service: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>,
+        config: &Config,
+    ) -> Self {
         let window = ApplicationWindow::new(application);
 
         window.init_layer_shell();
-        window.set_layer(Layer::Overlay);
-        window.auto_exclusive_zone_enable();
+        // Take keyboard focus when clicked, so the modules can be navigated with Tab.
+        window.set_keyboard_mode(KeyboardMode::OnDemand);
 
         let anchors = [(Edge::Left, true), (Edge::Right, true), (Edge::Top, true)];
 
@@ -27,16 +44,39 @@ impl Taskbar {
             window.set_anchor(edge, anchor);
         }
 
+        let mut auto_hide = AutoHide::new(application, &window, config.options.auto_hide);
+        auto_hide.register_to_listener(service);
+        let visibility = auto_hide.visibility().clone();
+        smol::spawn(gtk4::glib::spawn_future_local(async move {
+            auto_hide.listen_mut().await;
+        }))
+        .detach();
+
         let container = CenterBox::new();
-        let mut workspace = Workspace::new();
-        let mut current_window = CurrentWindow::new();
+        let scroll = Rc::new(Cell::new(config.options.scroll));
+        let mut workspace = Workspace::new(scroll.clone());
+        let mut current_window = CurrentWindow::new(scroll.clone());
+        let mut window_list = WindowList::new(config.options.window_list_scope);
+        let config = BarConfig::new(config, visibility.clone(), window_list.scope_sender(), scroll);
+        let mut keyboard_layout = KeyboardLayout::new();
         let panel = Panel::new();
         workspace.register_to_listener(service);
         current_window.register_to_listener(service);
+        window_list.register_to_listener(service);
+        keyboard_layout.register_to_listener(service);
+
+        let start = Box::new(gtk4::Orientation::Horizontal, 0);
+        start.append(workspace.export_widget());
+        start.append(window_list.export_widget());
         container.set_valign(gtk4::Align::Start);
-        container.set_start_widget(Some(workspace.export_widget()));
-        container.set_center_widget(Some(current_window.export_widget()));
-        container.set_end_widget(Some(panel.export_widget()));
+        container.set_start_widget(Some(&start));
+        let message = Message::new(current_window.export_widget());
+        container.set_center_widget(Some(message.export_widget()));
+
+        let end = Box::new(gtk4::Orientation::Horizontal, 0);
+        end.append(keyboard_layout.export_widget());
+        end.append(panel.export_widget());
+        container.set_end_widget(Some(&end));
         smol::spawn(gtk4::glib::spawn_future_local(async move {
             workspace.listen_mut().await;
         }))
@@ -45,6 +85,14 @@ impl Taskbar {
             current_window.listen_mut().await;
         }))
         .detach();
+        smol::spawn(gtk4::glib::spawn_future_local(async move {
+            window_list.listen_mut().await;
+        }))
+        .detach();
+        smol::spawn(gtk4::glib::spawn_future_local(async move {
+            keyboard_layout.listen_mut().await;
+        }))
+        .detach();
 
         let container_clone = container.clone();
         window.set_css_classes(&["taskbar"]);
@@ -56,10 +104,37 @@ impl Taskbar {
             );
         });
 
-        Taskbar { window, container }
+        Taskbar {
+            window,
+            container,
+            visibility,
+            panel,
+            config,
+            message,
+        }
+    }
+
+    pub fn visibility(&self) -> &BarVisibility {
+        &self.visibility
+    }
+
+    /// Handles `molyuu-bar msg` requests for this bar.
+    pub fn control(&self) -> BarControl {
+        BarControl::new(
+            self.visibility.clone(),
+            self.panel.network_popover().clone(),
+            self.panel.network_commands().cloned(),
+            self.config.clone(),
+            self.message.clone(),
+        )
+    }
+
+    pub fn panel(&self) -> &Panel {
+        &self.panel
     }
 
-    pub fn export_widget(&self) -> &ApplicationWindow {
-        &self.window
+    /// Shows the bar, or only its hot zone when it starts out hidden.
+    pub fn present(&self) {
+        self.visibility.present();
     }
 }
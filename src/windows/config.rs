@@ -0,0 +1,77 @@
+use std::{cell::Cell, rc::Rc};
+
+use gtk4::{CssProvider, gdk};
+use smol::channel::Sender;
+use tracing::warn;
+
+use crate::utils::config::{self, BarOptions, Config};
+use crate::utils::scroll::ScrollOptions;
+use crate::widgets::window_list::WindowListScope;
+use crate::windows::auto_hide::BarVisibility;
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Reads the configuration at startup. If the config directory cannot be read,
+/// only the options in the environment apply.
+pub fn read() -> Config {
+    let dir = config::config_dir(env_var);
+    Config::load(dir.as_deref(), env_var).unwrap_or_else(|e| {
+        warn!("Failed to read the configuration: {}", e);
+        Config {
+            options: BarOptions::read(env_var),
+            stylesheet: String::new(),
+        }
+    })
+}
+
+/// Applies the configuration to the parts of a running bar that follow it.
+/// Cheap to clone, every clone applies to the same bar.
+#[derive(Clone)]
+pub struct BarConfig {
+    stylesheet: CssProvider,
+    visibility: BarVisibility,
+    window_list_scope: Sender<WindowListScope>,
+    scroll: Rc<Cell<ScrollOptions>>,
+}
+
+impl BarConfig {
+    /// Loads the user stylesheet of `config` on top of the built-in one.
+    pub fn new(
+        config: &Config,
+        visibility: BarVisibility,
+        window_list_scope: Sender<WindowListScope>,
+        scroll: Rc<Cell<ScrollOptions>>,
+    ) -> Self {
+        let stylesheet = CssProvider::new();
+        stylesheet.load_from_string(&config.stylesheet);
+        match gdk::Display::default() {
+            Some(display) => gtk4::style_context_add_provider_for_display(
+                &display,
+                &stylesheet,
+                gtk4::STYLE_PROVIDER_PRIORITY_USER,
+            ),
+            None => warn!("No display to apply the user stylesheet to"),
+        }
+        Self {
+            stylesheet,
+            visibility,
+            window_list_scope,
+            scroll,
+        }
+    }
+
+    /// Reads the `MOLYUU_BAR_*` options and the user stylesheet again and applies
+    /// them. Keeps the current configuration if they cannot be read.
+    pub fn reload(&self) -> Result<(), String> {
+        let dir = config::config_dir(env_var);
+        let config = Config::load(dir.as_deref(), env_var)
+            .map_err(|e| format!("Failed to read the configuration: {}", e))?;
+        self.stylesheet.load_from_string(&config.stylesheet);
+        self.visibility.set_mode(config.options.auto_hide);
+        let _ = self.window_list_scope.try_send(config.options.window_list_scope);
+        self.scroll.set(config.options.scroll);
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use gtk4::{Popover, glib, prelude::PopoverExt};
+use serde_json::{Value, json};
+use smol::channel::Sender;
+use smol_timeout::TimeoutExt;
+use tracing::warn;
+
+use crate::service::{
+    compositor,
+    control::{
+        ControlHandler, ControlReply, ControlRequest,
+        dbus::{self, BarBus, BarState, BarStateFeed},
+        state::{network_state, query_compositor},
+    },
+    event::{EventHandler, EventListener, UIUpdateEvent, UIUpdateEventType},
+    network::endpoints::event::NetworkServiceRequest,
+};
+use crate::widgets::message::Message;
+use crate::windows::{auto_hide::BarVisibility, bar::Taskbar, config::BarConfig};
+
+/// How long `dump-state` waits for the network service.
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Carries out requests from `molyuu-bar msg` and the session bus on the bar.
+pub struct BarControl {
+    visibility: BarVisibility,
+    network_popover: Popover,
+    network_commands: Option<Sender<NetworkServiceRequest>>,
+    config: BarConfig,
+    message: Message,
+}
+
+impl BarControl {
+    pub fn new(
+        visibility: BarVisibility,
+        network_popover: Popover,
+        network_commands: Option<Sender<NetworkServiceRequest>>,
+        config: BarConfig,
+        message: Message,
+    ) -> Self {
+        Self {
+            visibility,
+            network_popover,
+            network_commands,
+            config,
+            message,
+        }
+    }
+
+    fn open_popover(&self, name: &str) -> ControlReply {
+        let popover = match name {
+            "network" => &self.network_popover,
+            _ => return Err(format!("Unknown popover {:?}, expected network", name)),
+        };
+        // A popover cannot open on a hidden bar.
+        if !self.visibility.is_visible() {
+            self.visibility.toggle();
+        }
+        popover.popup();
+        Ok(Value::Null)
+    }
+
+    async fn network(&self) -> Value {
+        let Some(commands) = &self.network_commands else {
+            return Value::Null;
+        };
+        let (sender, receiver) = smol::channel::bounded(1);
+        if commands
+            .send(NetworkServiceRequest::GetSnapshot { channel: sender })
+            .await
+            .is_err()
+        {
+            warn!("Network service is gone");
+            return Value::Null;
+        }
+        match receiver.recv().timeout(SNAPSHOT_TIMEOUT).await {
+            Some(Ok(snapshot)) => network_state(&snapshot),
+            _ => {
+                warn!("Network service did not send a snapshot");
+                Value::Null
+            }
+        }
+    }
+
+    async fn dump_state(&self) -> ControlReply {
+        let compositor = match compositor::current() {
+            Some(compositor) => query_compositor(&*compositor).await.unwrap_or_else(|e| {
+                warn!("Failed to query the compositor: {}", e);
+                Value::Null
+            }),
+            None => Value::Null,
+        };
+        Ok(json!({
+            "bar": json!({ "visible": self.visibility.is_visible() }),
+            "compositor": compositor,
+            "network": self.network().await,
+        }))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl ControlHandler for BarControl {
+    async fn handle(&self, request: ControlRequest) -> ControlReply {
+        match request {
+            ControlRequest::ToggleVisibility => {
+                self.visibility.toggle();
+                Ok(json!({ "visible": self.visibility.is_visible() }))
+            }
+            ControlRequest::OpenPopover { name } => self.open_popover(&name),
+            ControlRequest::ShowMessage { text, timeout_ms } => {
+                self.message.show(&text, Duration::from_millis(timeout_ms.into()));
+                Ok(Value::Null)
+            }
+            ControlRequest::ReloadConfig => self.config.reload().map(|()| Value::Null),
+            ControlRequest::DumpState => self.dump_state().await,
+        }
+    }
+}
+
+/// Exports `taskbar` on the session bus, following the focused window on `compositor`
+/// and the network service of the taskbar's panel.
+pub fn serve_session_bus(taskbar: &Taskbar, compositor: &mut impl EventListener<UIUpdateEventType, UIUpdateEvent>) {
+    let mut feed = BarStateFeed::new();
+    feed.register_to_listener(compositor);
+    feed.register_to_listener(&mut taskbar.panel().network_events().clone());
+
+    let (calls, call_receiver) = smol::channel::unbounded();
+    let control = taskbar.control();
+    glib::spawn_future_local(async move {
+        dbus::answer_calls(call_receiver, &control).await;
+    });
+
+    let visibility = taskbar.visibility().clone();
+    glib::spawn_future_local(async move {
+        let state = BarState {
+            visible: visibility.is_visible(),
+            ..Default::default()
+        };
+        let builder = match zbus::connection::Builder::session() {
+            Ok(builder) => builder,
+            Err(e) => {
+                warn!("Failed to connect to the session bus: {}", e);
+                return;
+            }
+        };
+        let bus = match BarBus::serve(builder, state, calls).await {
+            Ok(bus) => bus,
+            Err(e) => {
+                warn!("Failed to export the bar on the session bus: {}", e);
+                return;
+            }
+        };
+
+        let visibility_bus = bus.clone();
+        visibility.connect_changed(move |visible| {
+            let bus = visibility_bus.clone();
+            glib::spawn_future_local(async move {
+                if let Err(e) = bus.update(|state| state.visible = visible).await {
+                    warn!("Failed to update the bar state on the bus: {}", e);
+                }
+            });
+        });
+        feed.listen(bus).await;
+    });
+}
@@ -1 +1,4 @@
+pub mod auto_hide;
 pub mod bar;
+pub mod config;
+pub mod control;